@@ -0,0 +1,103 @@
+//! Compares deserializing a large map eagerly and lazily, when only a few
+//! entries are accessed.
+//!
+//! Run with:
+//!
+//! ```bash
+//! cargo run --release --example untagged_deserialize_map_lazy --features lazy
+//! ```
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use type_reg::untagged::{LazyTypeMap, TypeMap, TypeReg};
+
+/// Number of entries in the map.
+const ENTRY_COUNT: usize = 2_000;
+/// Number of times to repeat each measurement.
+const ITERATIONS: u32 = 20;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Task {
+    name: String,
+    steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Step {
+    command: String,
+    args: Vec<String>,
+    timeout_ms: u64,
+}
+
+/// Expensive to deserialize, as each value is validated and parsed.
+#[derive(Clone, Debug, Serialize)]
+struct Checksum(Vec<u8>);
+
+impl<'de> Deserialize<'de> for Checksum {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let hex = String::deserialize(deserializer)?;
+        (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map(Checksum)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+fn main() {
+    let mut type_reg = TypeReg::<String>::new();
+    let mut json = String::from("{");
+    (0..ENTRY_COUNT).for_each(|index| {
+        let separator = if index == 0 { "" } else { "," };
+        if index % 2 == 0 {
+            let key = format!("task_{index}");
+            type_reg.register::<Task>(key.clone());
+            let steps = (0..10)
+                .map(|step| {
+                    format!(
+                        r#"{{"command":"step_{step}","args":["--a","--b","--c"],"timeout_ms":{step}}}"#
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            json.push_str(&format!(
+                r#"{separator}"{key}":{{"name":"{key}","steps":[{steps}]}}"#
+            ));
+        } else {
+            let key = format!("checksum_{index}");
+            type_reg.register::<Checksum>(key.clone());
+            json.push_str(&format!(r#"{separator}"{key}":"{}""#, "ab".repeat(2048)));
+        }
+    });
+    json.push('}');
+
+    let eager = measure(|| {
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        let type_map: TypeMap<String> = type_reg.deserialize_map(&mut deserializer).unwrap();
+        assert!(type_map.get::<Task, _>("task_0").is_some());
+        assert!(type_map.get::<Checksum, _>("checksum_1").is_some());
+    });
+    let lazy = measure(|| {
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        let lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(&mut deserializer).unwrap();
+        assert!(lazy_type_map.get::<Task, _>("task_0").is_some());
+        assert!(lazy_type_map.get::<Checksum, _>("checksum_1").is_some());
+    });
+
+    println!("entries:  {ENTRY_COUNT}, accessed: 2");
+    println!("eager:    {eager:?}");
+    println!("lazy:     {lazy:?}");
+}
+
+/// Returns the mean duration of running `f`.
+fn measure(f: impl Fn()) -> Duration {
+    let start = Instant::now();
+    (0..ITERATIONS).for_each(|_| f());
+    start.elapsed() / ITERATIONS
+}
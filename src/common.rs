@@ -1,3 +1,17 @@
-pub use self::unknown_entries::{UnknownEntries, UnknownEntriesNone, UnknownEntriesSome};
+pub use self::{
+    entry_kind::EntryKind,
+    get_result::GetResult,
+    unknown_entries::{UnknownEntries, UnknownEntriesNone, UnknownEntriesSome},
+};
 
+pub(crate) use self::debug_map_sorted::{DebugMapSorted, DebugTypeMapSorted};
+
+#[cfg(feature = "untagged")]
+pub(crate) use self::depth_limit::DepthLimitDeserializer;
+
+mod debug_map_sorted;
+#[cfg(feature = "untagged")]
+mod depth_limit;
+mod entry_kind;
+mod get_result;
 mod unknown_entries;
@@ -0,0 +1,58 @@
+use std::fmt::{self, Debug};
+
+/// Formats entries as a map sorted by key.
+///
+/// This makes the `Debug` output deterministic regardless of the underlying
+/// map's iteration order.
+pub(crate) struct DebugMapSorted<'k, K, V>(Vec<(&'k K, V)>);
+
+impl<'k, K, V> DebugMapSorted<'k, K, V>
+where
+    K: Ord,
+{
+    /// Returns a new `DebugMapSorted` with the given entries sorted by key.
+    pub(crate) fn new<I>(entries: I) -> Self
+    where
+        I: Iterator<Item = (&'k K, V)>,
+    {
+        let mut entries = entries.collect::<Vec<_>>();
+        entries.sort_by_key(|(k, _)| *k);
+        Self(entries)
+    }
+}
+
+impl<K, V> Debug for DebugMapSorted<'_, K, V>
+where
+    K: Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+/// Formats a map with unknown entries, with both maps sorted by key.
+pub(crate) struct DebugTypeMapSorted<'k, K, V, ValueT> {
+    /// Name of the map type.
+    pub(crate) name: &'static str,
+    /// Known entries.
+    pub(crate) inner: DebugMapSorted<'k, K, V>,
+    /// Unknown entries.
+    pub(crate) unknown_entries: DebugMapSorted<'k, K, ValueT>,
+}
+
+impl<K, V, ValueT> Debug for DebugTypeMapSorted<'_, K, V, ValueT>
+where
+    K: Debug,
+    V: Debug,
+    ValueT: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(self.name)
+            .field("inner", &self.inner)
+            .field("unknown_entries", &self.unknown_entries)
+            .finish()
+    }
+}
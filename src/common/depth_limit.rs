@@ -0,0 +1,462 @@
+use std::fmt;
+
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+
+/// Returns the error for when the nesting of values exceeds the depth limit.
+fn depth_limit_exceeded<E>(depth_limit: usize) -> E
+where
+    E: de::Error,
+{
+    E::custom(format_args!(
+        "Value nesting exceeds the depth limit of {depth_limit}."
+    ))
+}
+
+/// Tracks how deeply values are nested while deserializing.
+///
+/// Sequences, maps, and enums each count as one level of nesting.
+#[derive(Clone, Copy)]
+struct Depth {
+    /// Number of levels that may still be entered.
+    remaining: usize,
+    /// Maximum number of levels, used in the error message.
+    limit: usize,
+}
+
+impl Depth {
+    /// Returns the depth one level down, or an error if the limit is reached.
+    fn enter<E>(self) -> Result<Self, E>
+    where
+        E: de::Error,
+    {
+        match self.remaining.checked_sub(1) {
+            Some(remaining) => Ok(Self {
+                remaining,
+                limit: self.limit,
+            }),
+            None => Err(depth_limit_exceeded(self.limit)),
+        }
+    }
+}
+
+/// Deserializer that returns an error when values are nested deeper than a
+/// limit.
+///
+/// This guards against stack overflows from recursive types on untrusted
+/// input.
+pub(crate) struct DepthLimitDeserializer<D> {
+    /// The wrapped deserializer.
+    inner: D,
+    /// Current nesting depth.
+    depth: Depth,
+}
+
+impl<D> DepthLimitDeserializer<D> {
+    /// Returns a new `DepthLimitDeserializer` which allows values to be nested
+    /// up to `depth_limit` levels, including the outermost value.
+    pub(crate) fn new(inner: D, depth_limit: usize) -> Self {
+        Self {
+            inner,
+            depth: Depth {
+                remaining: depth_limit,
+                limit: depth_limit,
+            },
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$method($($arg,)* DepthLimitVisitor {
+                    inner: visitor,
+                    depth: self.depth,
+                })
+            }
+        )*
+    };
+}
+
+impl<'de, D> Deserializer<'de> for DepthLimitDeserializer<D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Visitor that passes the nesting depth on to nested values.
+struct DepthLimitVisitor<V> {
+    /// The wrapped visitor.
+    inner: V,
+    /// Current nesting depth.
+    depth: Depth,
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V> Visitor<'de> for DepthLimitVisitor<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.visit_some(DepthLimitDeserializer {
+            inner: deserializer,
+            depth: self.depth,
+        })
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.visit_newtype_struct(DepthLimitDeserializer {
+            inner: deserializer,
+            depth: self.depth,
+        })
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let depth = self.depth.enter()?;
+        self.inner.visit_seq(DepthLimitAccess { inner: seq, depth })
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let depth = self.depth.enter()?;
+        self.inner.visit_map(DepthLimitAccess { inner: map, depth })
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let depth = self.depth.enter()?;
+        self.inner
+            .visit_enum(DepthLimitAccess { inner: data, depth })
+    }
+}
+
+/// `SeqAccess`, `MapAccess`, `EnumAccess`, or `VariantAccess` that passes the
+/// nesting depth on to nested values.
+struct DepthLimitAccess<A> {
+    /// The wrapped access.
+    inner: A,
+    /// Nesting depth of the values within the access.
+    depth: Depth,
+}
+
+impl<'de, A> SeqAccess<'de> for DepthLimitAccess<A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.next_element_seed(DepthLimitSeed {
+            inner: seed,
+            depth: self.depth,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A> MapAccess<'de> for DepthLimitAccess<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.inner.next_key_seed(DepthLimitSeed {
+            inner: seed,
+            depth: self.depth,
+        })
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.inner.next_value_seed(DepthLimitSeed {
+            inner: seed,
+            depth: self.depth,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A> EnumAccess<'de> for DepthLimitAccess<A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = DepthLimitAccess<A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let depth = self.depth;
+        let (value, variant) = self
+            .inner
+            .variant_seed(DepthLimitSeed { inner: seed, depth })?;
+        Ok((
+            value,
+            DepthLimitAccess {
+                inner: variant,
+                depth,
+            },
+        ))
+    }
+}
+
+impl<'de, A> VariantAccess<'de> for DepthLimitAccess<A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.inner.newtype_variant_seed(DepthLimitSeed {
+            inner: seed,
+            depth: self.depth,
+        })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.tuple_variant(
+            len,
+            DepthLimitVisitor {
+                inner: visitor,
+                depth: self.depth,
+            },
+        )
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.struct_variant(
+            fields,
+            DepthLimitVisitor {
+                inner: visitor,
+                depth: self.depth,
+            },
+        )
+    }
+}
+
+/// `DeserializeSeed` that passes the nesting depth on to the deserializer.
+struct DepthLimitSeed<S> {
+    /// The wrapped seed.
+    inner: S,
+    /// Current nesting depth.
+    depth: Depth,
+}
+
+impl<'de, S> DeserializeSeed<'de> for DepthLimitSeed<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.inner.deserialize(DepthLimitDeserializer {
+            inner: deserializer,
+            depth: self.depth,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::DepthLimitDeserializer;
+
+    #[test]
+    fn deserializes_value_within_depth_limit() {
+        let mut deserializer = serde_json::Deserializer::from_str(r#"[[1, 2], {"a": [3]}]"#);
+        let deserializer = DepthLimitDeserializer::new(&mut deserializer, 3);
+
+        let value = serde_json::Value::deserialize(deserializer).unwrap();
+
+        assert_eq!(serde_json::json!([[1, 2], {"a": [3]}]), value);
+    }
+
+    #[test]
+    fn returns_error_when_value_exceeds_depth_limit() {
+        let mut deserializer = serde_json::Deserializer::from_str(r#"[[1, 2], {"a": [3]}]"#);
+        let deserializer = DepthLimitDeserializer::new(&mut deserializer, 2);
+
+        let error = serde_json::Value::deserialize(deserializer).unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("Value nesting exceeds the depth limit of 2."),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn counts_enum_as_one_level() {
+        let serialized = r#"{"Node":{"Node":"Leaf"}}"#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let tree = Tree::deserialize(DepthLimitDeserializer::new(&mut deserializer, 3)).unwrap();
+        assert_eq!(Tree::Node(Box::new(Tree::Node(Box::new(Tree::Leaf)))), tree);
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let error =
+            Tree::deserialize(DepthLimitDeserializer::new(&mut deserializer, 2)).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Value nesting exceeds the depth limit of 2."),
+            "{error}"
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Tree {
+        Leaf,
+        Node(Box<Tree>),
+    }
+}
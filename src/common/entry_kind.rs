@@ -0,0 +1,9 @@
+/// Where an entry is stored within a `TypeMap` that tracks unknown entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    /// The entry's type was registered, and its value was deserialized.
+    Known,
+    /// The entry's type was not registered, and its value was stored as an
+    /// unknown entry.
+    Unknown,
+}
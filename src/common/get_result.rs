@@ -0,0 +1,27 @@
+use crate::TypeNameLit;
+
+/// Result of looking up a value of a given type in a `TypeMap`.
+///
+/// Unlike `Option`, this distinguishes between a key that is absent, and a
+/// key whose value is a different type.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GetResult<T> {
+    /// There is an entry for the key, with the requested type.
+    Present(T),
+    /// There is an entry for the key, but its value is a different type.
+    ///
+    /// This contains the type name of the stored value.
+    WrongType(TypeNameLit),
+    /// There is no entry for the key.
+    Absent,
+}
+
+impl<T> GetResult<T> {
+    /// Returns the value if it is present, discarding the reason it is not.
+    pub fn ok(self) -> Option<T> {
+        match self {
+            GetResult::Present(t) => Some(t),
+            GetResult::WrongType(_) | GetResult::Absent => None,
+        }
+    }
+}
@@ -12,9 +12,9 @@ pub struct UnknownEntriesSome<ValueT>(PhantomData<ValueT>);
 /// format's generic value type.
 pub trait UnknownEntries {
     #[cfg(not(feature = "debug"))]
-    type ValueT: Clone + PartialEq + Eq;
+    type ValueT: Clone;
     #[cfg(feature = "debug")]
-    type ValueT: Clone + std::fmt::Debug + PartialEq + Eq;
+    type ValueT: Clone + std::fmt::Debug;
 }
 
 impl UnknownEntries for UnknownEntriesNone {
@@ -24,7 +24,7 @@ impl UnknownEntries for UnknownEntriesNone {
 #[cfg(not(feature = "debug"))]
 impl<ValueT> UnknownEntries for UnknownEntriesSome<ValueT>
 where
-    ValueT: Clone + Eq,
+    ValueT: Clone,
 {
     type ValueT = ValueT;
 }
@@ -32,7 +32,7 @@ where
 #[cfg(feature = "debug")]
 impl<ValueT> UnknownEntries for UnknownEntriesSome<ValueT>
 where
-    ValueT: Clone + std::fmt::Debug + Eq,
+    ValueT: Clone + std::fmt::Debug,
 {
     type ValueT = ValueT;
 }
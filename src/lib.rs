@@ -20,6 +20,46 @@
 //!
 //! # Use insertion order for TypeMap and TypeReg iteration order.
 //! type_reg = { version = "0.8.0", features = ["ordered"] }
+//!
+//! # Allow `untagged::TypeReg` to deserialize both untagged and
+//! # externally tagged values in the same map.
+//! type_reg = { version = "0.8.0", features = ["adaptive"] }
+//!
+//! # Allow `untagged::TypeReg` to share equal values when deserializing.
+//! type_reg = { version = "0.8.0", features = ["interned"] }
+//!
+//! # Generate JSON schemas for `untagged::TypeReg` keys.
+//! type_reg = { version = "0.8.0", features = ["schemars"] }
+//!
+//! # Estimate the serialized size of an `untagged::TypeMap`.
+//! type_reg = { version = "0.8.0", features = ["estimated_size"] }
+//!
+//! # Record how long each value takes to deserialize.
+//! type_reg = { version = "0.8.0", features = ["instrument"] }
+//!
+//! # Deserialize JSON lines into an `untagged::TypeMap`.
+//! type_reg = { version = "0.8.0", features = ["jsonl"] }
+//!
+//! # Emit `tracing` spans when deserializing map entries.
+//! type_reg = { version = "0.8.0", features = ["tracing"] }
+//!
+//! # Select the type to deserialize each untagged value as by its YAML tag.
+//! type_reg = { version = "0.8.0", features = ["yaml_tag"] }
+//!
+//! # Iterate over deserialized map entries, deserializing each value on demand.
+//! type_reg = { version = "0.8.0", features = ["iter"] }
+//!
+//! # Deserialize a sequence of objects keyed by an id field into a `TypeMap`.
+//! type_reg = { version = "0.8.0", features = ["id_field"] }
+//!
+//! # Defer deserializing `untagged::TypeMap` values until they are accessed.
+//! type_reg = { version = "0.8.0", features = ["lazy"] }
+//!
+//! # Convert `untagged::TypeMap`s to and from `serde_json::Map`s.
+//! type_reg = { version = "0.8.0", features = ["json"] }
+//!
+//! # Compare `untagged::TypeMap`s with a tolerance for numeric values.
+//! type_reg = { version = "0.8.0", features = ["approx_eq"] }
 //! ```
 //!
 //! ### Untagged Type Registry
@@ -163,6 +203,9 @@ pub use crate::type_name_lit::TypeNameLit;
 
 mod type_name_lit;
 
+#[cfg(all(test, feature = "tagged", feature = "untagged"))]
+mod round_trip_tests;
+
 // This is used in `Debug` impls, but for some reason rustc warns the fields
 // are not used.
 #[allow(dead_code)]
@@ -171,3 +214,31 @@ pub(crate) struct TypedValue<'a> {
     r#type: TypeNameLit,
     value: &'a dyn std::fmt::Debug,
 }
+
+/// Stored value that is `Debug` formatted with its type name.
+#[cfg(any(feature = "tagged", feature = "untagged"))]
+pub(crate) trait TypedDebug {
+    fn type_name(&self) -> TypeNameLit;
+
+    #[cfg(feature = "debug")]
+    fn debug(&self) -> &dyn std::fmt::Debug;
+}
+
+/// Returns the value used to `Debug` format a stored value.
+#[cfg(any(feature = "tagged", feature = "untagged"))]
+pub(crate) fn typed_value<V>(resource: &V) -> TypedValue<'_>
+where
+    V: TypedDebug + ?Sized,
+{
+    // At runtime, we are unable to determine if the resource is `Debug`.
+    #[cfg(not(feature = "debug"))]
+    let value = &"..";
+
+    #[cfg(feature = "debug")]
+    let value = resource.debug();
+
+    TypedValue {
+        r#type: resource.type_name(),
+        value,
+    }
+}
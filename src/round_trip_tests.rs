@@ -0,0 +1,173 @@
+//! Tests that values of common types round trip through the `tagged` and
+//! `untagged` registries, in both YAML and JSON.
+
+use std::fmt::Debug;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{tagged, untagged};
+
+/// Data format to serialize to and deserialize from.
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    Yaml,
+    Json,
+}
+
+impl Format {
+    fn to_string<T>(self, value: &T) -> String
+    where
+        T: Serialize,
+    {
+        match self {
+            Format::Yaml => serde_yaml::to_string(value).unwrap(),
+            Format::Json => serde_json::to_string(value).unwrap(),
+        }
+    }
+}
+
+/// Serializes the value in an `untagged::TypeMap`, and deserializes it again.
+fn untagged_round_trip<T>(value: T, format: Format) -> T
+where
+    T: Clone + Debug + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let mut type_map = untagged::TypeMap::<String>::new();
+    type_map.insert(String::from("value"), value);
+    let serialized = format.to_string(&type_map);
+
+    let mut type_reg = untagged::TypeReg::<String>::new();
+    type_reg.register::<T>(String::from("value"));
+    let type_map: untagged::TypeMap<String> = match format {
+        Format::Yaml => type_reg
+            .deserialize_map(serde_yaml::Deserializer::from_str(&serialized))
+            .map_err(|error| error.to_string()),
+        Format::Json => type_reg
+            .deserialize_map(&mut serde_json::Deserializer::from_str(&serialized))
+            .map_err(|error| error.to_string()),
+    }
+    .unwrap_or_else(|error| panic!("Failed to deserialize `{serialized}`: {error}"));
+
+    type_map
+        .get::<T, _>("value")
+        .cloned()
+        .unwrap_or_else(|| panic!("Expected `value` to be deserialized from `{serialized}`."))
+}
+
+/// Serializes the value in a `tagged::TypeMap`, and deserializes it again.
+fn tagged_round_trip<T>(value: T, format: Format) -> T
+where
+    T: Clone + Debug + Serialize + DeserializeOwned + Send + Sync + 'static,
+{
+    let mut type_map = tagged::TypeMap::<String>::new();
+    type_map.insert(String::from("value"), value);
+    let serialized = format.to_string(&type_map);
+
+    let mut type_reg = tagged::TypeReg::new();
+    type_reg.register::<T>();
+    let type_map: tagged::TypeMap<String> = match format {
+        Format::Yaml => type_reg
+            .deserialize_map(serde_yaml::Deserializer::from_str(&serialized))
+            .map_err(|error| error.to_string()),
+        Format::Json => type_reg
+            .deserialize_map(&mut serde_json::Deserializer::from_str(&serialized))
+            .map_err(|error| error.to_string()),
+    }
+    .unwrap_or_else(|error| panic!("Failed to deserialize `{serialized}`: {error}"));
+
+    type_map
+        .get::<T, _>("value")
+        .cloned()
+        .unwrap_or_else(|| panic!("Expected `value` to be deserialized from `{serialized}`."))
+}
+
+/// Generates a module per type, with a test for each registry and format.
+macro_rules! round_trip_tests {
+    ($($name:ident: $ty:ty = $value:expr;)*) => {
+        $(
+            mod $name {
+                use super::*;
+
+                fn value() -> $ty {
+                    $value
+                }
+
+                #[test]
+                fn untagged_yaml() {
+                    assert_eq!(value(), untagged_round_trip(value(), Format::Yaml));
+                }
+
+                #[test]
+                fn untagged_json() {
+                    assert_eq!(value(), untagged_round_trip(value(), Format::Json));
+                }
+
+                #[test]
+                fn tagged_yaml() {
+                    assert_eq!(value(), tagged_round_trip(value(), Format::Yaml));
+                }
+
+                #[test]
+                fn tagged_json() {
+                    assert_eq!(value(), tagged_round_trip(value(), Format::Json));
+                }
+            }
+        )*
+    };
+}
+
+round_trip_tests! {
+    u8_max: u8 = u8::MAX;
+    u16_max: u16 = u16::MAX;
+    u32_max: u32 = u32::MAX;
+    u64_max: u64 = u64::MAX;
+    u128_max: u128 = u128::MAX;
+    i8_min: i8 = i8::MIN;
+    i16_min: i16 = i16::MIN;
+    i32_min: i32 = i32::MIN;
+    i64_min: i64 = i64::MIN;
+    i128_min: i128 = i128::MIN;
+    f32_fraction: f32 = -1.5;
+    f64_fraction: f64 = 0.1;
+    f64_max: f64 = f64::MAX;
+    bool_true: bool = true;
+    char_ascii: char = 'a';
+    char_unicode: char = 'λ';
+    string_empty: String = String::new();
+    string_special: String = String::from("multi\nline: \"quoted\" # not a comment");
+    string_numeric: String = String::from("123");
+    unit: () = ();
+    tuple: (u32, String, bool) = (1, String::from("two"), true);
+    vec_empty: Vec<u32> = Vec::new();
+    vec_values: Vec<u32> = vec![1, 2, 3];
+    option_some: Option<u32> = Some(1);
+    option_none: Option<u32> = None;
+    nested_struct: Outer = Outer {
+        inner: Inner {
+            name: String::from("inner"),
+            values: vec![1, 2],
+        },
+        flag: Some(false),
+    };
+}
+
+#[test]
+fn f64_nan_untagged_yaml() {
+    assert!(untagged_round_trip(f64::NAN, Format::Yaml).is_nan());
+}
+
+#[test]
+fn f64_nan_tagged_yaml() {
+    assert!(tagged_round_trip(f64::NAN, Format::Yaml).is_nan());
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct Outer {
+    inner: Inner,
+    flag: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+struct Inner {
+    name: String,
+    values: Vec<u8>,
+}
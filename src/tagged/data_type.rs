@@ -38,6 +38,17 @@ where
     }
 }
 
+impl crate::TypedDebug for dyn DataType {
+    fn type_name(&self) -> TypeNameLit {
+        DataType::type_name(self)
+    }
+
+    #[cfg(feature = "debug")]
+    fn debug(&self) -> &dyn std::fmt::Debug {
+        self
+    }
+}
+
 downcast_rs::impl_downcast!(sync DataType);
 dyn_clone::clone_trait_object!(DataType);
 
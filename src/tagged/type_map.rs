@@ -6,8 +6,12 @@ use std::{
 };
 
 use crate::{
-    common::{UnknownEntries, UnknownEntriesNone, UnknownEntriesSome},
+    common::{
+        DebugMapSorted, DebugTypeMapSorted, GetResult, UnknownEntries, UnknownEntriesNone,
+        UnknownEntriesSome,
+    },
     tagged::DataType,
+    TypeNameLit,
 };
 
 #[cfg(not(feature = "ordered"))]
@@ -75,17 +79,64 @@ where
     pub fn into_inner(self) -> Map<K, Box<dyn DataType>> {
         self.inner
     }
+
+    /// Returns a `Debug` view of this map with entries sorted by key.
+    ///
+    /// Without the `"ordered"` feature, the `Debug` output of a `TypeMap` is
+    /// in an unspecified order, which makes it unsuitable for snapshot tests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("b", 2u32);
+    /// type_map.insert("a", 1u32);
+    ///
+    /// let debug_str = format!("{:?}", type_map.debug_sorted());
+    /// assert!(debug_str.find("\"a\"") < debug_str.find("\"b\""));
+    /// ```
+    pub fn debug_sorted(&self) -> impl Debug + '_
+    where
+        K: Ord + Debug,
+    {
+        DebugMapSorted::new(
+            self.inner
+                .iter()
+                .map(|(k, v)| (k, crate::typed_value(v.as_ref()))),
+        )
+    }
 }
 
 impl<K, ValueT> TypeMap<K, UnknownEntriesSome<ValueT>>
 where
     K: Eq + Hash,
-    ValueT: Clone + Debug + PartialEq + Eq,
+    ValueT: Clone + Debug,
 {
     /// Returns the underlying map and unknown entries.
     pub fn into_inner(self) -> (Map<K, Box<dyn DataType>>, Map<K, ValueT>) {
         (self.inner, self.unknown_entries)
     }
+
+    /// Returns a `Debug` view of this map with entries sorted by key.
+    ///
+    /// Without the `"ordered"` feature, the `Debug` output of a `TypeMap` is
+    /// in an unspecified order, which makes it unsuitable for snapshot tests.
+    pub fn debug_sorted(&self) -> impl Debug + '_
+    where
+        K: Ord + Debug,
+    {
+        DebugTypeMapSorted {
+            name: "TypeMap",
+            inner: DebugMapSorted::new(
+                self.inner
+                    .iter()
+                    .map(|(k, v)| (k, crate::typed_value(v.as_ref()))),
+            ),
+            unknown_entries: DebugMapSorted::new(self.unknown_entries.iter()),
+        }
+    }
 }
 
 impl<K, UnknownEntriesT> TypeMap<K, UnknownEntriesT>
@@ -187,6 +238,46 @@ where
         self.inner.get(q).and_then(|n| n.downcast_ref::<R>())
     }
 
+    /// Returns the value corresponding to the key, or why it is not returned.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// Unlike [`get`], if there is an entry, but the data type does not match,
+    /// [`GetResult::WrongType`] is returned with the stored value's type name.
+    ///
+    /// [`get`]: Self::get
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{common::GetResult, tagged::TypeMap, TypeNameLit};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// assert_eq!(GetResult::Present(&1), type_map.get_typed::<u32, _>("one"));
+    /// assert_eq!(
+    ///     GetResult::WrongType(TypeNameLit("u32")),
+    ///     type_map.get_typed::<u64, _>("one")
+    /// );
+    /// assert_eq!(GetResult::Absent, type_map.get_typed::<u32, _>("two"));
+    /// ```
+    pub fn get_typed<R, Q>(&self, q: &Q) -> GetResult<&R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        R: DataType,
+    {
+        match self.inner.get(q) {
+            Some(data_type) => match data_type.downcast_ref::<R>() {
+                Some(r) => GetResult::Present(r),
+                None => GetResult::WrongType(DataType::type_name(data_type.as_ref())),
+            },
+            None => GetResult::Absent,
+        }
+    }
+
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map’s key type, but `Hash` and
@@ -246,6 +337,36 @@ where
         self.inner.get_mut(q).and_then(|n| n.downcast_mut::<R>())
     }
 
+    /// Returns the value corresponding to the key as an
+    /// [`erased_serde::Serialize`] trait object.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// The value serializes without its type tag, as the tag is only added
+    /// when serializing the map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let one = type_map.erased_value("one").unwrap();
+    /// assert_eq!("1\n", serde_yaml::to_string(one).unwrap());
+    /// ```
+    pub fn erased_value<Q>(&self, q: &Q) -> Option<&dyn erased_serde::Serialize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner
+            .get(q)
+            .map(|data_type| data_type.as_ref() as &dyn erased_serde::Serialize)
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// If the map did not have this key present, `None` is returned.
@@ -286,6 +407,134 @@ where
     pub fn insert_raw(&mut self, k: K, v: Box<dyn DataType>) -> Option<Box<dyn DataType>> {
         self.inner.insert(k, v)
     }
+
+    /// Sorts the entries by key.
+    ///
+    /// Unknown entries are sorted as well. This is useful to canonicalize the
+    /// order of entries before serialization.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("b", 2u32);
+    /// type_map.insert("a", 1u32);
+    ///
+    /// type_map.sort_keys();
+    ///
+    /// assert_eq!(vec![&"a", &"b"], type_map.keys().collect::<Vec<_>>());
+    /// ```
+    #[cfg(feature = "ordered")]
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.inner.sort_keys();
+        self.unknown_entries.sort_keys();
+    }
+
+    /// Sorts the entries using the given comparison function.
+    ///
+    /// Unknown entries are not sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("a", 1u32);
+    /// type_map.insert("bb", 2u32);
+    ///
+    /// type_map.sort_by(|k1, _v1, k2, _v2| k2.len().cmp(&k1.len()));
+    ///
+    /// assert_eq!(vec![&"bb", &"a"], type_map.keys().collect::<Vec<_>>());
+    /// ```
+    #[cfg(feature = "ordered")]
+    pub fn sort_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &Box<dyn DataType>, &K, &Box<dyn DataType>) -> std::cmp::Ordering,
+    {
+        self.inner.sort_by(f);
+    }
+
+    /// Returns a serializable view of this map as a sequence of `[key, value]`
+    /// pairs.
+    ///
+    /// This is useful for formats such as JSON, which only support string map
+    /// keys, when `K` is not a string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<u32>::new();
+    /// type_map.insert(1, 1u32);
+    ///
+    /// let serialized = serde_json::to_string(&type_map.serialize_as_seq()).unwrap();
+    /// assert_eq!(r#"[[1,{"u32":1}]]"#, serialized);
+    /// ```
+    pub fn serialize_as_seq(&self) -> impl serde::Serialize + '_
+    where
+        K: serde::Serialize,
+    {
+        TypeMapSeq { inner: &self.inner }
+    }
+
+    /// Returns an iterator over each key and the type name of its value.
+    ///
+    /// This is useful to inspect which types are stored in the map, without
+    /// serializing the values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{tagged::TypeMap, TypeNameLit};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let type_names = type_map.type_names().collect::<Vec<_>>();
+    /// assert_eq!(vec![(&"one", TypeNameLit("u32"))], type_names);
+    /// ```
+    pub fn type_names(&self) -> impl Iterator<Item = (&K, TypeNameLit)> + '_ {
+        self.inner
+            .iter()
+            .map(|(k, v)| (k, DataType::type_name(v.as_ref())))
+    }
+
+    /// Returns a serializable view of this map that only includes entries for
+    /// which `f` returns `true`.
+    ///
+    /// The map is not modified. This is useful to omit entries, such as
+    /// secrets, when exporting the map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert("password", String::from("hunter2"));
+    ///
+    /// let serialized =
+    ///     serde_yaml::to_string(&type_map.serialize_filtered(|k, _| *k != "password")).unwrap();
+    /// assert_eq!("one:\n  u32: 1\n", serialized);
+    /// ```
+    pub fn serialize_filtered<'f, F>(&'f self, f: F) -> impl serde::Serialize + 'f
+    where
+        K: serde::Serialize,
+        F: Fn(&K, &dyn DataType) -> bool + 'f,
+    {
+        TypeMapFiltered {
+            inner: &self.inner,
+            f,
+        }
+    }
 }
 
 impl<K, UnknownEntriesT> Clone for TypeMap<K, UnknownEntriesT>
@@ -351,27 +600,13 @@ where
     K: Eq + Hash + Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
-
-        self.inner.iter().for_each(|(k, resource)| {
-            // At runtime, we are unable to determine if the resource is `Debug`.
-            #[cfg(not(feature = "debug"))]
-            let value = &"..";
-
-            #[cfg(feature = "debug")]
-            let value = &resource;
-
-            let type_name = resource.as_ref().type_name();
-            let debug_value = crate::TypedValue {
-                r#type: type_name,
-                value,
-            };
-
-            debug_map.key(&k);
-            debug_map.value(&debug_value);
-        });
-
-        debug_map.finish()
+        f.debug_map()
+            .entries(
+                self.inner
+                    .iter()
+                    .map(|(k, resource)| (k, crate::typed_value(resource.as_ref()))),
+            )
+            .finish()
     }
 }
 
@@ -387,34 +622,20 @@ where
     K: Eq + Hash + Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
-
-        self.inner.iter().for_each(|(k, resource)| {
-            // At runtime, we are unable to determine if the resource is `Debug`.
-            #[cfg(not(feature = "debug"))]
-            let value = &"..";
-
-            #[cfg(feature = "debug")]
-            let value = &resource;
-
-            let type_name = resource.as_ref().type_name();
-            let debug_value = crate::TypedValue {
-                r#type: type_name,
-                value,
-            };
-
-            debug_map.key(&k);
-            debug_map.value(&debug_value);
-        });
-
-        debug_map.finish()
+        f.debug_map()
+            .entries(
+                self.inner
+                    .iter()
+                    .map(|(k, resource)| (k, crate::typed_value(resource.as_ref()))),
+            )
+            .finish()
     }
 }
 
 impl<K, ValueT> Debug for TypeMap<K, UnknownEntriesSome<ValueT>>
 where
     K: Eq + Hash + Debug,
-    ValueT: Clone + Debug + PartialEq + Eq,
+    ValueT: Clone + Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("TypeMap")
@@ -424,11 +645,54 @@ where
     }
 }
 
+/// Serializes the entries of a map for which the filter function returns
+/// `true`.
+struct TypeMapFiltered<'inner, K, F> {
+    inner: &'inner Map<K, Box<dyn DataType>>,
+    f: F,
+}
+
+impl<K, F> serde::Serialize for TypeMapFiltered<'_, K, F>
+where
+    K: Eq + Hash + serde::Serialize,
+    F: Fn(&K, &dyn DataType) -> bool,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(self.inner.iter().filter(|(k, v)| (self.f)(k, v.as_ref())))
+    }
+}
+
+/// Serializes the entries of a map as a sequence of `[key, value]` pairs.
+struct TypeMapSeq<'inner, K> {
+    inner: &'inner Map<K, Box<dyn DataType>>,
+}
+
+impl<K> serde::Serialize for TypeMapSeq<'_, K>
+where
+    K: Eq + Hash + serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.inner.iter())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::any::type_name;
+
     use serde::{Deserialize, Serialize};
 
-    use crate::{common::UnknownEntriesSome, tagged::TypeMap};
+    use crate::{
+        common::{GetResult, UnknownEntriesSome},
+        tagged::TypeMap,
+        TypeNameLit,
+    };
 
     #[cfg(feature = "ordered")]
     #[test]
@@ -450,6 +714,94 @@ three:
         assert_eq!(expected, serialized);
     }
 
+    #[test]
+    fn serialize_as_seq() {
+        let mut type_map = TypeMap::new();
+        type_map.insert(1u32, A(1));
+
+        let serialized = serde_json::to_string(&type_map.serialize_as_seq())
+            .expect("Failed to serialize `type_map`.");
+        assert_eq!(
+            r#"[[1,{"type_reg::tagged::type_map::tests::A":1}]]"#,
+            serialized
+        );
+    }
+
+    #[test]
+    fn erased_value() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("three", A(3));
+
+        let three = type_map.erased_value("three").unwrap();
+
+        assert_eq!("3\n", serde_yaml::to_string(three).unwrap());
+        assert!(type_map.erased_value("four").is_none());
+    }
+
+    #[test]
+    fn type_names() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("three", A(3));
+
+        let mut type_names = type_map.type_names().collect::<Vec<_>>();
+        type_names.sort_by_key(|(k, _)| **k);
+
+        assert_eq!(
+            vec![
+                (&"one", TypeNameLit(type_name::<u32>())),
+                (&"three", TypeNameLit(type_name::<A>())),
+            ],
+            type_names
+        );
+    }
+
+    #[test]
+    fn serialize_filtered() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", A(3));
+
+        let serialized = serde_yaml::to_string(
+            &type_map.serialize_filtered(|_k, v| v.type_name() == TypeNameLit(type_name::<A>())),
+        )
+        .expect("Failed to serialize `type_map`.");
+        assert_eq!(
+            "three:\n  type_reg::tagged::type_map::tests::A: 3\n",
+            serialized
+        );
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn sort_keys() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+
+        type_map.sort_keys();
+
+        assert_eq!(vec![&"one", &"two"], type_map.keys().collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn sort_by() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+        type_map.insert("two", A(2));
+
+        type_map.sort_by(|_k1, v1, _k2, v2| {
+            let a1 = v1.downcast_ref::<A>().unwrap();
+            let a2 = v2.downcast_ref::<A>().unwrap();
+            a2.0.cmp(&a1.0)
+        });
+
+        assert_eq!(vec![&"two", &"one"], type_map.keys().collect::<Vec<_>>());
+    }
+
     #[test]
     fn clone() {
         let mut type_map = TypeMap::new();
@@ -462,6 +814,31 @@ three:
         assert_eq!(Some(A(2)), type_map_clone.get("one").copied());
     }
 
+    #[test]
+    fn into_inner_new_typed() {
+        let mut type_map = TypeMap::<&'static str>::new_typed();
+        type_map.insert("one", A(1));
+
+        let inner = type_map.into_inner();
+
+        assert_eq!(1, inner.len());
+        assert_eq!(
+            Some(&A(1)),
+            inner.get("one").and_then(|v| v.downcast_ref::<A>())
+        );
+    }
+
+    #[test]
+    fn into_inner_new_typed_unknown_entries_some() {
+        let mut type_map = TypeMap::<&'static str, UnknownEntriesSome<()>>::new_typed();
+        type_map.insert("one", A(1));
+
+        let (inner, unknown_entries) = type_map.into_inner();
+
+        assert_eq!(1, inner.len());
+        assert!(unknown_entries.is_empty());
+    }
+
     #[cfg(not(feature = "debug"))]
     #[test]
     fn debug() {
@@ -502,6 +879,48 @@ three:
         );
     }
 
+    #[cfg(not(feature = "debug"))]
+    #[test]
+    fn debug_sorted() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+
+        assert_eq!(
+            "{\
+                \"one\": TypedValue { type: \"type_reg::tagged::type_map::tests::A\", value: \"..\" }, \
+                \"two\": TypedValue { type: \"type_reg::tagged::type_map::tests::A\", value: \"..\" }\
+            }",
+            format!("{:?}", type_map.debug_sorted())
+        );
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn debug_sorted() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+
+        assert_eq!(
+            "{\
+                \"one\": TypedValue { type: \"type_reg::tagged::type_map::tests::A\", value: A(1) }, \
+                \"two\": TypedValue { type: \"type_reg::tagged::type_map::tests::A\", value: A(2) }\
+            }",
+            format!("{:?}", type_map.debug_sorted())
+        );
+    }
+
+    #[test]
+    fn debug_sorted_with_unknown_entries_some() {
+        let type_map = TypeMap::<&'static str, UnknownEntriesSome<()>>::default();
+
+        assert_eq!(
+            "TypeMap { inner: {}, unknown_entries: {} }",
+            format!("{:?}", type_map.debug_sorted())
+        );
+    }
+
     #[test]
     fn into_inner_unknown_entries_none() {
         let mut type_map = TypeMap::new();
@@ -531,6 +950,20 @@ three:
         assert!(unknown_entries.is_empty());
     }
 
+    #[test]
+    fn get_typed() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        assert_eq!(GetResult::Present(&A(1)), type_map.get_typed::<A, _>("one"));
+        assert_eq!(
+            GetResult::WrongType(TypeNameLit(type_name::<A>())),
+            type_map.get_typed::<u32, _>("one")
+        );
+        assert_eq!(GetResult::Absent, type_map.get_typed::<A, _>("two"));
+        assert_eq!(None, type_map.get_typed::<u32, _>("one").ok());
+    }
+
     #[test]
     fn get_mut() {
         let mut type_map = TypeMap::new();
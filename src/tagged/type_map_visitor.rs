@@ -49,8 +49,24 @@ where
         };
 
         while let Some(key) = map_access.next_key::<MapK>()? {
+            // The type tag is only known once the value is deserialized.
+            #[cfg(feature = "tracing")]
+            let span = tracing::debug_span!(
+                "deserialize_entry",
+                key = ?key,
+                type_name = tracing::field::Empty,
+            );
+            #[cfg(feature = "tracing")]
+            let _entered = span.enter();
+
             let value = map_access.next_value_seed(self.type_reg)?;
 
+            #[cfg(feature = "tracing")]
+            span.record(
+                "type_name",
+                crate::tagged::DataType::type_name(value.as_ref()).0,
+            );
+
             type_map.insert_raw(key, value);
         }
 
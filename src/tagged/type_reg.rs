@@ -5,10 +5,16 @@ use std::{
     ops::{Deref, DerefMut},
 };
 
-use serde::de::DeserializeSeed;
-use serde_tagged::de::{BoxFnSeed, SeedFactory};
+use serde::{de::DeserializeSeed, ser::SerializeMap};
+use serde_tagged::{
+    de::{BoxFnSeed, SeedFactory},
+    util::erased::SerializeErased,
+};
 
-use crate::tagged::{DataType, TypeMap, TypeMapVisitor};
+use crate::{
+    common::UnknownEntries,
+    tagged::{DataType, TypeMap, TypeMapVisitor},
+};
 
 #[cfg(not(feature = "ordered"))]
 use std::collections::HashMap as Map;
@@ -16,13 +22,27 @@ use std::collections::HashMap as Map;
 #[cfg(feature = "ordered")]
 use indexmap::IndexMap as Map;
 
+/// Hook called with the tag of each registered type.
+type TagHook = Box<dyn Fn(&str) + Send + Sync>;
+
 /// Map from a given key to logic to deserialize a type.
 #[derive(Default)]
 pub struct TypeReg<'key> {
     fn_seeds: Map<Cow<'key, str>, BoxFnSeed<Box<dyn DataType>>>,
+    /// Custom tags for types registered using [`register_with_tag`], keyed by
+    /// type name.
+    ///
+    /// [`register_with_tag`]: Self::register_with_tag
+    tags: Map<&'static str, Cow<'key, str>>,
+    /// Hook called with the tag of each registered type.
+    ///
+    /// See [`set_unstable_tag_warning_hook`].
+    ///
+    /// [`set_unstable_tag_warning_hook`]: Self::set_unstable_tag_warning_hook
+    tag_hook: Option<TagHook>,
 }
 
-impl TypeReg<'_> {
+impl<'key> TypeReg<'key> {
     // Creates an empty `TypeReg`.
     ///
     /// The map is initially created with a capacity of 0, so it will not
@@ -37,6 +57,8 @@ impl TypeReg<'_> {
     pub fn new() -> Self {
         Self {
             fn_seeds: Map::new(),
+            tags: Map::new(),
+            tag_hook: None,
         }
     }
 
@@ -54,6 +76,8 @@ impl TypeReg<'_> {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             fn_seeds: Map::with_capacity(capacity),
+            tags: Map::new(),
+            tag_hook: None,
         }
     }
 
@@ -82,21 +106,238 @@ impl TypeReg<'_> {
     where
         R: serde::de::DeserializeOwned + DataType + 'static,
     {
-        fn deserialize<R>(
-            deserializer: &mut dyn erased_serde::Deserializer<'_>,
-        ) -> Result<Box<dyn DataType>, erased_serde::Error>
-        where
-            R: serde::de::DeserializeOwned + DataType + 'static,
-        {
-            Ok(Box::new(R::deserialize(deserializer)?))
+        let tag = std::any::type_name::<R>();
+        if let Some(tag_hook) = self.tag_hook.as_ref() {
+            tag_hook(tag);
         }
-
+        self.forget_tag_of_other_types(tag, tag);
         self.fn_seeds.insert(
-            Cow::Borrowed(std::any::type_name::<R>()),
-            BoxFnSeed::new(deserialize::<R>),
+            Cow::Borrowed(tag),
+            BoxFnSeed::new(Self::deserialize_boxed::<R>),
         );
     }
 
+    /// Registers a type in this type registry, using a custom tag instead of
+    /// its type name.
+    ///
+    /// This is useful for generic types, whose type names include the full
+    /// path of each type parameter. The custom tag is used when serializing
+    /// through [`serialize_map`].
+    ///
+    /// [`serialize_map`]: Self::serialize_map
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use type_reg::tagged::{TypeMap, TypeReg};
+    ///
+    /// #[derive(Clone, Debug, Deserialize, Serialize)]
+    /// struct Wrapper<T>(T);
+    ///
+    /// let mut type_reg = TypeReg::new();
+    /// type_reg.register_with_tag::<Wrapper<u32>, _>("wrapper-u32");
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: { wrapper-u32: 1 }");
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///
+    /// let serialized = serde_yaml::to_string(&type_reg.serialize_map(&type_map)).unwrap();
+    /// assert_eq!("one:\n  wrapper-u32: 1\n", serialized);
+    /// ```
+    pub fn register_with_tag<R, T>(&mut self, tag: T)
+    where
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        T: Into<Cow<'key, str>>,
+    {
+        let tag = tag.into();
+        if let Some(tag_hook) = self.tag_hook.as_ref() {
+            tag_hook(&tag);
+        }
+        let type_name = std::any::type_name::<R>();
+        self.forget_tag_of_other_types(&tag, type_name);
+        self.tags.insert(type_name, tag.clone());
+        self.fn_seeds
+            .insert(tag, BoxFnSeed::new(Self::deserialize_boxed::<R>));
+    }
+
+    /// Removes custom tags of types other than `type_name` that are `tag`, as
+    /// `tag` is about to deserialize `type_name`.
+    ///
+    /// Otherwise [`serialize_map`] would tag values of those types with a tag
+    /// that deserializes a different type.
+    ///
+    /// [`serialize_map`]: Self::serialize_map
+    fn forget_tag_of_other_types(&mut self, tag: &str, type_name: &'static str) {
+        if !self.tags.is_empty() {
+            self.tags.retain(|type_name_existing, tag_existing| {
+                *type_name_existing == type_name || tag_existing != tag
+            });
+        }
+    }
+
+    /// Sets a hook that is called with the tag of each type registered after
+    /// this call.
+    ///
+    /// Tags for types registered using [`register`] come from
+    /// [`std::any::type_name`], whose output may change between compiler
+    /// versions. This hook allows the tags to be logged or snapshot tested, so
+    /// that such changes are noticed.
+    ///
+    /// [`register`]: Self::register
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use type_reg::tagged::TypeReg;
+    ///
+    /// let tags = Arc::new(Mutex::new(Vec::new()));
+    ///
+    /// let mut type_reg = TypeReg::new();
+    /// type_reg.set_unstable_tag_warning_hook({
+    ///     let tags = Arc::clone(&tags);
+    ///     move |tag| tags.lock().unwrap().push(tag.to_string())
+    /// });
+    /// type_reg.register::<u32>();
+    ///
+    /// assert_eq!(vec![String::from("u32")], *tags.lock().unwrap());
+    /// ```
+    pub fn set_unstable_tag_warning_hook<F>(&mut self, f: F)
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.tag_hook = Some(Box::new(f));
+    }
+
+    /// Returns a fingerprint of the tags of all registered types.
+    ///
+    /// The fingerprint does not depend on registration order, and is computed
+    /// using FNV-1a, so it is stable across compiler versions and platforms.
+    /// This can be asserted in tests to detect when a tag changes.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeReg;
+    ///
+    /// let mut type_reg_a = TypeReg::new();
+    /// type_reg_a.register::<u32>();
+    /// type_reg_a.register::<u64>();
+    ///
+    /// let mut type_reg_b = TypeReg::new();
+    /// type_reg_b.register::<u64>();
+    /// type_reg_b.register::<u32>();
+    ///
+    /// assert_eq!(
+    ///     type_reg_a.registered_tags_fingerprint(),
+    ///     type_reg_b.registered_tags_fingerprint()
+    /// );
+    /// ```
+    pub fn registered_tags_fingerprint(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+        let mut tags = self.fn_seeds.keys().collect::<Vec<_>>();
+        tags.sort_unstable();
+
+        // Each tag is terminated by a `0` byte so that `["ab", "c"]` and
+        // `["a", "bc"]` produce different fingerprints.
+        tags.iter()
+            .flat_map(|tag| tag.bytes().chain(std::iter::once(0u8)))
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    fn deserialize_boxed<R>(
+        deserializer: &mut dyn erased_serde::Deserializer<'_>,
+    ) -> Result<Box<dyn DataType>, erased_serde::Error>
+    where
+        R: serde::de::DeserializeOwned + DataType + 'static,
+    {
+        Ok(Box::new(R::deserialize(deserializer)?))
+    }
+
+    /// Moves all registrations from `other` into this registry.
+    ///
+    /// Registrations in `other` replace existing registrations with the same
+    /// tag. Use [`try_merge`] to detect collisions instead.
+    ///
+    /// [`try_merge`]: Self::try_merge
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::new();
+    /// type_reg.register::<u32>();
+    ///
+    /// let mut type_reg_plugin = TypeReg::new();
+    /// type_reg_plugin.register::<u64>();
+    ///
+    /// type_reg.merge(type_reg_plugin);
+    ///
+    /// assert_eq!(2, type_reg.len());
+    /// ```
+    pub fn merge(&mut self, other: TypeReg<'key>) {
+        let TypeReg {
+            fn_seeds,
+            tags,
+            tag_hook: _,
+        } = other;
+
+        // Custom tags in this registry that `other` registers a type for no
+        // longer deserialize the tagged type. `other`'s custom tags are added
+        // back below.
+        if !self.tags.is_empty() {
+            self.tags
+                .retain(|_type_name, tag| !fn_seeds.contains_key(tag));
+        }
+        self.fn_seeds.extend(fn_seeds);
+        self.tags.extend(tags);
+    }
+
+    /// Moves all registrations from `other` into this registry, failing if
+    /// any tag is registered in both.
+    ///
+    /// On failure, the colliding tags are returned, and this registry is not
+    /// modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::tagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::new();
+    /// type_reg.register::<u32>();
+    ///
+    /// let mut type_reg_plugin = TypeReg::new();
+    /// type_reg_plugin.register::<u32>();
+    /// type_reg_plugin.register::<u64>();
+    ///
+    /// let collisions = type_reg.try_merge(type_reg_plugin).unwrap_err();
+    ///
+    /// assert_eq!(vec!["u32"], collisions);
+    /// assert_eq!(1, type_reg.len());
+    /// ```
+    pub fn try_merge(&mut self, other: TypeReg<'key>) -> Result<(), Vec<Cow<'key, str>>> {
+        let collisions = other
+            .fn_seeds
+            .keys()
+            .filter(|tag| self.fn_seeds.contains_key(*tag))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if collisions.is_empty() {
+            self.merge(other);
+            Ok(())
+        } else {
+            Err(collisions)
+        }
+    }
+
     /// Deserializes a map of arbitrary values into a [`TypeMap`].
     ///
     /// Each type must be registered in this type registry before attempting to
@@ -170,6 +411,84 @@ impl TypeReg<'_> {
     {
         serde_tagged::de::external::deserialize(deserializer, self)
     }
+
+    /// Returns a serializable view of a [`TypeMap`], which tags each value
+    /// with the custom tag it was registered with.
+    ///
+    /// Values of types that were not registered using [`register_with_tag`]
+    /// are tagged with their type name, the same as when serializing the
+    /// `TypeMap` directly.
+    ///
+    /// [`register_with_tag`]: Self::register_with_tag
+    pub fn serialize_map<'r, K, UnknownEntriesT>(
+        &'r self,
+        type_map: &'r TypeMap<K, UnknownEntriesT>,
+    ) -> impl serde::Serialize + 'r
+    where
+        K: Eq + Hash + serde::Serialize,
+        UnknownEntriesT: UnknownEntries,
+    {
+        TypeMapCustomTags {
+            type_reg: self,
+            type_map,
+        }
+    }
+}
+
+/// Serializes a [`TypeMap`], using custom tags from the [`TypeReg`].
+struct TypeMapCustomTags<'r, 'key, K, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    type_reg: &'r TypeReg<'key>,
+    type_map: &'r TypeMap<K, UnknownEntriesT>,
+}
+
+impl<K, UnknownEntriesT> serde::Serialize for TypeMapCustomTags<'_, '_, K, UnknownEntriesT>
+where
+    K: Eq + Hash + serde::Serialize,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.type_map.len()))?;
+        self.type_map.iter().try_for_each(|(k, v)| {
+            map.serialize_entry(
+                k,
+                &ValueCustomTag {
+                    type_reg: self.type_reg,
+                    value: &**v,
+                },
+            )
+        })?;
+        map.end()
+    }
+}
+
+/// Serializes a value, using its custom tag from the [`TypeReg`].
+struct ValueCustomTag<'r, 'key> {
+    type_reg: &'r TypeReg<'key>,
+    value: &'r dyn DataType,
+}
+
+impl serde::Serialize for ValueCustomTag<'_, '_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let type_name = DataType::type_name(self.value).0;
+        let tag = self
+            .type_reg
+            .tags
+            .get(type_name)
+            .map(Cow::as_ref)
+            .unwrap_or(type_name);
+
+        serde_tagged::ser::external::serialize(serializer, tag, &SerializeErased(self.value))
+    }
 }
 
 impl fmt::Debug for TypeReg<'_> {
@@ -249,6 +568,8 @@ impl<'key: 'de, 'de: 'r, 'r> DeserializeSeed<'de> for &'r TypeReg<'key> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::{Arc, Mutex};
+
     use crate::tagged::{TypeMap, TypeReg};
     use serde::{Deserialize, Serialize};
 
@@ -264,6 +585,102 @@ mod tests {
         assert_eq!(Some(1), data_u32);
     }
 
+    #[test]
+    fn set_unstable_tag_warning_hook_receives_custom_tags() {
+        let tags = Arc::new(Mutex::new(Vec::new()));
+
+        let mut type_reg = TypeReg::new();
+        type_reg.register::<u32>();
+        type_reg.set_unstable_tag_warning_hook({
+            let tags = Arc::clone(&tags);
+            move |tag| tags.lock().unwrap().push(tag.to_string())
+        });
+        type_reg.register::<A>();
+        type_reg.register_with_tag::<Wrapper<u32>, _>("wrapper-u32");
+
+        assert_eq!(
+            vec![
+                String::from("type_reg::tagged::type_reg::tests::A"),
+                String::from("wrapper-u32"),
+            ],
+            *tags.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn type_reg_is_sync() {
+        fn assert_sync<T: Sync>() {}
+
+        assert_sync::<TypeReg<'static>>();
+    }
+
+    #[test]
+    fn registered_tags_fingerprint() {
+        let type_reg = TypeReg::new();
+        assert_eq!(
+            0xcbf2_9ce4_8422_2325,
+            type_reg.registered_tags_fingerprint()
+        );
+
+        let mut type_reg_ab = TypeReg::new();
+        type_reg_ab.register_with_tag::<u32, _>("ab");
+        type_reg_ab.register_with_tag::<u64, _>("c");
+        let mut type_reg_bc = TypeReg::new();
+        type_reg_bc.register_with_tag::<u32, _>("a");
+        type_reg_bc.register_with_tag::<u64, _>("bc");
+
+        assert_ne!(
+            type_reg_ab.registered_tags_fingerprint(),
+            type_reg_bc.registered_tags_fingerprint()
+        );
+    }
+
+    #[test]
+    fn merge() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register::<u32>();
+        let mut type_reg_other = TypeReg::new();
+        type_reg_other.register::<u32>();
+        type_reg_other.register::<A>();
+
+        type_reg.merge(type_reg_other);
+
+        let deserializer =
+            serde_yaml::Deserializer::from_str("{ 'type_reg::tagged::type_reg::tests::A': 3 }");
+        let data_a = type_reg.deserialize_single(deserializer).unwrap();
+        let data_a = data_a.downcast_ref::<A>().copied();
+
+        assert_eq!(2, type_reg.len());
+        assert_eq!(Some(A(3)), data_a);
+    }
+
+    #[test]
+    fn try_merge() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register::<u32>();
+        let mut type_reg_other = TypeReg::new();
+        type_reg_other.register::<A>();
+
+        let result = type_reg.try_merge(type_reg_other);
+
+        assert!(result.is_ok());
+        assert_eq!(2, type_reg.len());
+    }
+
+    #[test]
+    fn try_merge_returns_collisions() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register::<u32>();
+        let mut type_reg_other = TypeReg::new();
+        type_reg_other.register::<u32>();
+        type_reg_other.register::<A>();
+
+        let collisions = type_reg.try_merge(type_reg_other).unwrap_err();
+
+        assert_eq!(vec!["u32"], collisions);
+        assert_eq!(1, type_reg.len());
+    }
+
     #[test]
     fn deserialize_map() {
         let mut type_reg = TypeReg::new();
@@ -313,6 +730,95 @@ Available types are:
         }
     }
 
+    #[test]
+    fn register_with_tag_round_trips_generic_types() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register::<u32>();
+        type_reg.register_with_tag::<Wrapper<u32>, _>("wrapper-u32");
+        type_reg.register_with_tag::<Wrapper<String>, _>(String::from("wrapper-string"));
+
+        let serialized = "---\n\
+            one: { u32: 1 }\n\
+            two: { wrapper-u32: 2 }\n\
+            three: { wrapper-string: three }\n\
+            ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+        assert_eq!(
+            Some(Wrapper(2u32)),
+            type_map.get::<Wrapper<u32>, _>("two").cloned()
+        );
+        assert_eq!(
+            Some(Wrapper(String::from("three"))),
+            type_map.get::<Wrapper<String>, _>("three").cloned()
+        );
+    }
+
+    #[test]
+    fn serialize_map_uses_custom_tags() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register::<u32>();
+        type_reg.register_with_tag::<Wrapper<u32>, _>("wrapper-u32");
+
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("two", Wrapper(2u32));
+        assert_eq!(
+            "two:\n  wrapper-u32: 2\n",
+            serde_yaml::to_string(&type_reg.serialize_map(&type_map)).unwrap()
+        );
+
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("one", 1u32);
+        assert_eq!(
+            "one:\n  u32: 1\n",
+            serde_yaml::to_string(&type_reg.serialize_map(&type_map)).unwrap()
+        );
+    }
+
+    #[test]
+    fn register_with_tag_forgets_tag_of_overwritten_type() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register_with_tag::<A, _>("x");
+        type_reg.register_with_tag::<Wrapper<u32>, _>("x");
+
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("one", A(1));
+        type_map.insert("two", Wrapper(2u32));
+
+        let serialized = serde_yaml::to_string(&type_reg.serialize_map(&type_map)).unwrap();
+        assert!(
+            serialized.contains(&format!("{}: 1", std::any::type_name::<A>())),
+            "{serialized}"
+        );
+        assert!(serialized.contains("x: 2"), "{serialized}");
+    }
+
+    #[test]
+    fn merge_forgets_tag_of_overwritten_type() {
+        let mut type_reg = TypeReg::new();
+        type_reg.register_with_tag::<A, _>("x");
+        type_reg.register_with_tag::<Wrapper<String>, _>("y");
+
+        let mut type_reg_other = TypeReg::new();
+        type_reg_other.register_with_tag::<Wrapper<u32>, _>("x");
+        type_reg.merge(type_reg_other);
+
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("one", A(1));
+        type_map.insert("two", Wrapper(2u32));
+        type_map.insert("three", Wrapper(String::from("three")));
+
+        let serialized = serde_yaml::to_string(&type_reg.serialize_map(&type_map)).unwrap();
+        assert!(
+            serialized.contains(&format!("{}: 1", std::any::type_name::<A>())),
+            "{serialized}"
+        );
+        assert!(serialized.contains("x: 2"), "{serialized}");
+        assert!(serialized.contains("y: three"), "{serialized}");
+    }
+
     #[test]
     fn with_capacity() {
         let type_reg = TypeReg::new();
@@ -335,4 +841,7 @@ Available types are:
 
     #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
     struct A(u32);
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct Wrapper<T>(T);
 }
@@ -5,6 +5,31 @@ use std::fmt;
 #[derive(PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct TypeNameLit(pub &'static str);
 
+impl TypeNameLit {
+    /// Returns the `TypeNameLit` for `T`.
+    ///
+    /// This allows type names to be compared without an instance of `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::TypeNameLit;
+    ///
+    /// assert_eq!(TypeNameLit("u32"), TypeNameLit::of::<u32>());
+    /// ```
+    pub fn of<T>() -> Self
+    where
+        T: ?Sized,
+    {
+        Self(std::any::type_name::<T>())
+    }
+
+    /// Returns the type name as a `&'static str`.
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
 impl fmt::Debug for TypeNameLit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
@@ -27,6 +52,12 @@ mod tests {
         assert_eq!("\"A\"", format!("{type_name_lit:?}"));
     }
 
+    #[test]
+    fn of() {
+        assert_eq!(TypeNameLit("u32"), TypeNameLit::of::<u32>());
+        assert_eq!("str", TypeNameLit::of::<str>().as_str());
+    }
+
     #[test]
     fn display() {
         let type_name_lit = TypeNameLit("A");
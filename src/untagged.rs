@@ -45,21 +45,86 @@
 //! ```
 
 pub use self::{
-    box_data_type_downcast::BoxDataTypeDowncast, box_dt::BoxDt, box_dt_display::BoxDtDisplay,
+    arc_dt::ArcDt, box_data_type_downcast::BoxDataTypeDowncast, box_dt::BoxDt,
+    box_dt_display::BoxDtDisplay, closed_type_reg::ClosedTypeReg, data_ref::DataRef,
     data_type::DataType, data_type_display::DataTypeDisplay, data_type_wrapper::DataTypeWrapper,
-    from_data_type::FromDataType, type_map::TypeMap, type_map_opt::TypeMapOpt,
-    type_map_opt_visitor::TypeMapOptVisitor, type_map_visitor::TypeMapVisitor, type_reg::TypeReg,
+    from_data_type::FromDataType, key_interner::KeyInterner, key_transform::KeyTransform,
+    missing_keys_error::MissingKeysError, missing_type_error::MissingTypeError, numeric::Numeric,
+    registration_handle::RegistrationHandle, required_type::RequiredType,
+    scoped_registration::ScopedRegistration, struct_with_extras::StructWithExtras,
+    tracked_type_map::TrackedTypeMap, trait_object_reg::TraitObjectReg,
+    type_dispatch::TypeDispatch, type_map::TypeMap, type_map_builder::TypeMapBuilder,
+    type_map_entry::TypeMapEntry, type_map_flattened_visitor::TypeMapFlattenedVisitor,
+    type_map_opt::TypeMapOpt, type_map_opt_visitor::TypeMapOptVisitor,
+    type_map_reviver_visitor::TypeMapReviverVisitor,
+    type_map_seq_of_entries_visitor::TypeMapSeqOfEntriesVisitor, type_map_visitor::TypeMapVisitor,
+    type_reg::TypeReg,
 };
 
+#[cfg(feature = "adaptive")]
+pub use self::type_map_adaptive_visitor::TypeMapAdaptiveVisitor;
+
+#[cfg(feature = "iter")]
+pub use self::type_map_iter::TypeMapIter;
+
+#[cfg(feature = "id_field")]
+pub use self::type_map_seq_by_id_visitor::TypeMapSeqByIdVisitor;
+
+#[cfg(feature = "lazy")]
+pub use self::lazy_type_map::LazyTypeMap;
+
+#[cfg(feature = "instrument")]
+pub use self::type_map_timed_visitor::TypeMapTimedVisitor;
+
+#[cfg(feature = "yaml_tag")]
+pub use self::type_map_yaml_tag_visitor::TypeMapYamlTagVisitor;
+
+mod arc_dt;
 mod box_data_type_downcast;
 mod box_dt;
 mod box_dt_display;
+mod closed_type_map_visitor;
+mod closed_type_reg;
+mod data_ref;
 mod data_type;
 mod data_type_display;
 mod data_type_wrapper;
 mod from_data_type;
+mod key_interner;
+mod key_transform;
+#[cfg(feature = "lazy")]
+mod lazy_type_map;
+mod missing_keys_error;
+mod missing_type_error;
+mod numeric;
+mod registration_handle;
+mod required_type;
+mod scoped_registration;
+mod struct_with_extras;
+mod tracked_type_map;
+mod trait_object_reg;
+mod type_dispatch;
 mod type_map;
+#[cfg(feature = "adaptive")]
+mod type_map_adaptive_visitor;
+mod type_map_builder;
+mod type_map_entry;
+mod type_map_flattened_visitor;
+mod type_map_interned_keys_visitor;
+#[cfg(feature = "iter")]
+mod type_map_iter;
 mod type_map_opt;
 mod type_map_opt_visitor;
+#[cfg(feature = "json")]
+mod type_map_positioned_visitor;
+mod type_map_reviver_visitor;
+#[cfg(feature = "id_field")]
+mod type_map_seq_by_id_visitor;
+mod type_map_seq_of_entries_visitor;
+#[cfg(feature = "instrument")]
+mod type_map_timed_visitor;
+mod type_map_unknowns_handler_visitor;
 mod type_map_visitor;
+#[cfg(feature = "yaml_tag")]
+mod type_map_yaml_tag_visitor;
 mod type_reg;
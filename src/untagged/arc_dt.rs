@@ -0,0 +1,229 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+use serde::Serialize;
+
+use crate::{
+    untagged::{BoxDataTypeDowncast, DataType, DataTypeWrapper, FromDataType},
+    TypeNameLit,
+};
+
+/// Reference counted pointer to any type, with no additional trait
+/// constraints.
+///
+/// Clones share the same underlying value. Mutable access clones the value if
+/// it is shared, so mutations never affect other `ArcDt`s.
+#[derive(Clone)]
+pub struct ArcDt(pub(crate) Arc<dyn DataType>);
+
+#[cfg(not(feature = "debug"))]
+impl std::fmt::Debug for ArcDt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("ArcDt").field(&"..").finish()
+    }
+}
+
+#[cfg(feature = "debug")]
+impl std::fmt::Debug for ArcDt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("ArcDt").field(&self.0).finish()
+    }
+}
+
+impl ArcDt {
+    /// Returns a new `ArcDt` wrapper around the provided type.
+    pub fn new<T>(t: T) -> Self
+    where
+        T: DataType,
+    {
+        Self(Arc::new(t))
+    }
+
+    /// Returns a new `ArcDt` wrapper around an already shared value.
+    ///
+    /// This is the inverse of [`into_inner`](Self::into_inner).
+    pub fn from_arc(a: Arc<dyn DataType>) -> Self {
+        Self(a)
+    }
+
+    /// Returns the inner `Arc<dyn DataType>`.
+    pub fn into_inner(self) -> Arc<dyn DataType> {
+        self.0
+    }
+
+    /// Returns whether both `ArcDt`s point to the same value.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl Deref for ArcDt {
+    type Target = dyn DataType;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl DerefMut for ArcDt {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        dyn_clone::arc_make_mut(&mut self.0)
+    }
+}
+
+impl Serialize for ArcDt {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        (*self.0).serialize(serializer)
+    }
+}
+
+impl<T> FromDataType<T> for ArcDt
+where
+    T: DataType,
+{
+    fn from(t: T) -> ArcDt {
+        ArcDt(Arc::new(t))
+    }
+}
+
+impl<T> BoxDataTypeDowncast<T> for ArcDt
+where
+    T: DataType,
+{
+    fn downcast_ref(&self) -> Option<&T> {
+        self.0.downcast_ref::<T>()
+    }
+
+    fn downcast_mut(&mut self) -> Option<&mut T> {
+        if self.0.is::<T>() {
+            dyn_clone::arc_make_mut(&mut self.0).downcast_mut::<T>()
+        } else {
+            None
+        }
+    }
+}
+
+impl DataTypeWrapper for ArcDt {
+    fn type_name(&self) -> TypeNameLit {
+        DataType::type_name(&*self.0)
+    }
+
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+
+    #[cfg(feature = "debug")]
+    fn debug(&self) -> &dyn std::fmt::Debug {
+        &self.0
+    }
+
+    fn inner(&self) -> &dyn DataType {
+        &*self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        any::TypeId,
+        ops::{Deref, DerefMut},
+    };
+
+    use crate::untagged::{BoxDataTypeDowncast, DataTypeWrapper};
+
+    use super::ArcDt;
+
+    #[test]
+    fn from_arc() {
+        let arc_dt = ArcDt::from_arc(ArcDt::new(1u32).into_inner());
+
+        assert_eq!(
+            Some(1u32),
+            BoxDataTypeDowncast::<u32>::downcast_ref(&arc_dt).copied()
+        );
+    }
+
+    #[test]
+    fn clone_shares_value() {
+        let arc_dt = ArcDt::new(1u32);
+        let arc_dt_clone = Clone::clone(&arc_dt);
+
+        assert!(ArcDt::ptr_eq(&arc_dt, &arc_dt_clone));
+    }
+
+    #[test]
+    fn downcast_mut_clones_shared_value() {
+        let arc_dt = ArcDt::new(1u32);
+        let mut arc_dt_clone = Clone::clone(&arc_dt);
+
+        *BoxDataTypeDowncast::<u32>::downcast_mut(&mut arc_dt_clone).unwrap() = 2;
+
+        assert!(!ArcDt::ptr_eq(&arc_dt, &arc_dt_clone));
+        assert_eq!(
+            Some(1u32),
+            BoxDataTypeDowncast::<u32>::downcast_ref(&arc_dt).copied()
+        );
+        assert_eq!(
+            Some(2u32),
+            BoxDataTypeDowncast::<u32>::downcast_ref(&arc_dt_clone).copied()
+        );
+    }
+
+    #[test]
+    fn downcast_mut_returns_none_for_mismatched_type() {
+        let arc_dt = ArcDt::new(1u32);
+        let mut arc_dt_clone = Clone::clone(&arc_dt);
+
+        assert!(BoxDataTypeDowncast::<u64>::downcast_mut(&mut arc_dt_clone).is_none());
+        assert!(ArcDt::ptr_eq(&arc_dt, &arc_dt_clone));
+    }
+
+    #[cfg(not(feature = "debug"))]
+    #[test]
+    fn debug() {
+        let arc_dt = ArcDt::new(1u32);
+
+        assert_eq!(r#"ArcDt("..")"#, format!("{arc_dt:?}"));
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn debug() {
+        let arc_dt = ArcDt::new(1u32);
+
+        assert_eq!("ArcDt(1)", format!("{arc_dt:?}"));
+    }
+
+    #[test]
+    fn inner_type_id_matches_inner_type_type_id() {
+        let arc_dt = ArcDt::new(1u32);
+
+        assert_eq!(TypeId::of::<u32>(), arc_dt.inner().type_id_inner());
+    }
+
+    #[test]
+    fn deref() {
+        let arc_dt = ArcDt::new(1u32);
+        let _data_type = Deref::deref(&arc_dt);
+    }
+
+    #[test]
+    fn deref_mut() {
+        let mut arc_dt = ArcDt::new(1u32);
+        let _data_type = DerefMut::deref_mut(&mut arc_dt);
+    }
+
+    #[test]
+    fn serialize() -> Result<(), serde_yaml::Error> {
+        let arc_dt = ArcDt::new(1u32);
+        let data_type_wrapper: &dyn DataTypeWrapper = &arc_dt;
+
+        assert_eq!("1\n", serde_yaml::to_string(data_type_wrapper)?);
+        Ok(())
+    }
+}
@@ -1,4 +1,8 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    any::Any,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
 
 use serde::Serialize;
 
@@ -34,10 +38,39 @@ impl BoxDt {
         Self(Box::new(t))
     }
 
+    /// Returns a new `BoxDt` wrapper around an already boxed value.
+    ///
+    /// This is the inverse of [`into_inner`](Self::into_inner).
+    pub fn from_box(b: Box<dyn DataType>) -> Self {
+        Self(b)
+    }
+
     /// Returns the inner `Box<dyn DataType>`.
     pub fn into_inner(self) -> Box<dyn DataType> {
         self.0
     }
+
+    /// Returns the inner value as an `Arc<dyn Any + Send + Sync>`.
+    ///
+    /// This allows values to be passed to frameworks that store type erased
+    /// values, and downcast by the concrete type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    ///
+    /// use type_reg::untagged::BoxDt;
+    ///
+    /// let any_arc = BoxDt::new(1u32).into_any_arc();
+    ///
+    /// assert_eq!(Some(&1), any_arc.downcast_ref::<u32>());
+    /// assert!(Arc::downcast::<u32>(any_arc).is_ok());
+    /// ```
+    pub fn into_any_arc(self) -> Arc<dyn Any + Send + Sync> {
+        let data_type: Arc<dyn DataType> = Arc::from(self.0);
+        downcast_rs::DowncastSync::into_any_arc(data_type)
+    }
 }
 
 impl Deref for BoxDt {
@@ -76,6 +109,30 @@ where
     }
 }
 
+/// Compares the boxed value with a value of its concrete type.
+///
+/// Returns `false` if the boxed value is not a `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::BoxDt;
+///
+/// let box_dt = BoxDt::new(1u32);
+///
+/// assert!(box_dt == 1u32);
+/// assert!(box_dt != 2u32);
+/// assert!(box_dt != 1u64);
+/// ```
+impl<T> PartialEq<T> for BoxDt
+where
+    T: DataType + PartialEq,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.0.downcast_ref::<T>() == Some(other)
+    }
+}
+
 impl DataTypeWrapper for BoxDt {
     fn type_name(&self) -> TypeNameLit {
         DataType::type_name(&*self.0)
@@ -91,18 +148,46 @@ impl DataTypeWrapper for BoxDt {
     }
 
     fn inner(&self) -> &dyn DataType {
-        &self.0
+        &*self.0
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ops::{Deref, DerefMut};
+    use std::{
+        any::TypeId,
+        ops::{Deref, DerefMut},
+    };
 
-    use crate::untagged::{BoxDataTypeDowncast, DataTypeWrapper};
+    use crate::{
+        untagged::{BoxDataTypeDowncast, DataType, DataTypeWrapper},
+        TypeNameLit,
+    };
 
     use super::BoxDt;
 
+    #[test]
+    fn into_any_arc() {
+        let any_arc = BoxDt::new(String::from("one")).into_any_arc();
+
+        assert_eq!(TypeId::of::<String>(), (*any_arc).type_id());
+        assert_eq!(
+            Some("one"),
+            any_arc.downcast_ref::<String>().map(String::as_str)
+        );
+        assert!(any_arc.downcast_ref::<u32>().is_none());
+    }
+
+    #[test]
+    fn from_box() {
+        let box_dt = BoxDt::from_box(BoxDt::new(1u32).into_inner());
+
+        assert_eq!(
+            Some(1u32),
+            BoxDataTypeDowncast::<u32>::downcast_ref(&box_dt).copied()
+        );
+    }
+
     #[test]
     fn clone() {
         let box_dt = BoxDt::new(1u32);
@@ -136,6 +221,37 @@ mod tests {
         assert_eq!("BoxDt(1)", format!("{box_dt:?}"));
     }
 
+    #[test]
+    fn partial_eq_inner_type() {
+        let box_dt = BoxDt::new(1u32);
+
+        assert_eq!(box_dt, 1u32);
+        assert_ne!(box_dt, 2u32);
+        assert_ne!(box_dt, 1u64);
+    }
+
+    #[test]
+    fn inner_type_id_matches_inner_type_type_id() {
+        let box_dt = BoxDt::new(1u32);
+
+        assert_eq!(TypeId::of::<u32>(), box_dt.inner().type_id_inner());
+    }
+
+    #[test]
+    fn inner_returns_boxed_value() {
+        let box_dt = BoxDt::new(1u32);
+
+        assert_eq!(TypeNameLit("u32"), DataType::type_name(box_dt.inner()));
+        assert_eq!(Some(&1u32), box_dt.inner().downcast_ref::<u32>());
+    }
+
+    #[test]
+    fn type_id_inner_matches_inner_type_type_id() {
+        let box_dt = BoxDt::new(1u32);
+
+        assert_eq!(TypeId::of::<u32>(), DataTypeWrapper::type_id_inner(&box_dt));
+    }
+
     #[test]
     fn deref() {
         let box_dt = BoxDt::new(1u32);
@@ -31,6 +31,13 @@ impl BoxDtDisplay {
         Self(Box::new(t))
     }
 
+    /// Returns a new `BoxDtDisplay` wrapper around an already boxed value.
+    ///
+    /// This is the inverse of [`into_inner`](Self::into_inner).
+    pub fn from_box(b: Box<dyn DataTypeDisplay>) -> Self {
+        Self(b)
+    }
+
     /// Returns the inner `Box<dyn DataTypeDisplay>`.
     pub fn into_inner(self) -> Box<dyn DataTypeDisplay> {
         self.0
@@ -79,6 +86,30 @@ where
     }
 }
 
+/// Compares the boxed value with a value of its concrete type.
+///
+/// Returns `false` if the boxed value is not a `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::BoxDtDisplay;
+///
+/// let box_dt_display = BoxDtDisplay::new(1u32);
+///
+/// assert!(box_dt_display == 1u32);
+/// assert!(box_dt_display != 2u32);
+/// assert!(box_dt_display != 1u64);
+/// ```
+impl<T> PartialEq<T> for BoxDtDisplay
+where
+    T: DataType + Display + PartialEq,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.0.downcast_ref::<T>() == Some(other)
+    }
+}
+
 impl DataTypeWrapper for BoxDtDisplay {
     fn type_name(&self) -> TypeNameLit {
         DataType::type_name(&*self.0)
@@ -94,18 +125,38 @@ impl DataTypeWrapper for BoxDtDisplay {
     }
 
     fn inner(&self) -> &dyn DataType {
-        &self.0
+        &*self.0
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::ops::{Deref, DerefMut};
+    use std::{
+        any::TypeId,
+        fmt,
+        ops::{Deref, DerefMut},
+    };
+
+    use serde::Serialize;
 
-    use crate::untagged::{BoxDataTypeDowncast, DataTypeWrapper};
+    use crate::{
+        untagged::{BoxDataTypeDowncast, DataType, DataTypeWrapper},
+        TypeNameLit,
+    };
 
     use super::BoxDtDisplay;
 
+    #[test]
+    fn from_box() {
+        let box_dt_display = BoxDtDisplay::from_box(BoxDtDisplay::new(1u32).into_inner());
+
+        assert_eq!(
+            Some(1u32),
+            BoxDataTypeDowncast::<u32>::downcast_ref(&box_dt_display).copied()
+        );
+        assert_eq!("1", box_dt_display.to_string());
+    }
+
     #[test]
     fn clone() {
         let box_dt_display = BoxDtDisplay::new(1u32);
@@ -146,6 +197,72 @@ mod tests {
         assert_eq!("1", format!("{box_dt_display}"));
     }
 
+    #[test]
+    fn display_forwards_width_and_precision() {
+        let box_dt_display = BoxDtDisplay::new(1.2345f64);
+
+        assert_eq!("    1.23", format!("{box_dt_display:>8.2}"));
+        assert_eq!("1.2345  ", format!("{box_dt_display:<8}"));
+    }
+
+    #[test]
+    fn display_forwards_alternate() {
+        let box_dt_display = BoxDtDisplay::new(Alternate);
+
+        assert_eq!("plain", format!("{box_dt_display}"));
+        assert_eq!("alternate", format!("{box_dt_display:#}"));
+    }
+
+    #[derive(Clone, Debug, Serialize)]
+    struct Alternate;
+
+    impl fmt::Display for Alternate {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            if f.alternate() {
+                write!(f, "alternate")
+            } else {
+                write!(f, "plain")
+            }
+        }
+    }
+
+    #[test]
+    fn partial_eq_inner_type() {
+        let box_dt_display = BoxDtDisplay::new(1u32);
+
+        assert_eq!(box_dt_display, 1u32);
+        assert_ne!(box_dt_display, 2u32);
+        assert_ne!(box_dt_display, 1u64);
+    }
+
+    #[test]
+    fn inner_type_id_matches_inner_type_type_id() {
+        let box_dt_display = BoxDtDisplay::new(1u32);
+
+        assert_eq!(TypeId::of::<u32>(), box_dt_display.inner().type_id_inner());
+    }
+
+    #[test]
+    fn inner_returns_boxed_value() {
+        let box_dt_display = BoxDtDisplay::new(1u32);
+
+        assert_eq!(
+            TypeNameLit("u32"),
+            DataType::type_name(box_dt_display.inner())
+        );
+        assert_eq!(Some(&1u32), box_dt_display.inner().downcast_ref::<u32>());
+    }
+
+    #[test]
+    fn type_id_inner_matches_inner_type_type_id() {
+        let box_dt_display = BoxDtDisplay::new(1u32);
+
+        assert_eq!(
+            TypeId::of::<u32>(),
+            DataTypeWrapper::type_id_inner(&box_dt_display)
+        );
+    }
+
     #[test]
     fn deref() {
         let box_dt_display = BoxDtDisplay::new(1u32);
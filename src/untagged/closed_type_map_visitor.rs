@@ -0,0 +1,61 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::untagged::ClosedTypeReg;
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashMap as Map;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexMap as Map;
+
+/// A visitor that deserializes a map of untagged values into variants of
+/// `EnumT`, using a [`ClosedTypeReg`].
+pub(crate) struct ClosedTypeMapVisitor<'r, K, EnumT>
+where
+    K: Eq + Hash + Debug,
+{
+    type_reg: &'r ClosedTypeReg<K, EnumT>,
+}
+
+impl<'r, K, EnumT> ClosedTypeMapVisitor<'r, K, EnumT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// Creates a new visitor with the given [`ClosedTypeReg`].
+    pub(crate) fn new(type_reg: &'r ClosedTypeReg<K, EnumT>) -> Self {
+        Self { type_reg }
+    }
+}
+
+impl<'de, K, EnumT> serde::de::Visitor<'de> for ClosedTypeMapVisitor<'_, K, EnumT>
+where
+    K: Eq + Hash + Debug + serde::Deserialize<'de> + 'de,
+{
+    type Value = Map<K, EnumT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of registered data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut map = match map_access.size_hint() {
+            Some(n) => Map::with_capacity(n),
+            None => Map::new(),
+        };
+
+        while let Some(key) = map_access.next_key::<K>()? {
+            let value = map_access
+                .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
+            map.insert(key, value);
+        }
+
+        Ok(map)
+    }
+}
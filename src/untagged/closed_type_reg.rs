@@ -0,0 +1,264 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde_tagged::de::BoxFnSeed;
+
+use crate::{untagged::closed_type_map_visitor::ClosedTypeMapVisitor, TypeNameLit};
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashMap as Map;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexMap as Map;
+
+/// Map from a given key to logic to deserialize a type into a variant of
+/// `EnumT`.
+///
+/// This is a closed alternative to [`TypeReg`]: when the set of types is
+/// known, each registered type is wrapped in a variant of a user defined enum,
+/// so deserialized values are matched on instead of downcast.
+///
+/// [`TypeReg`]: crate::untagged::TypeReg
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::ClosedTypeReg;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Config {
+///     Port(u16),
+///     Name(String),
+/// }
+///
+/// let mut type_reg = ClosedTypeReg::<String, Config>::new();
+/// type_reg.register_variant::<u16, _>(String::from("port"), Config::Port);
+/// type_reg.register_variant::<String, _>(String::from("name"), Config::Name);
+///
+/// let deserializer = serde_yaml::Deserializer::from_str("port: 8080\nname: server");
+/// let map = type_reg.deserialize_map(deserializer).unwrap();
+///
+/// assert_eq!(Some(&Config::Port(8080)), map.get("port"));
+/// assert_eq!(Some(&Config::Name(String::from("server"))), map.get("name"));
+/// ```
+pub struct ClosedTypeReg<K, EnumT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn_seeds: Map<K, BoxFnSeed<EnumT>>,
+    /// Type names of the types registered against each key.
+    type_names: Map<K, TypeNameLit>,
+}
+
+impl<K, EnumT> ClosedTypeReg<K, EnumT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// Creates an empty `ClosedTypeReg`.
+    ///
+    /// The map is initially created with a capacity of 0, so it will not
+    /// allocate until it is first inserted into.
+    pub fn new() -> Self {
+        Self {
+            fn_seeds: Map::new(),
+            type_names: Map::new(),
+        }
+    }
+
+    /// Registers a type in this type registry, wrapping each deserialized
+    /// value using `variant_fn`.
+    ///
+    /// `variant_fn` is usually the enum variant's constructor, e.g.
+    /// `Config::Port`.
+    pub fn register_variant<R, F>(&mut self, key: K, variant_fn: F)
+    where
+        K: Clone,
+        R: serde::de::DeserializeOwned + 'static,
+        F: Fn(R) -> EnumT + Sync + 'static,
+    {
+        self.fn_seeds.insert(
+            key.clone(),
+            BoxFnSeed::new(move |deserializer| R::deserialize(deserializer).map(&variant_fn)),
+        );
+        self.type_names
+            .insert(key, TypeNameLit(std::any::type_name::<R>()));
+    }
+
+    /// Returns the number of registered types.
+    pub fn len(&self) -> usize {
+        self.fn_seeds.len()
+    }
+
+    /// Returns `true` if no types are registered.
+    pub fn is_empty(&self) -> bool {
+        self.fn_seeds.is_empty()
+    }
+
+    /// Deserializes a map of arbitrary values, wrapping each value in the
+    /// variant registered for its key.
+    ///
+    /// An error is returned if any key is not registered.
+    pub fn deserialize_map<'de, D, E>(&self, deserializer: D) -> Result<Map<K, EnumT>, E>
+    where
+        K: Clone + serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        deserializer.deserialize_map(ClosedTypeMapVisitor::new(self))
+    }
+
+    /// Returns the function to deserialize the value for `type_key`.
+    pub(crate) fn deserialize_seed<E>(&self, type_key: &K) -> Result<&BoxFnSeed<EnumT>, E>
+    where
+        E: serde::de::Error,
+    {
+        self.fn_seeds.get(type_key).ok_or_else(|| {
+            use std::fmt::Write;
+            let mut message = String::with_capacity(256);
+            write!(
+                message,
+                "Type key `{type_key:?}` not registered in closed type registry."
+            )
+            .expect("Failed to write error message");
+
+            message.push_str("\nAvailable types are:\n\n");
+            let mut message = self
+                .fn_seeds
+                .keys()
+                .try_fold(message, |mut message, key| {
+                    writeln!(message, "- {key:?}")?;
+                    Result::<_, fmt::Error>::Ok(message)
+                })
+                .expect("Failed to write error message");
+            message.push('\n');
+
+            serde::de::Error::custom(message)
+        })
+    }
+
+    /// Returns an error that adds the key and registered type name to an error
+    /// from deserializing a value.
+    pub(crate) fn value_error<E>(&self, type_key: &K, error: E) -> E
+    where
+        E: serde::de::Error,
+    {
+        let type_name = self
+            .type_names
+            .get(type_key)
+            .map(|type_name| type_name.0)
+            .unwrap_or("<unknown>");
+
+        serde::de::Error::custom(format!(
+            "Failed to deserialize entry `{type_key:?}` as `{type_name}`: {error}"
+        ))
+    }
+}
+
+impl<K, EnumT> Default for ClosedTypeReg<K, EnumT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, EnumT> Debug for ClosedTypeReg<K, EnumT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug_map = f.debug_map();
+
+        // BoxFnSeed is `!Debug`, so we use the registered type name.
+        self.type_names.iter().for_each(|(k, type_name)| {
+            debug_map.key(&k);
+            debug_map.value(&type_name.0);
+        });
+
+        debug_map.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::ClosedTypeReg;
+
+    #[derive(Debug, PartialEq)]
+    enum Config {
+        Port(u16),
+        Server(Server),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        host: String,
+    }
+
+    fn type_reg() -> ClosedTypeReg<String, Config> {
+        let mut type_reg = ClosedTypeReg::new();
+        type_reg.register_variant::<u16, _>(String::from("port"), Config::Port);
+        type_reg.register_variant::<Server, _>(String::from("server"), Config::Server);
+        type_reg
+    }
+
+    #[test]
+    fn deserialize_map() {
+        let type_reg = type_reg();
+
+        let mut deserializer = serde_json::Deserializer::from_str(
+            r#"{ "port": 8080, "server": { "host": "localhost" } }"#,
+        );
+        let map = type_reg.deserialize_map(&mut deserializer).unwrap();
+
+        assert_eq!(Some(&Config::Port(8080)), map.get("port"));
+        assert_eq!(
+            Some(&Config::Server(Server {
+                host: String::from("localhost")
+            })),
+            map.get("server")
+        );
+    }
+
+    #[test]
+    fn deserialize_map_errs_on_unregistered_key() {
+        let type_reg = type_reg();
+
+        let deserializer = serde_yaml::Deserializer::from_str("name: server");
+        let error = type_reg.deserialize_map(deserializer).unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("Type key `\"name\"` not registered in closed type registry."),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_errs_with_type_name_on_invalid_value() {
+        let type_reg = type_reg();
+
+        let deserializer = serde_yaml::Deserializer::from_str("port: -1");
+        let error = type_reg.deserialize_map(deserializer).unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("Failed to deserialize entry `\"port\"` as `u16`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn debug() {
+        let mut type_reg = ClosedTypeReg::<&'static str, Config>::new();
+        type_reg.register_variant::<u16, _>("port", Config::Port);
+
+        assert_eq!(r#"{"port": "u16"}"#, format!("{type_reg:?}"));
+    }
+}
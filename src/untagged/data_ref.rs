@@ -0,0 +1,75 @@
+use std::fmt;
+
+use crate::{untagged::DataType, TypeNameLit};
+
+/// Reference to a value stored in a [`TypeMap`], regardless of the map's box
+/// type.
+///
+/// This is returned by [`TypeMap::iter_data`], so that code that processes
+/// values does not need to know whether the map stores [`BoxDt`]s or
+/// [`BoxDtDisplay`]s.
+///
+/// [`BoxDt`]: crate::untagged::BoxDt
+/// [`BoxDtDisplay`]: crate::untagged::BoxDtDisplay
+/// [`TypeMap`]: crate::untagged::TypeMap
+/// [`TypeMap::iter_data`]: crate::untagged::TypeMap::iter_data
+#[derive(Clone, Copy)]
+pub struct DataRef<'a>(&'a dyn DataType);
+
+impl<'a> DataRef<'a> {
+    /// Returns a new `DataRef` wrapping the given value.
+    pub fn new(data: &'a dyn DataType) -> Self {
+        Self(data)
+    }
+
+    /// Returns the type name of the value.
+    pub fn type_name(&self) -> TypeNameLit {
+        DataType::type_name(self.0)
+    }
+
+    /// Returns a reference to the value if it is of type `R`.
+    pub fn downcast_ref<R>(&self) -> Option<&'a R>
+    where
+        R: DataType,
+    {
+        self.0.downcast_ref::<R>()
+    }
+
+    /// Returns the underlying `&dyn DataType`.
+    pub fn get(&self) -> &'a dyn DataType {
+        self.0
+    }
+}
+
+impl fmt::Debug for DataRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DataRef").field(&self.type_name()).finish()
+    }
+}
+
+impl serde::Serialize for DataRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        erased_serde::serialize(self.0, serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataRef;
+    use crate::TypeNameLit;
+
+    #[test]
+    fn downcast_ref() {
+        let n = 1u32;
+        let data_ref = DataRef::new(&n);
+
+        assert_eq!(TypeNameLit("u32"), data_ref.type_name());
+        assert_eq!(Some(&1u32), data_ref.downcast_ref::<u32>());
+        assert_eq!(None, data_ref.downcast_ref::<u64>());
+        assert_eq!("DataRef(\"u32\")", format!("{data_ref:?}"));
+        assert_eq!("1", serde_json::to_string(&data_ref).unwrap());
+    }
+}
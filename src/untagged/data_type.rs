@@ -10,6 +10,8 @@ use crate::TypeNameLit;
 pub trait DataType: DowncastSync + DynClone + erased_serde::Serialize {
     fn type_name(&self) -> TypeNameLit;
     fn type_id_inner(&self) -> TypeId;
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap);
 }
 
 #[cfg(all(not(feature = "debug"), not(feature = "resman")))]
@@ -24,6 +26,11 @@ where
     fn type_id_inner(&self) -> TypeId {
         TypeId::of::<T>()
     }
+
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap) {
+        any_map.insert(*self);
+    }
 }
 
 /// Trait to represent the stored type.
@@ -31,6 +38,8 @@ where
 pub trait DataType: resman::Resource + DowncastSync + DynClone + erased_serde::Serialize {
     fn type_name(&self) -> TypeNameLit;
     fn type_id_inner(&self) -> TypeId;
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap);
     fn upcast(self: Box<Self>) -> Box<dyn resman::Resource>;
 }
 
@@ -47,6 +56,11 @@ where
         TypeId::of::<T>()
     }
 
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap) {
+        any_map.insert(*self);
+    }
+
     fn upcast(self: Box<Self>) -> Box<dyn resman::Resource> {
         self
     }
@@ -57,6 +71,8 @@ where
 pub trait DataType: DowncastSync + DynClone + std::fmt::Debug + erased_serde::Serialize {
     fn type_name(&self) -> TypeNameLit;
     fn type_id_inner(&self) -> TypeId;
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap);
 }
 
 #[cfg(all(feature = "debug", not(feature = "resman")))]
@@ -71,6 +87,11 @@ where
     fn type_id_inner(&self) -> TypeId {
         TypeId::of::<T>()
     }
+
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap) {
+        any_map.insert(*self);
+    }
 }
 
 /// Trait to represent the stored type.
@@ -80,6 +101,8 @@ pub trait DataType:
 {
     fn type_name(&self) -> TypeNameLit;
     fn type_id_inner(&self) -> TypeId;
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap);
     fn upcast(self: Box<Self>) -> Box<dyn resman::Resource>;
 }
 
@@ -96,6 +119,11 @@ where
         TypeId::of::<T>()
     }
 
+    #[cfg(feature = "anymap2")]
+    fn insert_into_any_map(self: Box<Self>, any_map: &mut anymap2::AnyMap) {
+        any_map.insert(*self);
+    }
+
     fn upcast(self: Box<Self>) -> Box<dyn resman::Resource> {
         self
     }
@@ -1,3 +1,5 @@
+use std::any::TypeId;
+
 use crate::{untagged::DataType, TypeNameLit};
 
 /// Trait to represent the stored type.
@@ -10,6 +12,14 @@ pub trait DataTypeWrapper: erased_serde::Serialize {
         Self: Sized;
 
     fn inner(&self) -> &dyn DataType;
+
+    /// Returns the `TypeId` of the inner data type.
+    ///
+    /// This is the same as `self.inner().type_id_inner()`, and allows values
+    /// to be compared by type without attempting a downcast.
+    fn type_id_inner(&self) -> TypeId {
+        self.inner().type_id_inner()
+    }
 }
 
 /// Trait to represent the stored type.
@@ -24,6 +34,28 @@ pub trait DataTypeWrapper: std::fmt::Debug + erased_serde::Serialize {
     fn debug(&self) -> &dyn std::fmt::Debug;
 
     fn inner(&self) -> &dyn DataType;
+
+    /// Returns the `TypeId` of the inner data type.
+    ///
+    /// This is the same as `self.inner().type_id_inner()`, and allows values
+    /// to be compared by type without attempting a downcast.
+    fn type_id_inner(&self) -> TypeId {
+        self.inner().type_id_inner()
+    }
+}
+
+impl<T> crate::TypedDebug for T
+where
+    T: DataTypeWrapper,
+{
+    fn type_name(&self) -> TypeNameLit {
+        DataTypeWrapper::type_name(self)
+    }
+
+    #[cfg(feature = "debug")]
+    fn debug(&self) -> &dyn std::fmt::Debug {
+        DataTypeWrapper::debug(self)
+    }
 }
 
 impl serde::Serialize for dyn DataTypeWrapper + '_ {
@@ -0,0 +1,72 @@
+use std::{collections::HashSet, hash::Hash};
+
+/// Cache of keys, so that equal keys deserialized across many maps share
+/// storage.
+///
+/// This is used with [`TypeReg::deserialize_map_interned_keys`]. Storage is
+/// only shared when cloning `K` shares storage, such as for `Arc<str>`. For
+/// `String` keys, each clone is a separate allocation.
+///
+/// [`TypeReg::deserialize_map_interned_keys`]: crate::untagged::TypeReg::deserialize_map_interned_keys
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use type_reg::untagged::KeyInterner;
+///
+/// let mut key_interner = KeyInterner::<Arc<str>>::new();
+/// let one_a = key_interner.intern(Arc::from("one"));
+/// let one_b = key_interner.intern(Arc::from("one"));
+///
+/// assert!(Arc::ptr_eq(&one_a, &one_b));
+/// assert_eq!(1, key_interner.len());
+/// ```
+#[derive(Clone, Debug)]
+pub struct KeyInterner<K> {
+    keys: HashSet<K>,
+}
+
+impl<K> KeyInterner<K>
+where
+    K: Clone + Eq + Hash,
+{
+    /// Returns a new, empty `KeyInterner`.
+    pub fn new() -> Self {
+        Self {
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Returns a clone of the cached key equal to `key`, caching `key` if
+    /// there is none.
+    pub fn intern(&mut self, key: K) -> K {
+        match self.keys.get(&key) {
+            Some(key_interned) => key_interned.clone(),
+            None => {
+                self.keys.insert(key.clone());
+                key
+            }
+        }
+    }
+
+    /// Returns the number of cached keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if no keys are cached.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<K> Default for KeyInterner<K>
+where
+    K: Clone + Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,148 @@
+/// Common key transforms for [`TypeReg::with_key_transform`].
+///
+/// Keys are split into words at `_`, `-`, and spaces, and where an uppercase
+/// letter follows a lowercase letter or digit. Consecutive uppercase letters
+/// are treated as one word, e.g. `"HTTPServer"` is split into `"http"` and
+/// `"server"`.
+///
+/// [`TypeReg::with_key_transform`]: crate::untagged::TypeReg::with_key_transform
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::KeyTransform;
+///
+/// assert_eq!(
+///     "max-connections",
+///     KeyTransform::kebab_case("max_connections")
+/// );
+/// assert_eq!(
+///     "maxConnections",
+///     KeyTransform::camel_case("max_connections")
+/// );
+/// assert_eq!(
+///     "max_connections",
+///     KeyTransform::snake_case("maxConnections")
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct KeyTransform;
+
+impl KeyTransform {
+    /// Returns the key in `kebab-case`.
+    pub fn kebab_case(key: &str) -> String {
+        Self::words(key).join("-")
+    }
+
+    /// Returns the key in `camelCase`.
+    pub fn camel_case(key: &str) -> String {
+        Self::words(key)
+            .into_iter()
+            .enumerate()
+            .map(|(index, word)| {
+                if index == 0 {
+                    word
+                } else {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the key in `snake_case`.
+    pub fn snake_case(key: &str) -> String {
+        Self::words(key).join("_")
+    }
+
+    /// Splits the key into lowercase words.
+    fn words(key: &str) -> Vec<String> {
+        let chars = key.chars().collect::<Vec<char>>();
+        let mut words = Vec::new();
+        let mut word = String::new();
+
+        chars.iter().enumerate().for_each(|(index, c)| {
+            if matches!(c, '_' | '-' | ' ') {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                return;
+            }
+
+            if c.is_uppercase() && !word.is_empty() {
+                let prev = chars[index - 1];
+                let next_is_lowercase = chars.get(index + 1).is_some_and(|c| c.is_lowercase());
+                if prev.is_lowercase()
+                    || prev.is_ascii_digit()
+                    || (prev.is_uppercase() && next_is_lowercase)
+                {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+
+            word.extend(c.to_lowercase());
+        });
+
+        if !word.is_empty() {
+            words.push(word);
+        }
+
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyTransform;
+
+    #[test]
+    fn kebab_case() {
+        assert_eq!(
+            "max-connections",
+            KeyTransform::kebab_case("max_connections")
+        );
+        assert_eq!(
+            "max-connections",
+            KeyTransform::kebab_case("maxConnections")
+        );
+        assert_eq!(
+            "max-connections",
+            KeyTransform::kebab_case("max-connections")
+        );
+        assert_eq!("http-server-2", KeyTransform::kebab_case("HTTPServer_2"));
+    }
+
+    #[test]
+    fn camel_case() {
+        assert_eq!(
+            "maxConnections",
+            KeyTransform::camel_case("max_connections")
+        );
+        assert_eq!(
+            "maxConnections",
+            KeyTransform::camel_case("max-connections")
+        );
+        assert_eq!("maxConnections", KeyTransform::camel_case("maxConnections"));
+        assert_eq!("httpServer", KeyTransform::camel_case("HTTPServer"));
+    }
+
+    #[test]
+    fn snake_case() {
+        assert_eq!(
+            "max_connections",
+            KeyTransform::snake_case("maxConnections")
+        );
+        assert_eq!(
+            "max_connections",
+            KeyTransform::snake_case("max-connections")
+        );
+        assert_eq!(
+            "max_connections",
+            KeyTransform::snake_case("max_connections")
+        );
+        assert_eq!("version2_name", KeyTransform::snake_case("version2Name"));
+    }
+}
@@ -0,0 +1,466 @@
+use std::{
+    borrow::Borrow,
+    cell::OnceCell,
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::DeserializeSeed;
+use serde_value::{DeserializerError, Value, ValueDeserializer};
+
+use crate::untagged::{BoxDataTypeDowncast, BoxDt, DataTypeWrapper, TypeMap, TypeReg};
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashMap as Map;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexMap as Map;
+
+/// Map of buffered values that are deserialized on first access.
+///
+/// This is returned by [`TypeReg::deserialize_map_lazy`]. Each value is
+/// stored as a [`serde_value::Value`], and is deserialized using the
+/// [`TypeReg`] the first time it is accessed. The deserialized value is cached
+/// for subsequent accesses.
+///
+/// This is useful for large documents where most entries are never read, and
+/// the registered types are expensive to deserialize.
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::{LazyTypeMap, TypeReg};
+///
+/// let mut type_reg = TypeReg::<String>::new();
+/// type_reg.register::<u32>(String::from("one"));
+/// type_reg.register::<u64>(String::from("two"));
+///
+/// let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+/// let lazy_type_map: LazyTypeMap<'_, String> =
+///     type_reg.deserialize_map_lazy(deserializer).unwrap();
+///
+/// assert!(!lazy_type_map.is_deserialized("one"));
+/// assert_eq!(Some(1), lazy_type_map.get::<u32, _>("one").copied());
+/// assert!(lazy_type_map.is_deserialized("one"));
+/// assert!(!lazy_type_map.is_deserialized("two"));
+/// ```
+///
+/// [`TypeReg::deserialize_map_lazy`]: crate::untagged::TypeReg::deserialize_map_lazy
+pub struct LazyTypeMap<'r, K, BoxDT = BoxDt>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Registry used to deserialize each value.
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Buffered values, and their deserialized form once accessed.
+    entries: Map<K, LazyValue<BoxDT>>,
+}
+
+/// Buffered value, and its deserialized form once accessed.
+struct LazyValue<BoxDT> {
+    /// The buffered value.
+    value: Value,
+    /// The deserialized value.
+    box_dt: OnceCell<BoxDT>,
+}
+
+impl<K, BoxDT> LazyTypeMap<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns whether the map contains an entry for the key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    pub fn contains_key<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.contains_key(q)
+    }
+
+    /// Returns an iterator over the keys of the map.
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.entries.keys()
+    }
+
+    /// Returns whether the value for the key has been deserialized.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    pub fn is_deserialized<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries
+            .get(q)
+            .is_some_and(|lazy_value| lazy_value.box_dt.get().is_some())
+    }
+
+    /// Returns a reference to the value corresponding to the key,
+    /// deserializing it if it has not been accessed before.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// If there is an entry, but it fails to deserialize, or the data type
+    /// does not match, `None` is returned. Use [`get_raw`] to access the
+    /// deserialization error.
+    ///
+    /// [`get_raw`]: Self::get_raw
+    pub fn get<R, Q>(&self, q: &Q) -> Option<&R>
+    where
+        K: Borrow<Q>,
+        BoxDT: BoxDataTypeDowncast<R>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_raw(q)?
+            .ok()
+            .and_then(BoxDataTypeDowncast::<R>::downcast_ref)
+    }
+
+    /// Returns a reference to the boxed value corresponding to the key,
+    /// deserializing it if it has not been accessed before.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// Returns `None` if there is no entry for the key. A value that fails to
+    /// deserialize is not cached, so accessing it again returns the error
+    /// again.
+    pub fn get_raw<Q>(&self, q: &Q) -> Option<Result<&BoxDT, DeserializerError>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (key, lazy_value) = self.entries.get_key_value(q)?;
+        if let Some(box_dt) = lazy_value.box_dt.get() {
+            return Some(Ok(box_dt));
+        }
+
+        let box_dt = Self::deserialize_value(self.type_reg, key, lazy_value.value.clone());
+        Some(box_dt.map(|box_dt| lazy_value.box_dt.get_or_init(|| box_dt)))
+    }
+
+    /// Drops the deserialized value for the key, keeping the buffered value.
+    ///
+    /// The next access to the key deserializes the buffered value again. This
+    /// allows the map to be used as a cache, whose deserialized values are
+    /// dropped to reclaim memory.
+    ///
+    /// Returns whether a deserialized value was dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{LazyTypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    /// let mut lazy_type_map: LazyTypeMap<'_, String> =
+    ///     type_reg.deserialize_map_lazy(deserializer).unwrap();
+    ///
+    /// assert_eq!(Some(1), lazy_type_map.get::<u32, _>("one").copied());
+    /// assert!(lazy_type_map.evict("one"));
+    /// assert!(!lazy_type_map.is_deserialized("one"));
+    /// assert_eq!(Some(1), lazy_type_map.get::<u32, _>("one").copied());
+    /// ```
+    pub fn evict<Q>(&mut self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries
+            .get_mut(q)
+            .is_some_and(|lazy_value| lazy_value.box_dt.take().is_some())
+    }
+
+    /// Drops all deserialized values, keeping the buffered values.
+    ///
+    /// See [`evict`](Self::evict).
+    pub fn evict_all(&mut self) {
+        self.entries.values_mut().for_each(|lazy_value| {
+            lazy_value.box_dt.take();
+        });
+    }
+
+    /// Deserializes all remaining values, and returns them as a [`TypeMap`].
+    pub fn into_type_map(self) -> Result<TypeMap<K, BoxDT>, DeserializerError> {
+        let mut type_map = TypeMap::with_capacity_typed(self.entries.len());
+        let type_reg = self.type_reg;
+        self.entries
+            .into_iter()
+            .try_for_each(|(key, lazy_value)| {
+                let LazyValue { value, box_dt } = lazy_value;
+                let box_dt = match box_dt.into_inner() {
+                    Some(box_dt) => box_dt,
+                    None => Self::deserialize_value(type_reg, &key, value)?,
+                };
+                type_map.insert_raw(key, box_dt);
+                Ok(())
+            })
+            .map(|()| type_map)
+    }
+
+    /// Deserializes a buffered value as the type registered for `key`.
+    fn deserialize_value(
+        type_reg: &TypeReg<K, BoxDT>,
+        key: &K,
+        value: Value,
+    ) -> Result<BoxDT, DeserializerError> {
+        type_reg
+            .deserialize_seed::<DeserializerError>(key)?
+            .deserialize(ValueDeserializer::<DeserializerError>::new(value))
+            .map_err(|error| type_reg.value_error(key, error))
+    }
+}
+
+impl<K, BoxDT> Debug for LazyTypeMap<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+    BoxDT: DataTypeWrapper,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug_map = f.debug_map();
+
+        self.entries.iter().for_each(|(k, lazy_value)| {
+            debug_map.key(&k);
+            match lazy_value.box_dt.get() {
+                Some(box_dt) => debug_map.value(&crate::typed_value(box_dt)),
+                None => debug_map.value(&"<not deserialized>"),
+            };
+        });
+
+        debug_map.finish()
+    }
+}
+
+/// A visitor that buffers each value of a map into a [`LazyTypeMap`].
+///
+/// Keys are checked against the [`TypeReg`] while buffering, so unregistered
+/// keys are reported as errors when deserializing the map.
+pub(crate) struct LazyTypeMapVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Registry used to deserialize each value.
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'r, K, BoxDT> LazyTypeMapVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`].
+    pub(crate) fn new(type_reg: &'r TypeReg<K, BoxDT>) -> Self {
+        Self { type_reg }
+    }
+}
+
+impl<'de, 'r, K, BoxDT> serde::de::Visitor<'de> for LazyTypeMapVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = LazyTypeMap<'r, K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut entries = match map_access.size_hint() {
+            Some(n) => Map::with_capacity(n),
+            _ => Map::new(),
+        };
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            self.type_reg.deserialize_seed::<A::Error>(&key)?;
+
+            let value = map_access.next_value::<Value>()?;
+            entries.insert(
+                key,
+                LazyValue {
+                    value,
+                    box_dt: OnceCell::new(),
+                },
+            );
+        }
+
+        Ok(LazyTypeMap {
+            type_reg: self.type_reg,
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::untagged::{LazyTypeMap, TypeReg};
+
+    fn type_reg() -> TypeReg<String> {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+        type_reg.register::<u32>(String::from("three"));
+        type_reg
+    }
+
+    #[test]
+    fn get_deserializes_on_first_access() {
+        let type_reg = type_reg();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(deserializer).unwrap();
+
+        assert_eq!(2, lazy_type_map.len());
+        assert!(!lazy_type_map.is_empty());
+        assert!(lazy_type_map.contains_key("two"));
+        assert!(!lazy_type_map.is_deserialized("two"));
+
+        assert_eq!(Some(A(2)), lazy_type_map.get::<A, _>("two").copied());
+        assert!(lazy_type_map.is_deserialized("two"));
+        assert!(!lazy_type_map.is_deserialized("one"));
+
+        // Cached value is returned on subsequent access.
+        assert_eq!(Some(A(2)), lazy_type_map.get::<A, _>("two").copied());
+        assert_eq!(None, lazy_type_map.get::<u32, _>("two"));
+        assert_eq!(None, lazy_type_map.get::<u32, _>("four"));
+    }
+
+    #[test]
+    fn get_raw_returns_value_error_and_does_not_cache_it() {
+        let type_reg = type_reg();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, three: abc }");
+        let lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(deserializer).unwrap();
+
+        let error = lazy_type_map.get_raw("three").unwrap().unwrap_err();
+
+        assert!(error.to_string().contains("`\"three\"`"), "{error}");
+        assert!(!lazy_type_map.is_deserialized("three"));
+        assert_eq!(None, lazy_type_map.get::<u32, _>("three"));
+        assert!(lazy_type_map.get_raw("four").is_none());
+    }
+
+    #[test]
+    fn get_raw_returns_null_hint_for_buffered_null() {
+        let type_reg = type_reg();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: ~ }");
+        let lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(deserializer).unwrap();
+
+        let error = lazy_type_map.get_raw("one").unwrap().unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("Entry `\"one\"` was null, but type `u32` is not optional;"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_lazy_returns_error_for_unregistered_key() {
+        let type_reg = type_reg();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, four: 4 }");
+
+        let error = type_reg
+            .deserialize_map_lazy::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("Type key `\"four\"` not registered"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn evict_all_drops_deserialized_values() {
+        let type_reg = type_reg();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let mut lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(deserializer).unwrap();
+        assert_eq!(Some(1u32), lazy_type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), lazy_type_map.get::<A, _>("two").copied());
+
+        lazy_type_map.evict_all();
+
+        assert!(!lazy_type_map.is_deserialized("one"));
+        assert!(!lazy_type_map.is_deserialized("two"));
+        assert!(!lazy_type_map.evict("one"));
+        assert!(!lazy_type_map.evict("four"));
+        assert_eq!(Some(A(2)), lazy_type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn into_type_map_deserializes_remaining_values() {
+        let type_reg = type_reg();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(deserializer).unwrap();
+        assert_eq!(Some(1u32), lazy_type_map.get::<u32, _>("one").copied());
+
+        let type_map = lazy_type_map.into_type_map().unwrap();
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn debug() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<A>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\n");
+        let lazy_type_map: LazyTypeMap<'_, String> =
+            type_reg.deserialize_map_lazy(deserializer).unwrap();
+        lazy_type_map.get::<A, _>("one");
+
+        let debug_str = format!("{lazy_type_map:?}");
+
+        #[cfg(not(feature = "debug"))]
+        assert!(
+            debug_str.contains(
+                r#""one": TypedValue { type: "type_reg::untagged::lazy_type_map::tests::A", value: ".." }"#
+            ),
+            "{debug_str}"
+        );
+        #[cfg(feature = "debug")]
+        assert!(
+            debug_str.contains(
+                r#""one": TypedValue { type: "type_reg::untagged::lazy_type_map::tests::A", value: A(1) }"#
+            ),
+            "{debug_str}"
+        );
+        assert!(
+            debug_str.contains(r#""two": "<not deserialized>""#),
+            "{debug_str}"
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+    struct A(u32);
+}
@@ -0,0 +1,40 @@
+use std::fmt::{self, Debug};
+
+/// Error returned by [`TypeMapBuilder::build`] when required keys were not
+/// inserted.
+///
+/// [`TypeMapBuilder::build`]: crate::untagged::TypeMapBuilder::build
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MissingKeysError<K> {
+    /// Required keys that were not inserted, in the order they were required.
+    pub(crate) keys: Vec<K>,
+}
+
+impl<K> MissingKeysError<K> {
+    /// Returns the required keys that were not inserted.
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the required keys that were not inserted.
+    pub fn into_keys(self) -> Vec<K> {
+        self.keys
+    }
+}
+
+impl<K> fmt::Display for MissingKeysError<K>
+where
+    K: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Required keys were not inserted: ")?;
+        self.keys.iter().enumerate().try_for_each(|(index, key)| {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{key:?}`")
+        })
+    }
+}
+
+impl<K> std::error::Error for MissingKeysError<K> where K: Debug {}
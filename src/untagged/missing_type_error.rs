@@ -0,0 +1,44 @@
+use std::fmt;
+
+use crate::TypeNameLit;
+
+/// Error returned by [`TypeMap::require_type`] and [`TypeMap::require_types`]
+/// when no entry has a required type.
+///
+/// [`TypeMap::require_type`]: crate::untagged::TypeMap::require_type
+/// [`TypeMap::require_types`]: crate::untagged::TypeMap::require_types
+#[derive(Debug, PartialEq, Eq)]
+pub struct MissingTypeError {
+    /// Names of the required types that have no entries, in the order they
+    /// were required.
+    pub(crate) type_names: Vec<TypeNameLit>,
+}
+
+impl MissingTypeError {
+    /// Returns the names of the required types that have no entries.
+    pub fn type_names(&self) -> &[TypeNameLit] {
+        &self.type_names
+    }
+
+    /// Returns the names of the required types that have no entries.
+    pub fn into_type_names(self) -> Vec<TypeNameLit> {
+        self.type_names
+    }
+}
+
+impl fmt::Display for MissingTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "No entries exist for required types: ")?;
+        self.type_names
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, type_name)| {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "`{type_name}`")
+            })
+    }
+}
+
+impl std::error::Error for MissingTypeError {}
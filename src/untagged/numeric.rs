@@ -0,0 +1,67 @@
+use crate::untagged::DataRef;
+
+/// Integer types that [`TypeMap::get_as`] can convert between.
+///
+/// A value of any integer type converts to `Self` when the value fits within
+/// `Self`'s bounds.
+///
+/// [`TypeMap::get_as`]: crate::untagged::TypeMap::get_as
+pub trait Numeric: Sized {
+    /// Returns the value converted to `Self`, or `None` if it is not an
+    /// integer or does not fit within `Self`.
+    fn try_from_data(data: DataRef<'_>) -> Option<Self>;
+}
+
+/// Returns the first successful conversion from the listed source types.
+macro_rules! try_from_data {
+    ($data:ident, $target:ty, [$($source:ty),+ $(,)?]) => {
+        None$(.or_else(|| {
+            $data
+                .downcast_ref::<$source>()
+                .and_then(|value| <$target>::try_from(*value).ok())
+        }))+
+    };
+}
+
+macro_rules! impl_numeric {
+    ($($target:ty),+ $(,)?) => {
+        $(
+            impl Numeric for $target {
+                fn try_from_data(data: DataRef<'_>) -> Option<Self> {
+                    try_from_data!(
+                        data,
+                        $target,
+                        [u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize]
+                    )
+                }
+            }
+        )+
+    };
+}
+
+impl_numeric!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::Numeric;
+    use crate::untagged::DataRef;
+
+    #[test]
+    fn try_from_data_converts_within_bounds() {
+        assert_eq!(Some(1u64), u64::try_from_data(DataRef::new(&1u32)));
+        assert_eq!(Some(-1i64), i64::try_from_data(DataRef::new(&-1i8)));
+        assert_eq!(Some(255u8), u8::try_from_data(DataRef::new(&255i128)));
+    }
+
+    #[test]
+    fn try_from_data_returns_none_out_of_bounds() {
+        assert_eq!(None, u8::try_from_data(DataRef::new(&256u16)));
+        assert_eq!(None, u32::try_from_data(DataRef::new(&-1i32)));
+    }
+
+    #[test]
+    fn try_from_data_returns_none_for_non_integers() {
+        assert_eq!(None, u32::try_from_data(DataRef::new(&1.0f64)));
+        assert_eq!(None, u32::try_from_data(DataRef::new(&String::from("1"))));
+    }
+}
@@ -0,0 +1,29 @@
+use crate::TypeNameLit;
+
+/// Handle to a type registration, used to remove exactly that registration.
+///
+/// This is returned by [`TypeReg::register_handle`], and passed to
+/// [`TypeReg::unregister_handle`].
+///
+/// [`TypeReg::register_handle`]: crate::untagged::TypeReg::register_handle
+/// [`TypeReg::unregister_handle`]: crate::untagged::TypeReg::unregister_handle
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "dropping the handle does not unregister the type"]
+pub struct RegistrationHandle<K> {
+    /// Key that the type was registered against.
+    pub(crate) key: K,
+    /// Name of the registered type.
+    pub(crate) type_name: TypeNameLit,
+}
+
+impl<K> RegistrationHandle<K> {
+    /// Returns the key that the type was registered against.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the name of the registered type.
+    pub fn type_name(&self) -> TypeNameLit {
+        TypeNameLit(self.type_name.0)
+    }
+}
@@ -0,0 +1,36 @@
+use std::any::{Any, TypeId};
+
+use crate::TypeNameLit;
+
+/// A type that must have at least one entry in a [`TypeMap`].
+///
+/// This is passed to [`TypeMap::require_types`] to check multiple types at
+/// once.
+///
+/// [`TypeMap`]: crate::untagged::TypeMap
+/// [`TypeMap::require_types`]: crate::untagged::TypeMap::require_types
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequiredType {
+    /// `TypeId` of the required type.
+    pub(crate) type_id: TypeId,
+    /// Name of the required type, used in error messages.
+    pub(crate) type_name: &'static str,
+}
+
+impl RequiredType {
+    /// Returns a `RequiredType` for `R`.
+    pub fn of<R>() -> Self
+    where
+        R: Any,
+    {
+        Self {
+            type_id: TypeId::of::<R>(),
+            type_name: std::any::type_name::<R>(),
+        }
+    }
+
+    /// Returns the name of the required type.
+    pub fn type_name(&self) -> TypeNameLit {
+        TypeNameLit(self.type_name)
+    }
+}
@@ -0,0 +1,77 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Deref, DerefMut},
+};
+
+use crate::untagged::{BoxDt, RegistrationHandle, TypeReg};
+
+/// Type registration that is removed from the [`TypeReg`] when dropped.
+///
+/// This is returned by [`TypeReg::register_scoped`]. While it is alive, the
+/// `TypeReg` is mutably borrowed, so it is accessed through this guard, which
+/// dereferences to the `TypeReg`.
+///
+/// [`TypeReg::register_scoped`]: crate::untagged::TypeReg::register_scoped
+#[must_use = "the type is unregistered as soon as this is dropped"]
+pub struct ScopedRegistration<'r, K, BoxDT = BoxDt>
+where
+    K: Eq + Hash + Debug,
+{
+    /// The registry the type is registered in.
+    type_reg: &'r mut TypeReg<K, BoxDT>,
+    /// Handle to the registration, `None` once it is released.
+    handle: Option<RegistrationHandle<K>>,
+}
+
+impl<'r, K, BoxDT> ScopedRegistration<'r, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// Returns a new `ScopedRegistration`.
+    pub(crate) fn new(type_reg: &'r mut TypeReg<K, BoxDT>, handle: RegistrationHandle<K>) -> Self {
+        Self {
+            type_reg,
+            handle: Some(handle),
+        }
+    }
+
+    /// Keeps the registration, returning its handle so that it can be
+    /// unregistered later.
+    pub fn into_handle(mut self) -> RegistrationHandle<K> {
+        self.handle
+            .take()
+            .expect("Expected registration handle to be present until dropped.")
+    }
+}
+
+impl<K, BoxDT> Deref for ScopedRegistration<'_, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    type Target = TypeReg<K, BoxDT>;
+
+    fn deref(&self) -> &Self::Target {
+        self.type_reg
+    }
+}
+
+impl<K, BoxDT> DerefMut for ScopedRegistration<'_, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.type_reg
+    }
+}
+
+impl<K, BoxDT> Drop for ScopedRegistration<'_, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.type_reg.unregister_handle(handle);
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    borrow::Borrow,
+    fmt::{self, Debug},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use serde::de::{
+    value::{MapAccessDeserializer, StrDeserializer},
+    DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, Visitor,
+};
+
+use crate::untagged::{BoxDt, DataTypeWrapper, TypeMap, TypeReg};
+
+/// A struct of fixed typed fields, plus a [`TypeMap`] of the remaining
+/// entries.
+///
+/// This is deserialized from a single map using
+/// [`TypeReg::deserialize_struct_with_extras`]. Entries whose keys match a
+/// field of `Fixed` are deserialized into `Fixed`, and all other entries are
+/// deserialized using the [`TypeReg`].
+pub struct StructWithExtras<Fixed, K, BoxDT = BoxDt>
+where
+    K: Eq + Hash,
+{
+    /// The fixed typed fields.
+    pub fixed: Fixed,
+    /// The entries that are not fields of `Fixed`.
+    pub extras: TypeMap<K, BoxDT>,
+}
+
+impl<Fixed, K, BoxDT> Clone for StructWithExtras<Fixed, K, BoxDT>
+where
+    Fixed: Clone,
+    K: Clone + Eq + Hash,
+    BoxDT: DataTypeWrapper,
+{
+    fn clone(&self) -> Self {
+        Self {
+            fixed: self.fixed.clone(),
+            extras: self.extras.clone(),
+        }
+    }
+}
+
+impl<Fixed, K, BoxDT> Debug for StructWithExtras<Fixed, K, BoxDT>
+where
+    Fixed: Debug,
+    K: Eq + Hash + Debug,
+    BoxDT: DataTypeWrapper,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StructWithExtras")
+            .field("fixed", &self.fixed)
+            .field("extras", &self.extras)
+            .finish()
+    }
+}
+
+/// Returns the field names of `Fixed`, or `None` if `Fixed` is not a struct.
+///
+/// This is determined by recording the fields that `Fixed`'s `Deserialize`
+/// implementation passes to `Deserializer::deserialize_struct`.
+pub(crate) fn struct_fields<Fixed>() -> Option<&'static [&'static str]>
+where
+    Fixed: DeserializeOwned,
+{
+    let mut fields = None;
+    let _result = Fixed::deserialize(StructFieldsProbe {
+        fields: &mut fields,
+    });
+    fields
+}
+
+/// Deserializer that records the fields passed to `deserialize_struct`.
+///
+/// Deserialization always fails, as only the field names are needed.
+struct StructFieldsProbe<'f> {
+    /// Where to record the field names.
+    fields: &'f mut Option<&'static [&'static str]>,
+}
+
+impl<'de> Deserializer<'de> for StructFieldsProbe<'_> {
+    type Error = serde::de::value::Error;
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(serde::de::Error::custom("expected a struct"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        *self.fields = Some(fields);
+        Err(serde::de::Error::custom("struct fields recorded"))
+    }
+}
+
+/// A visitor that deserializes a map into a [`StructWithExtras`].
+pub(crate) struct StructWithExtrasVisitor<'r, Fixed, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// Registry used to deserialize entries that are not fields of `Fixed`.
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Field names of `Fixed`.
+    fields: &'static [&'static str],
+    /// Marker for the fixed type.
+    marker: PhantomData<Fixed>,
+}
+
+impl<'r, Fixed, K, BoxDT> StructWithExtrasVisitor<'r, Fixed, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and `Fixed` field
+    /// names.
+    pub(crate) fn new(type_reg: &'r TypeReg<K, BoxDT>, fields: &'static [&'static str]) -> Self {
+        Self {
+            type_reg,
+            fields,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, Fixed, K, BoxDT> Visitor<'de> for StructWithExtrasVisitor<'_, Fixed, K, BoxDT>
+where
+    Fixed: DeserializeOwned,
+    K: Borrow<str> + Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = StructWithExtras<Fixed, K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of fixed fields and arbitrary data types")
+    }
+
+    fn visit_map<A>(self, map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut extras = TypeMap::new_typed();
+        let fixed = Fixed::deserialize(MapAccessDeserializer::new(ExtrasMapAccess {
+            map_access,
+            type_reg: self.type_reg,
+            fields: self.fields,
+            extras: &mut extras,
+        }))?;
+
+        Ok(StructWithExtras { fixed, extras })
+    }
+}
+
+/// `MapAccess` that only yields entries for `Fixed`'s fields, and
+/// deserializes all other entries into `extras`.
+struct ExtrasMapAccess<'r, 'x, A, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// The wrapped map access.
+    map_access: A,
+    /// Registry used to deserialize entries that are not fields of `Fixed`.
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Field names of `Fixed`.
+    fields: &'static [&'static str],
+    /// Entries that are not fields of `Fixed`.
+    extras: &'x mut TypeMap<K, BoxDT>,
+}
+
+impl<'de, A, K, BoxDT> MapAccess<'de> for ExtrasMapAccess<'_, '_, A, K, BoxDT>
+where
+    A: MapAccess<'de>,
+    K: Borrow<str> + Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        while let Some(key) = self.map_access.next_key::<K>()? {
+            if self.fields.contains(&key.borrow()) {
+                return seed
+                    .deserialize(StrDeserializer::<A::Error>::new(key.borrow()))
+                    .map(Some);
+            }
+
+            let key = self.type_reg.fold_key(key);
+            let value = self
+                .map_access
+                .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
+            self.extras.insert_raw(key, value);
+        }
+
+        Ok(None)
+    }
+
+    fn next_value_seed<S>(&mut self, seed: S) -> Result<S::Value, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        self.map_access.next_value_seed(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::untagged::TypeReg;
+
+    use super::struct_fields;
+
+    #[test]
+    fn struct_fields_returns_fields_of_struct() {
+        assert_eq!(Some(&["name", "count"][..]), struct_fields::<Fixed>());
+    }
+
+    #[test]
+    fn struct_fields_returns_none_for_non_struct() {
+        assert_eq!(None, struct_fields::<u32>());
+    }
+
+    #[test]
+    fn deserialize_struct_with_extras() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let deserializer = serde_yaml::Deserializer::from_str(
+            "one: 1\n\
+            name: fixed\n\
+            three: 3\n\
+            count: 2\n",
+        );
+        let struct_with_extras = type_reg
+            .deserialize_struct_with_extras::<Fixed, _, _>(deserializer)
+            .unwrap();
+
+        assert_eq!(
+            Fixed {
+                name: String::from("fixed"),
+                count: 2,
+            },
+            struct_with_extras.fixed
+        );
+        assert_eq!(2, struct_with_extras.extras.len());
+        assert_eq!(
+            Some(1u32),
+            struct_with_extras.extras.get::<u32, _>("one").copied()
+        );
+        assert_eq!(
+            Some(A(3)),
+            struct_with_extras.extras.get::<A, _>("three").copied()
+        );
+    }
+
+    #[test]
+    fn deserialize_struct_with_extras_returns_error_for_unregistered_extra() {
+        let type_reg = TypeReg::<String>::new();
+
+        let deserializer = serde_yaml::Deserializer::from_str(
+            "name: fixed\n\
+            count: 2\n\
+            four: 4\n",
+        );
+        let error = type_reg
+            .deserialize_struct_with_extras::<Fixed, _, _>(deserializer)
+            .unwrap_err();
+
+        assert!(error.to_string().contains("four"), "{error}");
+    }
+
+    #[test]
+    fn deserialize_struct_with_extras_returns_error_for_missing_fixed_field() {
+        let type_reg = TypeReg::<String>::new();
+
+        let deserializer = serde_yaml::Deserializer::from_str("name: fixed\n");
+        let error = type_reg
+            .deserialize_struct_with_extras::<Fixed, _, _>(deserializer)
+            .unwrap_err();
+
+        assert!(
+            error.to_string().contains("missing field `count`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_struct_with_extras_returns_error_for_non_struct() {
+        let type_reg = TypeReg::<String>::new();
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\n");
+        let error = type_reg
+            .deserialize_struct_with_extras::<u32, _, _>(deserializer)
+            .unwrap_err();
+
+        assert!(error.to_string().contains("is not a struct"), "{error}");
+    }
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct Fixed {
+        name: String,
+        count: u32,
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+    struct A(u32);
+}
@@ -0,0 +1,334 @@
+use std::{
+    borrow::Borrow,
+    fmt::{self, Debug},
+    hash::Hash,
+    ops::Deref,
+};
+
+use crate::{
+    common::{UnknownEntries, UnknownEntriesNone},
+    untagged::{BoxDataTypeDowncast, BoxDt, DataTypeWrapper, FromDataType, TypeMap},
+};
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashSet as Set;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexSet as Set;
+
+/// [`TypeMap`] that records which entries were inserted or mutably accessed.
+///
+/// This is useful to only write back changed entries, such as after loading
+/// configuration.
+///
+/// Since a mutable reference does not indicate whether the caller mutated the
+/// value, an entry is marked dirty on any mutable access.
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::{TrackedTypeMap, TypeMap};
+///
+/// let mut type_map = TypeMap::<&'static str>::new();
+/// type_map.insert("one", 1u32);
+/// type_map.insert("two", 2u32);
+///
+/// let mut tracked_type_map = TrackedTypeMap::new(type_map);
+/// if let Some(two) = tracked_type_map.get_mut::<u32, _>("two") {
+///     *two += 1;
+/// }
+///
+/// assert_eq!(
+///     vec![&"two"],
+///     tracked_type_map.dirty_keys().collect::<Vec<_>>()
+/// );
+/// ```
+pub struct TrackedTypeMap<K, BoxDT = BoxDt, UnknownEntriesT = UnknownEntriesNone>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    /// Underlying map.
+    type_map: TypeMap<K, BoxDT, UnknownEntriesT>,
+    /// Keys of entries that were inserted or mutably accessed.
+    dirty: Set<K>,
+}
+
+impl<K, BoxDT, UnknownEntriesT> TrackedTypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Clone + Eq + Hash,
+    BoxDT: DataTypeWrapper,
+    UnknownEntriesT: UnknownEntries,
+{
+    /// Returns a new `TrackedTypeMap` with no dirty entries.
+    pub fn new(type_map: TypeMap<K, BoxDT, UnknownEntriesT>) -> Self {
+        Self {
+            type_map,
+            dirty: Set::new(),
+        }
+    }
+
+    /// Returns the underlying map.
+    pub fn into_inner(self) -> TypeMap<K, BoxDT, UnknownEntriesT> {
+        self.type_map
+    }
+
+    /// Returns the keys of entries that were inserted or mutably accessed
+    /// since this map was created, or [`clear_dirty`] was last called.
+    ///
+    /// [`clear_dirty`]: Self::clear_dirty
+    pub fn dirty_keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.dirty.iter()
+    }
+
+    /// Returns whether the entry for the key was inserted or mutably accessed.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    pub fn is_dirty<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.dirty.contains(q)
+    }
+
+    /// Marks all entries as clean.
+    pub fn clear_dirty(&mut self) {
+        self.dirty.clear();
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key, and
+    /// marks the entry dirty.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// If there is an entry, but the data type does not match, `None` is
+    /// returned, and the entry is not marked dirty.
+    pub fn get_mut<R, Q>(&mut self, q: &Q) -> Option<&mut R>
+    where
+        K: Borrow<Q>,
+        BoxDT: BoxDataTypeDowncast<R>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let is_type_match = self
+            .type_map
+            .get_raw(q)
+            .and_then(BoxDataTypeDowncast::<R>::downcast_ref)
+            .is_some();
+        if is_type_match {
+            self.get_raw_mut(q)
+                .and_then(BoxDataTypeDowncast::<R>::downcast_mut)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the boxed value corresponding to the
+    /// key, and marks the entry dirty.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    pub fn get_raw_mut<Q>(&mut self, q: &Q) -> Option<&mut BoxDT>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (key, _) = self.type_map.get_key_value(q)?;
+        if !self.dirty.contains(q) {
+            self.dirty.insert(key.clone());
+        }
+        self.type_map.get_raw_mut(q)
+    }
+
+    /// Inserts a key-value pair into the map, and marks the entry dirty.
+    ///
+    /// If the map did not have this key present, `None` is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the old
+    /// value is returned.
+    pub fn insert<R>(&mut self, k: K, r: R) -> Option<BoxDT>
+    where
+        BoxDT: FromDataType<R>,
+    {
+        self.insert_raw(k, <BoxDT as FromDataType<R>>::from(r))
+    }
+
+    /// Inserts a key-value pair into the map, and marks the entry dirty.
+    ///
+    /// If the map did not have this key present, `None` is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the old
+    /// value is returned.
+    pub fn insert_raw(&mut self, k: K, v: BoxDT) -> Option<BoxDT> {
+        self.dirty.insert(k.clone());
+        self.type_map.insert_raw(k, v)
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> From<TypeMap<K, BoxDT, UnknownEntriesT>>
+    for TrackedTypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Clone + Eq + Hash,
+    BoxDT: DataTypeWrapper,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn from(type_map: TypeMap<K, BoxDT, UnknownEntriesT>) -> Self {
+        Self::new(type_map)
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> Clone for TrackedTypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Clone + Eq + Hash,
+    BoxDT: DataTypeWrapper,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn clone(&self) -> Self {
+        Self {
+            type_map: self.type_map.clone(),
+            dirty: self.dirty.clone(),
+        }
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> Debug for TrackedTypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash + Debug,
+    UnknownEntriesT: UnknownEntries,
+    TypeMap<K, BoxDT, UnknownEntriesT>: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TrackedTypeMap")
+            .field("type_map", &self.type_map)
+            .field("dirty", &self.dirty)
+            .finish()
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> Deref for TrackedTypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    type Target = TypeMap<K, BoxDT, UnknownEntriesT>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.type_map
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> serde::Serialize for TrackedTypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+    TypeMap<K, BoxDT, UnknownEntriesT>: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.type_map.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::untagged::{BoxDataTypeDowncast, TrackedTypeMap, TypeMap};
+
+    fn tracked_type_map() -> TrackedTypeMap<&'static str> {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+        type_map.insert("two", A(2));
+        type_map.insert("three", 3u32);
+        TrackedTypeMap::new(type_map)
+    }
+
+    #[test]
+    fn new_has_no_dirty_keys() {
+        let tracked_type_map = tracked_type_map();
+
+        assert_eq!(0, tracked_type_map.dirty_keys().count());
+        assert_eq!(Some(A(1)), tracked_type_map.get::<A, _>("one").copied());
+    }
+
+    #[test]
+    fn insert_marks_dirty() {
+        let mut tracked_type_map = tracked_type_map();
+
+        tracked_type_map.insert("four", A(4));
+
+        assert_eq!(
+            vec![&"four"],
+            tracked_type_map.dirty_keys().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(A(4)), tracked_type_map.get::<A, _>("four").copied());
+    }
+
+    #[test]
+    fn get_mut_marks_dirty() {
+        let mut tracked_type_map = tracked_type_map();
+
+        let _one = tracked_type_map.get_mut::<A, _>("one");
+
+        assert!(tracked_type_map.is_dirty("one"));
+        assert!(!tracked_type_map.is_dirty("two"));
+    }
+
+    #[test]
+    fn get_mut_does_not_mark_dirty_on_type_mismatch() {
+        let mut tracked_type_map = tracked_type_map();
+
+        assert_eq!(None, tracked_type_map.get_mut::<u32, _>("one"));
+        assert_eq!(None, tracked_type_map.get_mut::<u32, _>("four"));
+
+        assert_eq!(0, tracked_type_map.dirty_keys().count());
+    }
+
+    #[test]
+    fn get_raw_mut_marks_dirty() {
+        let mut tracked_type_map = tracked_type_map();
+
+        if let Some(three) = tracked_type_map
+            .get_raw_mut("three")
+            .and_then(BoxDataTypeDowncast::<u32>::downcast_mut)
+        {
+            *three += 1;
+        }
+        let _four = tracked_type_map.get_raw_mut("four");
+
+        assert_eq!(
+            vec![&"three"],
+            tracked_type_map.dirty_keys().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(4u32), tracked_type_map.get::<u32, _>("three").copied());
+    }
+
+    #[test]
+    fn clear_dirty() {
+        let mut tracked_type_map = tracked_type_map();
+        tracked_type_map.insert("four", A(4));
+
+        tracked_type_map.clear_dirty();
+
+        assert_eq!(0, tracked_type_map.dirty_keys().count());
+        assert_eq!(4, tracked_type_map.into_inner().len());
+    }
+
+    #[test]
+    fn serialize() {
+        let mut tracked_type_map = TrackedTypeMap::from(TypeMap::<&'static str>::new());
+        tracked_type_map.insert("one", A(1));
+
+        assert_eq!(
+            "one: 1\n",
+            serde_yaml::to_string(&tracked_type_map).unwrap()
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+    struct A(u32);
+}
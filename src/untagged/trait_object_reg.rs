@@ -0,0 +1,233 @@
+use std::{borrow::Cow, fmt};
+
+use serde::de::DeserializeSeed;
+use serde_tagged::de::SeedFactory;
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashMap as Map;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexMap as Map;
+
+/// Registry of concrete types that are deserialized as a `Box<T>` trait
+/// object.
+///
+/// Each concrete type is selected by an externally serialized type tag, which
+/// is the qualified type name of the concrete type, the same as for the
+/// [`tagged`] registry:
+///
+/// ```yaml
+/// u32: 1
+/// ```
+///
+/// This is registered against a single key in a [`TypeReg`] using
+/// [`TypeReg::register_trait_object`].
+///
+/// [`tagged`]: crate::tagged
+/// [`TypeReg`]: crate::untagged::TypeReg
+/// [`TypeReg::register_trait_object`]: crate::untagged::TypeReg::register_trait_object
+pub struct TraitObjectReg<T>
+where
+    T: ?Sized,
+{
+    /// Functions to deserialize each concrete type, keyed by type name.
+    fn_seeds: Map<&'static str, TraitObjectFn<T>>,
+}
+
+/// Function to deserialize a concrete type and upcast it to a `Box<T>`.
+type TraitObjectFn<T> = Box<
+    dyn Fn(&mut dyn erased_serde::Deserializer<'_>) -> Result<Box<T>, erased_serde::Error>
+        + Send
+        + Sync,
+>;
+
+impl<T> TraitObjectReg<T>
+where
+    T: ?Sized + 'static,
+{
+    /// Returns an empty `TraitObjectReg`.
+    pub fn new() -> Self {
+        Self {
+            fn_seeds: Map::new(),
+        }
+    }
+
+    /// Returns the number of registered concrete types.
+    pub fn len(&self) -> usize {
+        self.fn_seeds.len()
+    }
+
+    /// Returns whether there are no registered concrete types.
+    pub fn is_empty(&self) -> bool {
+        self.fn_seeds.is_empty()
+    }
+
+    /// Registers a concrete type, and the function to upcast it to `Box<T>`.
+    ///
+    /// The upcast function is usually `|c| Box::new(c)`, as the `Box<C>` is
+    /// coerced to a `Box<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::fmt::Display;
+    ///
+    /// use type_reg::untagged::TraitObjectReg;
+    ///
+    /// let mut trait_object_reg = TraitObjectReg::<dyn Display + Send + Sync>::new();
+    /// trait_object_reg.register::<u32>(|n| Box::new(n));
+    /// trait_object_reg.register::<bool>(|b| Box::new(b));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("bool: true");
+    /// let value = trait_object_reg.deserialize_single(deserializer).unwrap();
+    ///
+    /// assert_eq!("true", value.to_string());
+    /// ```
+    pub fn register<C>(&mut self, upcast: fn(C) -> Box<T>)
+    where
+        C: serde::de::DeserializeOwned + 'static,
+    {
+        self.fn_seeds.insert(
+            std::any::type_name::<C>(),
+            Box::new(move |deserializer| C::deserialize(deserializer).map(upcast)),
+        );
+    }
+
+    /// Deserializes a type tagged value into a `Box<T>`.
+    ///
+    /// The concrete type must be registered in this registry before attempting
+    /// to deserialize it.
+    pub fn deserialize_single<'de, D, E>(&self, deserializer: D) -> Result<Box<T>, E>
+    where
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        serde_tagged::de::external::deserialize(deserializer, self)
+    }
+}
+
+impl<T> Default for TraitObjectReg<T>
+where
+    T: ?Sized + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for TraitObjectReg<T>
+where
+    T: ?Sized,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug_map = f.debug_map();
+
+        // `TraitObjectFn` is `!Debug`, so we just use "..".
+        self.fn_seeds.keys().for_each(|k| {
+            debug_map.key(&k);
+            debug_map.value(&"..");
+        });
+
+        debug_map.finish()
+    }
+}
+
+/// Seed to deserialize a concrete type registered in a [`TraitObjectReg`].
+pub struct TraitObjectSeed<'r, T>
+where
+    T: ?Sized,
+{
+    fn_seed: &'r TraitObjectFn<T>,
+}
+
+impl<'de, T> DeserializeSeed<'de> for TraitObjectSeed<'_, T>
+where
+    T: ?Sized,
+{
+    type Value = Box<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.fn_seed)(&mut deserializer).map_err(serde::de::Error::custom)
+    }
+}
+
+// Used by [`serde_tagged`] to select which [`DeserializeSeed`] function to use.
+impl<'de, 'r, T> SeedFactory<'de, Cow<'de, str>> for &'r TraitObjectReg<T>
+where
+    T: ?Sized,
+{
+    type Seed = TraitObjectSeed<'r, T>;
+    type Value = Box<T>;
+
+    fn seed<E>(self, type_tag: Cow<'de, str>) -> Result<Self::Seed, E>
+    where
+        E: serde::de::Error,
+    {
+        self.fn_seeds
+            .get(&*type_tag)
+            .map(|fn_seed| TraitObjectSeed { fn_seed })
+            .ok_or_else(|| {
+                use std::fmt::Write;
+                let mut message = String::with_capacity(256);
+                write!(
+                    message,
+                    "Type `{type_tag:?}` not registered in trait object registry."
+                )
+                .expect("Failed to write error message");
+
+                message.push_str("\nAvailable types are:\n\n");
+                let mut message = self
+                    .fn_seeds
+                    .keys()
+                    .try_fold(message, |mut message, key| {
+                        writeln!(message, "- {key:?}")?;
+                        Result::<_, fmt::Error>::Ok(message)
+                    })
+                    .expect("Failed to write error message");
+                message.push('\n');
+
+                serde::de::Error::custom(message)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Display;
+
+    use super::TraitObjectReg;
+
+    #[test]
+    fn deserialize_single_upcasts_registered_type() {
+        let mut trait_object_reg = TraitObjectReg::<dyn Display + Send + Sync>::new();
+        trait_object_reg.register::<u32>(|n| Box::new(n));
+        trait_object_reg.register::<bool>(|b| Box::new(b));
+
+        let deserializer = serde_yaml::Deserializer::from_str("u32: 1");
+        let value = trait_object_reg.deserialize_single(deserializer).unwrap();
+
+        assert_eq!(2, trait_object_reg.len());
+        assert_eq!("1", value.to_string());
+    }
+
+    #[test]
+    fn deserialize_single_error_lists_available_types() {
+        let mut trait_object_reg = TraitObjectReg::<dyn Display + Send + Sync>::new();
+        trait_object_reg.register::<u32>(|n| Box::new(n));
+
+        let deserializer = serde_yaml::Deserializer::from_str("u64: 1");
+        let error = trait_object_reg
+            .deserialize_single(deserializer)
+            .map(|_| ())
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Type `\"u64\"` not registered in trait object registry."));
+        assert!(error.to_string().contains("- \"u32\""));
+    }
+}
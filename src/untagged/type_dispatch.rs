@@ -0,0 +1,282 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::untagged::{BoxDt, DataType, TypeReg};
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashMap as Map;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexMap as Map;
+
+/// Handler invoked with each deserialized value.
+type Handler<'f> = Box<dyn FnMut(BoxDt) + 'f>;
+
+/// Handler invoked with the key of each unknown entry.
+type UnknownHandler<'f, K> = Box<dyn FnMut(K) + 'f>;
+
+/// Deserializes a map of untagged values, passing each value to the handler
+/// registered for its key.
+///
+/// This is an alternative to [`TypeReg::deserialize_map`] for processing
+/// values as they are deserialized, such as for commands or messages, instead
+/// of collecting them into a [`TypeMap`].
+///
+/// [`TypeMap`]: crate::untagged::TypeMap
+///
+/// # Examples
+///
+/// ```rust
+/// use std::cell::Cell;
+///
+/// use type_reg::untagged::TypeDispatch;
+///
+/// let total = Cell::new(0u64);
+/// let mut unknown_keys = Vec::new();
+///
+/// let mut type_dispatch = TypeDispatch::<String>::new();
+/// type_dispatch.on::<u32, _>(String::from("one"), |value| {
+///     total.set(total.get() + u64::from(value))
+/// });
+/// type_dispatch.on::<u64, _>(String::from("two"), |value| total.set(total.get() + value));
+/// type_dispatch.on_unknown(|key| unknown_keys.push(key));
+///
+/// // This may be any deserializer.
+/// let deserializer = serde_yaml::Deserializer::from_str(
+///     "---\n\
+///     one: 1\n\
+///     two: 2\n\
+///     three: 3\n\
+///     ",
+/// );
+///
+/// type_dispatch.dispatch(deserializer).unwrap();
+/// drop(type_dispatch);
+///
+/// assert_eq!(3, total.get());
+/// assert_eq!(vec![String::from("three")], unknown_keys);
+/// ```
+pub struct TypeDispatch<'f, K>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+{
+    /// Registry of the types to deserialize for each key.
+    type_reg: TypeReg<K, BoxDt>,
+    /// Handler for each key.
+    handlers: Map<K, Handler<'f>>,
+    /// Handler for keys without a registered handler.
+    unknown_handler: Option<UnknownHandler<'f, K>>,
+}
+
+impl<'f, K> TypeDispatch<'f, K>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+{
+    /// Returns a new `TypeDispatch` with no handlers.
+    pub fn new() -> Self {
+        Self {
+            type_reg: TypeReg::new(),
+            handlers: Map::new(),
+            unknown_handler: None,
+        }
+    }
+
+    /// Registers a handler for values of type `R` under the given key.
+    ///
+    /// This replaces any existing handler for the key.
+    pub fn on<R, F>(&mut self, key: K, mut f: F)
+    where
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        F: FnMut(R) + 'f,
+    {
+        self.type_reg.register::<R>(key.clone());
+        self.handlers.insert(
+            key,
+            Box::new(move |box_dt: BoxDt| {
+                // The value is always an `R`, as it was deserialized using the
+                // seed registered for `R`.
+                let r = box_dt
+                    .into_inner()
+                    .into_any()
+                    .downcast::<R>()
+                    .expect("Expected value to be `R`, as it was deserialized as `R`.");
+                f(*r);
+            }),
+        );
+    }
+
+    /// Registers a handler for keys that do not have a handler.
+    ///
+    /// The handler is called with the key, and the value is skipped. Without
+    /// this handler, [`dispatch`] returns an error on unknown keys.
+    ///
+    /// [`dispatch`]: Self::dispatch
+    pub fn on_unknown<F>(&mut self, f: F)
+    where
+        F: FnMut(K) + 'f,
+    {
+        self.unknown_handler = Some(Box::new(f));
+    }
+
+    /// Deserializes a map, calling the handler for each entry in order.
+    ///
+    /// If an entry fails to deserialize, handlers for earlier entries will
+    /// already have been called.
+    pub fn dispatch<'de, D, E>(&mut self, deserializer: D) -> Result<(), E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        deserializer.deserialize_map(TypeDispatchVisitor {
+            type_dispatch: self,
+        })
+    }
+}
+
+impl<K> Default for TypeDispatch<'_, K>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> Debug for TypeDispatch<'_, K>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Handlers are `!Debug`, so we just use "..".
+        f.debug_struct("TypeDispatch")
+            .field("type_reg", &self.type_reg)
+            .field(
+                "unknown_handler",
+                &self.unknown_handler.as_ref().map(|_| ".."),
+            )
+            .finish()
+    }
+}
+
+/// Visitor that passes each deserialized value to its handler.
+struct TypeDispatchVisitor<'d, 'f, K>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+{
+    type_dispatch: &'d mut TypeDispatch<'f, K>,
+}
+
+impl<'de, K> serde::de::Visitor<'de> for TypeDispatchVisitor<'_, '_, K>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let TypeDispatch {
+            type_reg,
+            handlers,
+            unknown_handler,
+        } = self.type_dispatch;
+
+        while let Some(key) = type_reg.next_key(&mut map_access)? {
+            match (handlers.get_mut(&key), unknown_handler.as_mut()) {
+                (Some(handler), _) => {
+                    let value = map_access
+                        .next_value_seed(type_reg.deserialize_seed(&key)?)
+                        .map_err(|error| type_reg.value_error(&key, error))?;
+                    handler(value);
+                }
+                (None, Some(unknown_handler)) => {
+                    map_access.next_value::<serde::de::IgnoredAny>()?;
+                    unknown_handler(key);
+                }
+                (None, None) => {
+                    // Returns the unknown type error.
+                    type_reg.deserialize_seed::<A::Error>(&key)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::TypeDispatch;
+
+    #[test]
+    fn dispatch() {
+        let values = RefCell::new(Vec::new());
+
+        let mut type_dispatch = TypeDispatch::<String>::new();
+        type_dispatch.on::<A, _>(String::from("one"), |a| values.borrow_mut().push(a));
+        type_dispatch.on::<A, _>(String::from("two"), |a| {
+            values.borrow_mut().push(A(a.0 * 10))
+        });
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2, three: 3 }");
+        type_dispatch.on::<A, _>(String::from("three"), |a| values.borrow_mut().push(a));
+        type_dispatch.dispatch(deserializer).unwrap();
+        drop(type_dispatch);
+
+        assert_eq!(vec![A(1), A(20), A(3)], values.into_inner());
+    }
+
+    #[test]
+    fn dispatch_calls_unknown_handler() {
+        let mut unknown_keys = Vec::new();
+
+        let mut type_dispatch = TypeDispatch::<String>::new();
+        type_dispatch.on::<A, _>(String::from("one"), |_| {});
+        type_dispatch.on_unknown(|key| unknown_keys.push(key));
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: { a: [1, 2] } }");
+        type_dispatch.dispatch(deserializer).unwrap();
+        drop(type_dispatch);
+
+        assert_eq!(vec![String::from("two")], unknown_keys);
+    }
+
+    #[test]
+    fn dispatch_returns_error_for_unknown_key_without_unknown_handler() {
+        let mut type_dispatch = TypeDispatch::<String>::new();
+        type_dispatch.on::<A, _>(String::from("one"), |_| {});
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ two: 2 }");
+        let error = type_dispatch.dispatch(deserializer).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .starts_with("Type key `\"two\"` not registered in type registry."));
+    }
+
+    #[test]
+    fn debug() {
+        let mut type_dispatch = TypeDispatch::<&'static str>::new();
+        type_dispatch.on::<A, _>("one", |_| {});
+
+        assert_eq!(
+            r#"TypeDispatch { type_reg: {"one": ".."}, unknown_handler: None }"#,
+            format!("{type_dispatch:?}")
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+    struct A(u32);
+}
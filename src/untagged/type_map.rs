@@ -1,24 +1,33 @@
 use std::{
+    any::Any,
     borrow::Borrow,
-    fmt::{self, Debug},
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug, Display},
     hash::Hash,
     ops::{Deref, DerefMut},
 };
 
+use serde::ser::SerializeMap;
+
 use crate::{
-    common::{UnknownEntries, UnknownEntriesNone, UnknownEntriesSome},
-    untagged::{BoxDataTypeDowncast, BoxDt, DataTypeWrapper, FromDataType},
+    common::{
+        DebugMapSorted, DebugTypeMapSorted, EntryKind, GetResult, UnknownEntries,
+        UnknownEntriesNone, UnknownEntriesSome,
+    },
+    untagged::{
+        BoxDataTypeDowncast, BoxDt, BoxDtDisplay, DataRef, DataType, DataTypeWrapper, FromDataType,
+        MissingTypeError, Numeric, RequiredType, TypeMapEntry, TypeReg,
+    },
+    TypeNameLit,
 };
 
 #[cfg(not(feature = "ordered"))]
-use std::collections::HashMap as Map;
+use std::collections::{hash_map::Entry, HashMap as Map};
 
 #[cfg(feature = "ordered")]
-use indexmap::IndexMap as Map;
+use indexmap::{map::Entry, IndexMap as Map};
 
 /// Map of types that can be serialized / deserialized.
-#[derive(serde::Serialize)]
-#[serde(transparent)]
 pub struct TypeMap<K, BoxDT = BoxDt, UnknownEntriesT = UnknownEntriesNone>
 where
     K: Eq + Hash,
@@ -27,10 +36,20 @@ where
     /// Underlying map.
     inner: Map<K, BoxDT>,
     /// Unknown entries encountered during deserialization.
-    #[serde(skip_serializing)]
     unknown_entries: Map<K, <UnknownEntriesT as UnknownEntries>::ValueT>,
+    /// Keys of entries that are skipped when serializing.
+    ephemeral: HashSet<K>,
+    /// Functions to register the type of entries inserted with
+    /// [`TypeMap::insert_registerable`].
+    registrations: HashMap<K, RegisterFn<K>>,
 }
 
+/// Function to register a type in a [`TypeReg`] against the given key.
+///
+/// The `TypeReg` is passed as `&mut dyn Any`, as `TypeReg` requires bounds on
+/// `K` that `TypeMap` does not.
+pub(crate) type RegisterFn<K> = fn(&mut dyn Any, K);
+
 impl<K> TypeMap<K, BoxDt>
 where
     K: Eq + Hash,
@@ -50,6 +69,8 @@ where
         Self {
             inner: Map::new(),
             unknown_entries: Map::new(),
+            ephemeral: HashSet::new(),
+            registrations: HashMap::new(),
         }
     }
 
@@ -68,8 +89,38 @@ where
         Self {
             inner: Map::with_capacity(capacity),
             unknown_entries: Map::new(),
+            ephemeral: HashSet::new(),
+            registrations: HashMap::new(),
         }
     }
+
+    /// Creates a `TypeMap` from entries whose values are all of the same type.
+    ///
+    /// This is useful to lift a `HashMap<K, R>` into a `TypeMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert("one", 1u32);
+    /// map.insert("two", 2u32);
+    ///
+    /// let type_map = TypeMap::from_homogeneous(map);
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// assert_eq!(Some(2), type_map.get::<u32, _>("two").copied());
+    /// ```
+    pub fn from_homogeneous<R, I>(entries: I) -> Self
+    where
+        BoxDt: FromDataType<R>,
+        I: IntoIterator<Item = (K, R)>,
+    {
+        Self::from_homogeneous_typed(entries)
+    }
 }
 
 impl<K, BoxDT> TypeMap<K, BoxDT, UnknownEntriesNone>
@@ -81,19 +132,58 @@ where
     pub fn into_inner(self) -> Map<K, BoxDT> {
         self.inner
     }
+
+    /// Returns a `Debug` view of this map with entries sorted by key.
+    ///
+    /// Without the `"ordered"` feature, the `Debug` output of a `TypeMap` is
+    /// in an unspecified order, which makes it unsuitable for snapshot tests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("b", 2u32);
+    /// type_map.insert("a", 1u32);
+    ///
+    /// let debug_str = format!("{:?}", type_map.debug_sorted());
+    /// assert!(debug_str.find("\"a\"") < debug_str.find("\"b\""));
+    /// ```
+    pub fn debug_sorted(&self) -> impl Debug + '_
+    where
+        K: Ord + Debug,
+    {
+        DebugMapSorted::new(self.inner.iter().map(|(k, v)| (k, crate::typed_value(v))))
+    }
 }
 
 impl<K, BoxDT, ValueT> TypeMap<K, BoxDT, UnknownEntriesSome<ValueT>>
 where
     K: Eq + Hash,
     BoxDT: DataTypeWrapper,
-    ValueT: Clone + Debug + PartialEq + Eq,
+    ValueT: Clone + Debug,
 {
     /// Returns the underlying map and unknown entries.
     pub fn into_inner(self) -> (Map<K, BoxDT>, Map<K, ValueT>) {
         (self.inner, self.unknown_entries)
     }
 
+    /// Returns a `Debug` view of this map with entries sorted by key.
+    ///
+    /// Without the `"ordered"` feature, the `Debug` output of a `TypeMap` is
+    /// in an unspecified order, which makes it unsuitable for snapshot tests.
+    pub fn debug_sorted(&self) -> impl Debug + '_
+    where
+        K: Ord + Debug,
+    {
+        DebugTypeMapSorted {
+            name: "TypeMap",
+            inner: DebugMapSorted::new(self.inner.iter().map(|(k, v)| (k, crate::typed_value(v)))),
+            unknown_entries: DebugMapSorted::new(self.unknown_entries.iter()),
+        }
+    }
+
     /// Returns the entries that were unable to be deserialized.
     ///
     /// These are the entries from the source data for which no type was
@@ -165,18 +255,264 @@ where
         self.unknown_entries().get(q)
     }
 
+    /// Returns whether the map contains a known entry for the key.
+    ///
+    /// This is the same as `contains_key`, but does not rely on `Deref`.
+    pub fn contains_known<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.contains_key(q)
+    }
+
+    /// Returns whether the map contains an unknown entry for the key.
+    pub fn contains_unknown<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.unknown_entries.contains_key(q)
+    }
+
+    /// Returns whether the entry for the key is known or unknown, or `None` if
+    /// there is no entry for the key.
+    ///
+    /// If the key is present as both a known and unknown entry, which may
+    /// happen when entries are inserted manually, [`EntryKind::Known`] is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{
+    ///     common::EntryKind,
+    ///     untagged::{TypeMap, TypeReg},
+    /// };
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let type_map = type_reg
+    ///     .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(
+    ///         serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }"),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Some(EntryKind::Known), type_map.location_of("one"));
+    /// assert_eq!(Some(EntryKind::Unknown), type_map.location_of("two"));
+    /// assert_eq!(None, type_map.location_of("three"));
+    /// ```
+    pub fn location_of<Q>(&self, q: &Q) -> Option<EntryKind>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.inner.contains_key(q) {
+            Some(EntryKind::Known)
+        } else if self.unknown_entries.contains_key(q) {
+            Some(EntryKind::Unknown)
+        } else {
+            None
+        }
+    }
+
     /// Inserts an unknown entry into the map.
     ///
-    /// This is only used during deserialization.
+    /// Unknown entries are usually only populated during deserialization.
+    /// This is primarily useful to construct expected values in tests.
     ///
     /// If the map did not have this key present, `None` is returned.
     ///
     /// If the map did have this key present, the value is updated, and the old
     /// value is returned. The key is not updated, though; this matters for
     /// types that can be `==` without being identical.
-    pub(crate) fn insert_unknown_entry(&mut self, k: K, v: ValueT) -> Option<ValueT> {
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str, BoxDt, _>::new_typed();
+    /// type_map.insert_unknown("one", serde_yaml::Value::Bool(true));
+    ///
+    /// assert_eq!(
+    ///     Some(&serde_yaml::Value::Bool(true)),
+    ///     type_map.get_unknown_entry("one")
+    /// );
+    /// ```
+    pub fn insert_unknown(&mut self, k: K, v: ValueT) -> Option<ValueT> {
         self.unknown_entries.insert(k, v)
     }
+
+    /// Retains only the unknown entries for which `f` returns `true`.
+    ///
+    /// Known entries are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str, BoxDt, _>::new_typed();
+    /// type_map.insert_unknown("one", serde_yaml::Value::Bool(true));
+    /// type_map.insert_unknown("two", serde_yaml::Value::Null);
+    ///
+    /// type_map.retain_unknown(|_, v| !v.is_null());
+    ///
+    /// assert!(type_map.contains_unknown("one"));
+    /// assert!(!type_map.contains_unknown("two"));
+    /// ```
+    pub fn retain_unknown<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut ValueT) -> bool,
+    {
+        self.unknown_entries.retain(f);
+    }
+
+    /// Moves all unknown entries into `target`, leaving none in this map.
+    ///
+    /// Entries in `target` with the same key are replaced. Known entries are
+    /// not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use type_reg::untagged::{BoxDt, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str, BoxDt, _>::new_typed();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert_unknown("two", serde_yaml::Value::Bool(true));
+    ///
+    /// let mut unknowns = HashMap::new();
+    /// type_map.drain_unknown_into(&mut unknowns);
+    ///
+    /// assert!(type_map.unknown_entries().is_empty());
+    /// assert_eq!(Some(&serde_yaml::Value::Bool(true)), unknowns.get("two"));
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// ```
+    pub fn drain_unknown_into<T>(&mut self, target: &mut T)
+    where
+        T: Extend<(K, ValueT)>,
+    {
+        target.extend(std::mem::take(&mut self.unknown_entries));
+    }
+
+    /// Returns a serializable view of this map that includes unknown entries.
+    ///
+    /// Known entries are serialized first, followed by unknown entries. An
+    /// unknown entry whose key is also a known entry is skipped.
+    ///
+    /// When unknown entries are deserialized as a value type that retains the
+    /// source representation, such as `serde_json::value::RawValue`, that
+    /// representation is serialized unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let serialized = r#"{"one":1,"two":{ "a": 1.50 }}"#;
+    /// let mut deserializer = serde_json::Deserializer::from_str(serialized);
+    /// let type_map = type_reg
+    ///     .deserialize_map_with_unknowns::<'_, Box<serde_json::value::RawValue>, _, _>(
+    ///         &mut deserializer,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// let reserialized = serde_json::to_string(&type_map.serialize_with_unknowns()).unwrap();
+    /// assert_eq!(serialized, reserialized);
+    /// ```
+    pub fn serialize_with_unknowns(&self) -> impl serde::Serialize + '_
+    where
+        K: serde::Serialize,
+        BoxDT: serde::Serialize,
+        ValueT: serde::Serialize,
+    {
+        TypeMapWithUnknowns {
+            inner: &self.inner,
+            ephemeral: &self.ephemeral,
+            unknown_entries: &self.unknown_entries,
+        }
+    }
+
+    /// Returns a serializable view of this map that includes unknown entries,
+    /// with known and unknown entries sorted together by key.
+    ///
+    /// This is the same as [`serialize_with_unknowns`], except entries are
+    /// serialized in key order, which is useful for canonical output that is
+    /// easy to diff.
+    ///
+    /// [`serialize_with_unknowns`]: Self::serialize_with_unknowns
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("b"));
+    ///
+    /// let serialized = r#"{"c":3,"b":2,"a":1}"#;
+    /// let mut deserializer = serde_json::Deserializer::from_str(serialized);
+    /// let type_map = type_reg
+    ///     .deserialize_map_with_unknowns::<'_, Box<serde_json::value::RawValue>, _, _>(
+    ///         &mut deserializer,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// let reserialized = serde_json::to_string(&type_map.serialize_with_unknowns_sorted()).unwrap();
+    /// assert_eq!(r#"{"a":1,"b":2,"c":3}"#, reserialized);
+    /// ```
+    pub fn serialize_with_unknowns_sorted(&self) -> impl serde::Serialize + '_
+    where
+        K: Ord + serde::Serialize,
+        BoxDT: serde::Serialize,
+        ValueT: serde::Serialize,
+    {
+        TypeMapWithUnknownsSorted {
+            inner: &self.inner,
+            ephemeral: &self.ephemeral,
+            unknown_entries: &self.unknown_entries,
+        }
+    }
+
+    /// Returns a serializable view of only the unknown entries in this map.
+    ///
+    /// This is useful to inspect which entries in the source data were not
+    /// recognized, e.g. when a configuration key does not take effect.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let serialized = r#"{"one":1,"two":{ "a": 1.50 }}"#;
+    /// let mut deserializer = serde_json::Deserializer::from_str(serialized);
+    /// let type_map = type_reg
+    ///     .deserialize_map_with_unknowns::<'_, Box<serde_json::value::RawValue>, _, _>(
+    ///         &mut deserializer,
+    ///     )
+    ///     .unwrap();
+    ///
+    /// let unknowns = serde_json::to_string(&type_map.serialize_unknowns_only()).unwrap();
+    /// assert_eq!(r#"{"two":{ "a": 1.50 }}"#, unknowns);
+    /// ```
+    pub fn serialize_unknowns_only(&self) -> impl serde::Serialize + '_
+    where
+        K: serde::Serialize,
+        ValueT: serde::Serialize,
+    {
+        &self.unknown_entries
+    }
 }
 
 impl<K, BoxDT, UnknownEntriesT> TypeMap<K, BoxDT, UnknownEntriesT>
@@ -200,6 +536,8 @@ where
         Self {
             inner: Map::new(),
             unknown_entries: Map::new(),
+            ephemeral: HashSet::new(),
+            registrations: HashMap::new(),
         }
     }
 
@@ -218,9 +556,99 @@ where
         Self {
             inner: Map::with_capacity(capacity),
             unknown_entries: Map::new(),
+            ephemeral: HashSet::new(),
+            registrations: HashMap::new(),
+        }
+    }
+
+    /// Creates a `TypeMap` from entries whose values are all of the same type.
+    ///
+    /// This is useful to lift a `HashMap<K, R>` into a `TypeMap`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use type_reg::untagged::{BoxDtDisplay, TypeMap};
+    ///
+    /// let mut map = HashMap::new();
+    /// map.insert("one", 1u32);
+    /// map.insert("two", 2u32);
+    ///
+    /// let type_map = TypeMap::<_, BoxDtDisplay>::from_homogeneous_typed(map);
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// assert_eq!(Some(2), type_map.get::<u32, _>("two").copied());
+    /// ```
+    pub fn from_homogeneous_typed<R, I>(entries: I) -> Self
+    where
+        BoxDT: FromDataType<R>,
+        I: IntoIterator<Item = (K, R)>,
+    {
+        let inner = entries
+            .into_iter()
+            .map(|(k, r)| (k, <BoxDT as FromDataType<R>>::from(r)))
+            .collect::<Map<K, BoxDT>>();
+
+        Self {
+            inner,
+            unknown_entries: Map::new(),
+            ephemeral: HashSet::new(),
+            registrations: HashMap::new(),
+        }
+    }
+
+    /// Creates a `TypeMap` from boxed entries.
+    ///
+    /// This is the inverse of [`into_entries`], and allows maps to be passed
+    /// between components without serialization. Later entries replace
+    /// earlier entries with the same key.
+    ///
+    /// [`into_entries`]: Self::into_entries
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMap};
+    ///
+    /// let entries = vec![("one", BoxDt::new(1u32)), ("two", BoxDt::new(2u64))];
+    /// let type_map = TypeMap::<_, BoxDt>::from_entries(entries);
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// assert_eq!(Some(2), type_map.get::<u64, _>("two").copied());
+    /// ```
+    pub fn from_entries(entries: Vec<(K, BoxDT)>) -> Self {
+        Self {
+            inner: entries.into_iter().collect::<Map<K, BoxDT>>(),
+            unknown_entries: Map::new(),
+            ephemeral: HashSet::new(),
+            registrations: HashMap::new(),
         }
     }
 
+    /// Returns the entries of this map as boxed values.
+    ///
+    /// With the `"ordered"` feature, entries are returned in insertion order.
+    /// Unknown entries are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let entries = type_map.into_entries();
+    /// let type_map = TypeMap::<_, BoxDt>::from_entries(entries);
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// ```
+    pub fn into_entries(self) -> Vec<(K, BoxDT)> {
+        self.inner.into_iter().collect()
+    }
+
     /// Returns a reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map’s key type, but `Hash` and
@@ -285,6 +713,79 @@ where
             .and_then(BoxDataTypeDowncast::<R>::downcast_ref)
     }
 
+    /// Returns the value corresponding to the key, or why it is not returned.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// Unlike [`get`], if there is an entry, but the data type does not match,
+    /// [`GetResult::WrongType`] is returned with the stored value's type name.
+    ///
+    /// [`get`]: Self::get
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{common::GetResult, untagged::TypeMap, TypeNameLit};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// assert_eq!(GetResult::Present(&1), type_map.get_typed::<u32, _>("one"));
+    /// assert_eq!(
+    ///     GetResult::WrongType(TypeNameLit("u32")),
+    ///     type_map.get_typed::<u64, _>("one")
+    /// );
+    /// assert_eq!(GetResult::Absent, type_map.get_typed::<u32, _>("two"));
+    /// ```
+    pub fn get_typed<R, Q>(&self, q: &Q) -> GetResult<&R>
+    where
+        K: Borrow<Q>,
+        BoxDT: BoxDataTypeDowncast<R>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.inner.get(q) {
+            Some(box_dt) => match BoxDataTypeDowncast::<R>::downcast_ref(box_dt) {
+                Some(r) => GetResult::Present(r),
+                None => GetResult::WrongType(DataTypeWrapper::type_name(box_dt)),
+            },
+            None => GetResult::Absent,
+        }
+    }
+
+    /// Returns the value corresponding to the key, converted to the integer
+    /// type `R`.
+    ///
+    /// Unlike [`get`], the stored value may be any integer type, and is
+    /// converted to `R` if it fits within `R`'s bounds. `None` is returned if
+    /// there is no entry, the value is not an integer, or it does not fit.
+    ///
+    /// [`get`]: Self::get
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert("big", u64::MAX);
+    ///
+    /// assert_eq!(None, type_map.get::<u64, _>("one"));
+    /// assert_eq!(Some(1u64), type_map.get_as::<u64, _>("one"));
+    /// assert_eq!(None, type_map.get_as::<u32, _>("big"));
+    /// ```
+    pub fn get_as<R, Q>(&self, q: &Q) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        R: Numeric,
+    {
+        self.inner
+            .get(q)
+            .and_then(|v| R::try_from_data(DataRef::new(v.inner())))
+    }
+
     /// Returns a mutable reference to the value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map’s key type, but `Hash` and
@@ -380,7 +881,64 @@ where
         self.inner.get(q)
     }
 
-    /// Returns a mutable reference to the boxed value corresponding to the key.
+    /// Returns a clone of the boxed value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDataTypeDowncast, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let boxed_one = type_map.get_raw_cloned("one").unwrap();
+    /// type_map.clear();
+    ///
+    /// let one = BoxDataTypeDowncast::<u32>::downcast_ref(&boxed_one).copied();
+    /// assert_eq!(Some(1), one);
+    /// ```
+    pub fn get_raw_cloned<Q>(&self, q: &Q) -> Option<BoxDT>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.get(q).map(DataTypeWrapper::clone)
+    }
+
+    /// Returns the value corresponding to the key as an
+    /// [`erased_serde::Serialize`] trait object.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// This allows individual values to be passed to `erased_serde` based code
+    /// without downcasting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let one = type_map.erased_value("one").unwrap();
+    /// assert_eq!("1\n", serde_yaml::to_string(one).unwrap());
+    /// ```
+    pub fn erased_value<Q>(&self, q: &Q) -> Option<&dyn erased_serde::Serialize>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner
+            .get(q)
+            .map(|box_dt| box_dt as &dyn erased_serde::Serialize)
+    }
+
+    /// Returns a mutable reference to the boxed value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map’s key type, but `Hash` and
     /// `Eq` on the borrowed form must match those for the key type.
@@ -415,6 +973,94 @@ where
         self.inner.get_mut(q)
     }
 
+    /// Returns a reference to the value corresponding to the key, panicking if
+    /// it is not present or is not an `R`.
+    ///
+    /// This is intended for tests and prototyping, where the entry is assumed
+    /// to exist. Use [`get`] to handle missing or mismatched entries.
+    ///
+    /// [`get`]: Self::get
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no entry for the key, or if the entry's data type is
+    /// not `R`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// assert_eq!(1, *type_map.at::<u32, _>("one"));
+    /// ```
+    #[track_caller]
+    pub fn at<R, Q>(&self, q: &Q) -> &R
+    where
+        K: Borrow<Q>,
+        BoxDT: BoxDataTypeDowncast<R>,
+        Q: Hash + Eq + Debug + ?Sized,
+    {
+        let value = self
+            .inner
+            .get(q)
+            .unwrap_or_else(|| panic!("no entry for key `{q:?}`"));
+        BoxDataTypeDowncast::<R>::downcast_ref(value).unwrap_or_else(|| {
+            panic!(
+                "entry `{q:?}` is a `{}`, not a `{}`",
+                DataTypeWrapper::type_name(value).0,
+                std::any::type_name::<R>()
+            )
+        })
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key,
+    /// panicking if it is not present or is not an `R`.
+    ///
+    /// This is intended for tests and prototyping, where the entry is assumed
+    /// to exist. Use [`get_mut`] to handle missing or mismatched entries.
+    ///
+    /// [`get_mut`]: Self::get_mut
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no entry for the key, or if the entry's data type is
+    /// not `R`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// *type_map.at_mut::<u32, _>("one") += 1;
+    ///
+    /// assert_eq!(2, *type_map.at::<u32, _>("one"));
+    /// ```
+    #[track_caller]
+    pub fn at_mut<R, Q>(&mut self, q: &Q) -> &mut R
+    where
+        K: Borrow<Q>,
+        BoxDT: BoxDataTypeDowncast<R>,
+        Q: Hash + Eq + Debug + ?Sized,
+    {
+        let value = self
+            .inner
+            .get_mut(q)
+            .unwrap_or_else(|| panic!("no entry for key `{q:?}`"));
+        let type_name = DataTypeWrapper::type_name(&*value).0;
+        BoxDataTypeDowncast::<R>::downcast_mut(value).unwrap_or_else(|| {
+            panic!(
+                "entry `{q:?}` is a `{type_name}`, not a `{}`",
+                std::any::type_name::<R>()
+            )
+        })
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// If the map did not have this key present, `None` is returned.
@@ -427,7 +1073,7 @@ where
     where
         BoxDT: FromDataType<R>,
     {
-        self.inner.insert(k, <BoxDT as FromDataType<R>>::from(r))
+        self.insert_raw(k, <BoxDT as FromDataType<R>>::from(r))
     }
 
     /// Inserts a key-value pair into the map.
@@ -442,7 +1088,234 @@ where
     where
         BoxDT: FromDataType<R>,
     {
-        self.inner.insert(k, <BoxDT as FromDataType<R>>::from(r))
+        self.insert_raw(k, <BoxDT as FromDataType<R>>::from(r))
+    }
+
+    /// Inserts a value under the next unused integer key, and returns the key.
+    ///
+    /// The key is the map's length, or the next larger integer if that key is
+    /// already present. This allows a map with integer keys to be used as a
+    /// list of values of different types.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<usize>::new();
+    /// let first = type_map.push_typed(1u32);
+    /// let second = type_map.push_typed(String::from("two"));
+    ///
+    /// assert_eq!((0, 1), (first, second));
+    /// assert_eq!(Some(1), type_map.get::<u32, _>(&0).copied());
+    /// ```
+    pub fn push_typed<R>(&mut self, r: R) -> K
+    where
+        K: Clone + From<usize>,
+        BoxDT: FromDataType<R>,
+    {
+        let k = (self.inner.len()..)
+            .map(K::from)
+            .find(|k| !self.inner.contains_key(k))
+            .expect("Expected an unused integer key.");
+        self.insert(k.clone(), r);
+        k
+    }
+
+    /// Inserts a key-value pair into the map, returning a mutable reference to
+    /// the inserted value, and the old value if the key was present.
+    ///
+    /// This is the same as [`insert`] followed by [`get_mut`], without looking
+    /// up the key twice.
+    ///
+    /// [`insert`]: Self::insert
+    /// [`get_mut`]: Self::get_mut
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("count", 1u64);
+    ///
+    /// let (count, old) = type_map.insert_and_get("count", 2u32);
+    /// *count += 1;
+    ///
+    /// assert!(old.is_some());
+    /// assert_eq!(Some(3), type_map.get::<u32, _>("count").copied());
+    /// ```
+    pub fn insert_and_get<R>(&mut self, k: K, r: R) -> (&mut R, Option<BoxDT>)
+    where
+        BoxDT: BoxDataTypeDowncast<R> + FromDataType<R>,
+    {
+        if !self.ephemeral.is_empty() {
+            self.ephemeral.remove(&k);
+        }
+        if !self.registrations.is_empty() {
+            self.registrations.remove(&k);
+        }
+
+        let box_dt = <BoxDT as FromDataType<R>>::from(r);
+        let (value, old) = match self.inner.entry(k) {
+            Entry::Occupied(mut entry) => {
+                let old = entry.insert(box_dt);
+                (entry.into_mut(), Some(old))
+            }
+            Entry::Vacant(entry) => (entry.insert(box_dt), None),
+        };
+
+        let r = BoxDataTypeDowncast::<R>::downcast_mut(value)
+            .expect("Expected value to be `R` after insertion.");
+        (r, old)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key,
+    /// inserting the value returned by `f` if it is absent.
+    ///
+    /// `f` is only called if there is no entry for the key, or if the entry's
+    /// data type is not `R`. If `f` returns an error, the map is unchanged and
+    /// the error is returned.
+    ///
+    /// If there is an entry, but the data type does not match, it is replaced
+    /// with the value returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let one = type_map.get_or_try_insert_with::<u32, _, ()>("one", || Err(()));
+    /// assert_eq!(Ok(&mut 1), one);
+    ///
+    /// let two = type_map.get_or_try_insert_with::<u32, _, ()>("two", || Ok(2));
+    /// assert_eq!(Ok(&mut 2), two);
+    ///
+    /// let three = type_map.get_or_try_insert_with::<u32, _, &str>("three", || Err("failed"));
+    /// assert_eq!(Err("failed"), three);
+    /// assert!(!type_map.contains_key("three"));
+    /// ```
+    pub fn get_or_try_insert_with<R, F, E>(&mut self, k: K, f: F) -> Result<&mut R, E>
+    where
+        BoxDT: BoxDataTypeDowncast<R> + FromDataType<R>,
+        F: FnOnce() -> Result<R, E>,
+    {
+        let value = match self.inner.entry(k) {
+            Entry::Occupied(mut entry) => {
+                if BoxDataTypeDowncast::<R>::downcast_ref(entry.get()).is_none() {
+                    let box_dt = <BoxDT as FromDataType<R>>::from(f()?);
+                    self.ephemeral.remove(entry.key());
+                    self.registrations.remove(entry.key());
+                    entry.insert(box_dt);
+                }
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                let box_dt = <BoxDT as FromDataType<R>>::from(f()?);
+                self.ephemeral.remove(entry.key());
+                self.registrations.remove(entry.key());
+                entry.insert(box_dt)
+            }
+        };
+
+        Ok(BoxDataTypeDowncast::<R>::downcast_mut(value)
+            .expect("Expected value to be `R` after insertion."))
+    }
+
+    /// Returns the entry for the key, for in-place manipulation of a value of
+    /// a given type.
+    ///
+    /// Unlike `entry` on the underlying map, which is available through
+    /// `DerefMut`, the returned entry's methods downcast the value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// ["a", "b", "a"].into_iter().for_each(|key| {
+    ///     type_map
+    ///         .entry_typed(key)
+    ///         .and_modify::<u32, _>(|count| *count += 1)
+    ///         .or_insert(1u32);
+    /// });
+    ///
+    /// assert_eq!(Some(2), type_map.get::<u32, _>("a").copied());
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("b").copied());
+    /// ```
+    pub fn entry_typed(&mut self, k: K) -> TypeMapEntry<'_, K, BoxDT> {
+        TypeMapEntry::new(
+            self.inner.entry(k),
+            &mut self.ephemeral,
+            &mut self.registrations,
+        )
+    }
+
+    /// Returns the entry for the key in the underlying map.
+    ///
+    /// If the entry is vacant, the key is no longer ephemeral or registered,
+    /// so a value inserted through the entry is serialized, and its type is
+    /// not registered by [`build_matching_type_reg`].
+    ///
+    /// [`build_matching_type_reg`]: Self::build_matching_type_reg
+    pub fn entry(&mut self, k: K) -> Entry<'_, K, BoxDT> {
+        let entry = self.inner.entry(k);
+        if let Entry::Vacant(entry) = &entry {
+            self.ephemeral.remove(entry.key());
+            self.registrations.remove(entry.key());
+        }
+        entry
+    }
+
+    /// Moves the entry for `from` to the key `to`, returning whether `from`
+    /// was present.
+    ///
+    /// Both the known and unknown entries for `from` are moved. If `from` is
+    /// present, any entries for `to` are overwritten, even if `from` only has
+    /// a known or an unknown entry. With the `"ordered"` feature, the entry
+    /// keeps its position.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("db", 1u32);
+    ///
+    /// assert!(type_map.rename_key("db", "database"));
+    /// assert!(!type_map.rename_key("db", "database"));
+    ///
+    /// assert_eq!(None, type_map.get::<u32, _>("db"));
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("database").copied());
+    /// ```
+    pub fn rename_key<Q>(&mut self, from: &Q, to: K) -> bool
+    where
+        K: Borrow<Q> + Clone,
+        Q: Hash + Eq + ?Sized,
+    {
+        let present = self.inner.contains_key(from) || self.unknown_entries.contains_key(from);
+        if !present || from == to.borrow() {
+            return present;
+        }
+
+        map_rename(&mut self.inner, from, to.clone());
+        map_rename(&mut self.unknown_entries, from, to.clone());
+
+        self.ephemeral.remove::<K>(&to);
+        if self.ephemeral.remove(from) {
+            self.ephemeral.insert(to.clone());
+        }
+        self.registrations.remove::<K>(&to);
+        if let Some(register_fn) = self.registrations.remove(from) {
+            self.registrations.insert(to, register_fn);
+        }
+
+        true
     }
 
     /// Inserts a key-value pair into the map.
@@ -452,178 +1325,2524 @@ where
     /// If the map did have this key present, the value is updated, and the old
     /// value is returned. The key is not updated, though; this matters for
     /// types that can be `==` without being identical.
+    ///
+    /// If the entry was inserted with [`insert_ephemeral`], it is no longer
+    /// ephemeral. If it was inserted with [`insert_registerable`], its type is
+    /// no longer registered by [`build_matching_type_reg`].
+    ///
+    /// [`insert_ephemeral`]: Self::insert_ephemeral
+    /// [`insert_registerable`]: Self::insert_registerable
+    /// [`build_matching_type_reg`]: Self::build_matching_type_reg
     pub fn insert_raw(&mut self, k: K, v: BoxDT) -> Option<BoxDT> {
+        if !self.ephemeral.is_empty() {
+            self.ephemeral.remove(&k);
+        }
+        if !self.registrations.is_empty() {
+            self.registrations.remove(&k);
+        }
         self.inner.insert(k, v)
     }
-}
 
-impl<K, BoxDT, UnknownEntriesT> Clone for TypeMap<K, BoxDT, UnknownEntriesT>
-where
-    K: Clone + Eq + Hash,
-    BoxDT: DataTypeWrapper,
-    UnknownEntriesT: UnknownEntries,
-{
-    fn clone(&self) -> Self {
-        let mut type_map = TypeMap::<K, BoxDT, UnknownEntriesT> {
-            inner: Map::with_capacity(self.inner.len()),
-            unknown_entries: Map::with_capacity(self.unknown_entries.len()),
-        };
-        self.inner.iter().for_each(|(k, v)| {
-            let value = v.clone();
-            type_map.insert_raw(k.clone(), value);
-        });
-        self.unknown_entries.iter().for_each(|(k, v)| {
-            let k = k.clone();
-            let v = v.clone();
-            type_map.unknown_entries.insert(k, v);
-        });
-        type_map
+    /// Removes the entry for the key, returning its value if it was present.
+    ///
+    /// The entry is also no longer ephemeral or registered, so a value
+    /// inserted later for the key is serialized. With the `"ordered"` feature,
+    /// the order of the remaining entries is preserved.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert_ephemeral("one", 1u32);
+    ///
+    /// assert!(type_map.remove("one").is_some());
+    /// type_map.entry("one").or_insert_with(|| BoxDt::new(2u32));
+    ///
+    /// assert!(!type_map.is_ephemeral("one"));
+    /// ```
+    pub fn remove<Q>(&mut self, q: &Q) -> Option<BoxDT>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.ephemeral.is_empty() {
+            self.ephemeral.remove(q);
+        }
+        if !self.registrations.is_empty() {
+            self.registrations.remove(q);
+        }
+
+        #[cfg(not(feature = "ordered"))]
+        {
+            self.inner.remove(q)
+        }
+
+        #[cfg(feature = "ordered")]
+        {
+            self.inner.shift_remove(q)
+        }
+    }
+
+    /// Inserts a key-value pair into the map, which is skipped when the map is
+    /// serialized.
+    ///
+    /// This is useful to store runtime-only state, such as cached computed
+    /// values, alongside values that are persisted. The entry is ephemeral
+    /// until it is removed, or replaced through [`insert`], [`insert_raw`],
+    /// [`get_or_try_insert_with`], or [`entry_typed`].
+    ///
+    /// If the map did not have this key present, `None` is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the old
+    /// value is returned.
+    ///
+    /// [`insert`]: Self::insert
+    /// [`insert_raw`]: Self::insert_raw
+    /// [`get_or_try_insert_with`]: Self::get_or_try_insert_with
+    /// [`entry_typed`]: Self::entry_typed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert_ephemeral("cache", 2u32);
+    ///
+    /// assert_eq!(Some(2), type_map.get::<u32, _>("cache").copied());
+    /// assert_eq!("one: 1\n", serde_yaml::to_string(&type_map).unwrap());
+    /// ```
+    pub fn insert_ephemeral<R>(&mut self, k: K, r: R) -> Option<BoxDT>
+    where
+        K: Clone,
+        BoxDT: FromDataType<R>,
+    {
+        let previous = self.insert_raw(k.clone(), <BoxDT as FromDataType<R>>::from(r));
+        self.ephemeral.insert(k);
+        previous
+    }
+
+    /// Inserts a key-value pair into the map, and records how to deserialize
+    /// the value's type.
+    ///
+    /// Entries inserted this way have their types registered in the
+    /// [`TypeReg`] returned by [`build_matching_type_reg`], so the serialized
+    /// map can be deserialized again. The type stays recorded until the entry
+    /// is replaced through [`insert`], [`insert_raw`],
+    /// [`get_or_try_insert_with`], or [`entry_typed`].
+    ///
+    /// If the map did not have this key present, `None` is returned.
+    ///
+    /// If the map did have this key present, the value is updated, and the old
+    /// value is returned.
+    ///
+    /// [`build_matching_type_reg`]: Self::build_matching_type_reg
+    /// [`insert`]: Self::insert
+    /// [`insert_raw`]: Self::insert_raw
+    /// [`get_or_try_insert_with`]: Self::get_or_try_insert_with
+    /// [`entry_typed`]: Self::entry_typed
+    pub fn insert_registerable<R>(&mut self, k: K, r: R) -> Option<BoxDT>
+    where
+        K: Clone + Debug + 'static,
+        BoxDT: FromDataType<R> + 'static,
+        R: serde::de::DeserializeOwned + DataType + 'static,
+    {
+        let previous = self.insert_raw(k.clone(), <BoxDT as FromDataType<R>>::from(r));
+        self.registrations
+            .insert(k, register_type::<K, BoxDT, R> as RegisterFn<K>);
+        previous
+    }
+
+    /// Returns a [`TypeReg`] with the types of entries inserted with
+    /// [`insert_registerable`] registered against their keys.
+    ///
+    /// This is best-effort: entries inserted any other way are not registered,
+    /// as their `Deserialize` implementations are not known.
+    ///
+    /// [`insert_registerable`]: Self::insert_registerable
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<String>::new();
+    /// type_map.insert_registerable(String::from("one"), 1u32);
+    /// type_map.insert_registerable(String::from("two"), String::from("two"));
+    ///
+    /// let serialized = serde_yaml::to_string(&type_map).unwrap();
+    ///
+    /// let type_reg = type_map.build_matching_type_reg();
+    /// let deserializer = serde_yaml::Deserializer::from_str(&serialized);
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// ```
+    pub fn build_matching_type_reg(&self) -> TypeReg<K, BoxDT>
+    where
+        K: Clone + Debug + 'static,
+        BoxDT: 'static,
+    {
+        let mut type_reg = TypeReg::<K, BoxDT>::with_capacity_typed(self.registrations.len());
+        self.registrations
+            .iter()
+            .filter(|(k, _)| self.inner.contains_key(*k))
+            .for_each(|(k, register_fn)| register_fn(&mut type_reg, k.clone()));
+        type_reg
+    }
+
+    /// Returns whether the entry for the key is skipped when serializing.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    pub fn is_ephemeral<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.contains_key(q) && self.ephemeral.contains(q)
+    }
+
+    /// Sorts the entries by key.
+    ///
+    /// Unknown entries are sorted as well. This is useful to canonicalize the
+    /// order of entries before serialization.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("b", 2u32);
+    /// type_map.insert("a", 1u32);
+    ///
+    /// type_map.sort_keys();
+    ///
+    /// assert_eq!(vec![&"a", &"b"], type_map.keys().collect::<Vec<_>>());
+    /// ```
+    #[cfg(feature = "ordered")]
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        self.inner.sort_keys();
+        self.unknown_entries.sort_keys();
+    }
+
+    /// Sorts the entries using the given comparison function.
+    ///
+    /// Unknown entries are not sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("a", 1u32);
+    /// type_map.insert("bb", 2u32);
+    ///
+    /// type_map.sort_by(|k1, _v1, k2, _v2| k2.len().cmp(&k1.len()));
+    ///
+    /// assert_eq!(vec![&"bb", &"a"], type_map.keys().collect::<Vec<_>>());
+    /// ```
+    #[cfg(feature = "ordered")]
+    pub fn sort_by<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &BoxDT, &K, &BoxDT) -> std::cmp::Ordering,
+    {
+        self.inner.sort_by(f);
+    }
+
+    /// Serializes the value corresponding to the key into bytes.
+    ///
+    /// `to_vec` is the format-specific serialization function, such as
+    /// [`serde_json::to_vec`]. This allows a single entry to be serialized
+    /// without knowing its concrete type, such as when caching serialized
+    /// values.
+    ///
+    /// Returns `None` if there is no entry for the key.
+    ///
+    /// [`serde_json::to_vec`]: https://docs.rs/serde_json/latest/serde_json/fn.to_vec.html
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let bytes = type_map
+    ///     .serialize_entry_to_vec("one", serde_json::to_vec)
+    ///     .transpose()
+    ///     .unwrap();
+    /// assert_eq!(Some(b"1".to_vec()), bytes);
+    ///
+    /// let bytes = type_map.serialize_entry_to_vec("two", serde_json::to_vec);
+    /// assert!(bytes.is_none());
+    /// ```
+    pub fn serialize_entry_to_vec<Q, F, E>(&self, q: &Q, to_vec: F) -> Option<Result<Vec<u8>, E>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        F: FnOnce(&dyn DataType) -> Result<Vec<u8>, E>,
+    {
+        self.inner.get(q).map(|v| to_vec(v.inner()))
+    }
+
+    /// Returns an approximate size in bytes of the map's contents.
+    ///
+    /// This is the sum of `size_of::<K>()` for each key, and the length of
+    /// each value serialized as JSON. It is an estimate of the serialized
+    /// size, not the heap footprint, and is intended for heuristics such as
+    /// cache eviction.
+    ///
+    /// Values that fail to serialize contribute the bytes written before the
+    /// failure.
+    ///
+    /// Ephemeral entries are not counted, as they are not serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<u8>::new();
+    /// type_map.insert(1, 123u32);
+    /// type_map.insert(2, String::from("abc"));
+    ///
+    /// // 1 byte per key, plus `123` and `"abc"`.
+    /// assert_eq!(10, type_map.estimated_size());
+    /// ```
+    #[cfg(feature = "estimated_size")]
+    pub fn estimated_size(&self) -> usize {
+        let mut byte_counter = ByteCounter(0);
+        let mut key_count = 0;
+        self.inner
+            .iter()
+            .filter(|(k, _)| !self.ephemeral.contains(*k))
+            .for_each(|(_, v)| {
+                key_count += 1;
+                // The error is ignored, as the estimate only needs the byte count.
+                let _ = serde_json::to_writer(&mut byte_counter, v.inner());
+            });
+
+        key_count * std::mem::size_of::<K>() + byte_counter.0
+    }
+
+    /// Returns the entries of this map as a JSON object.
+    ///
+    /// Each value is serialized to a [`serde_json::Value`]. Ephemeral entries
+    /// are skipped, as they are not serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let json_object = type_map.to_json_object().unwrap();
+    /// assert_eq!(Some(&serde_json::json!(1)), json_object.get("one"));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json_object(
+        &self,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, serde_json::Error>
+    where
+        K: Clone + Into<String>,
+    {
+        self.inner
+            .iter()
+            .filter(|(k, _)| !self.ephemeral.contains(*k))
+            .map(|(k, v)| serde_json::to_value(v.inner()).map(|value| (k.clone().into(), value)))
+            .collect()
+    }
+
+    /// Splits this map into entries for which `f` returns `true`, and entries
+    /// for which it returns `false`.
+    ///
+    /// Unknown entries are moved into the second map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert("two", 2u64);
+    ///
+    /// let (type_map_one, type_map_rest) = type_map.partition(|k, _| *k == "one");
+    ///
+    /// assert_eq!(Some(1), type_map_one.get::<u32, _>("one").copied());
+    /// assert_eq!(Some(2), type_map_rest.get::<u64, _>("two").copied());
+    /// assert_eq!(1, type_map_one.len());
+    /// assert_eq!(1, type_map_rest.len());
+    /// ```
+    pub fn partition<F>(self, mut f: F) -> (Self, Self)
+    where
+        F: FnMut(&K, &dyn DataType) -> bool,
+    {
+        let TypeMap {
+            inner,
+            unknown_entries,
+            ephemeral,
+            mut registrations,
+        } = self;
+
+        let (inner_matching, inner_rest) = inner
+            .into_iter()
+            .partition::<Map<K, BoxDT>, _>(|(k, v)| f(k, v.inner()));
+        let (ephemeral_matching, ephemeral_rest) = ephemeral
+            .into_iter()
+            .partition::<HashSet<K>, _>(|k| inner_matching.contains_key(k));
+
+        let registrations_matching = inner_matching
+            .keys()
+            .filter_map(|k| registrations.remove_entry(k))
+            .collect::<HashMap<K, RegisterFn<K>>>();
+
+        let type_map_matching = Self {
+            inner: inner_matching,
+            unknown_entries: Map::new(),
+            ephemeral: ephemeral_matching,
+            registrations: registrations_matching,
+        };
+        let type_map_rest = Self {
+            inner: inner_rest,
+            unknown_entries,
+            ephemeral: ephemeral_rest,
+            registrations,
+        };
+
+        (type_map_matching, type_map_rest)
+    }
+
+    /// Splits this map into entries whose value is an `R`, and all other
+    /// entries.
+    ///
+    /// Unknown entries are moved into the second map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert("two", 2u64);
+    /// type_map.insert("three", 3u32);
+    ///
+    /// let (type_map_u32, type_map_rest) = type_map.partition_type::<u32>();
+    ///
+    /// assert_eq!(2, type_map_u32.len());
+    /// assert_eq!(Some(2), type_map_rest.get::<u64, _>("two").copied());
+    /// ```
+    pub fn partition_type<R>(self) -> (Self, Self)
+    where
+        R: 'static,
+    {
+        self.partition(|_k, v| v.type_id_inner() == std::any::TypeId::of::<R>())
+    }
+
+    /// Returns a serializable view of this map as a sequence of `[key, value]`
+    /// pairs.
+    ///
+    /// This is useful for formats such as JSON, which only support string map
+    /// keys, when `K` is not a string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<u32>::new();
+    /// type_map.insert(1, String::from("one"));
+    ///
+    /// let serialized = serde_json::to_string(&type_map.serialize_as_seq()).unwrap();
+    /// assert_eq!(r#"[[1,"one"]]"#, serialized);
+    /// ```
+    pub fn serialize_as_seq(&self) -> impl serde::Serialize + '_
+    where
+        K: serde::Serialize,
+    {
+        TypeMapSeq {
+            inner: &self.inner,
+            ephemeral: &self.ephemeral,
+        }
+    }
+
+    /// Serializes this map to `writer`, using the given format's `to_writer`
+    /// function, such as `serde_yaml::to_writer` or `serde_json::to_writer`.
+    ///
+    /// Entries are serialized one at a time as the map is iterated, so the
+    /// serialized map is not buffered in memory before being written. Pass a
+    /// [`BufWriter`] as the writer to reduce the number of writes.
+    ///
+    /// [`BufWriter`]: std::io::BufWriter
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let mut buffer = Vec::new();
+    /// type_map
+    ///     .serialize_to_writer(&mut buffer, serde_yaml::to_writer)
+    ///     .unwrap();
+    ///
+    /// assert_eq!("one: 1\n", String::from_utf8(buffer).unwrap());
+    /// ```
+    pub fn serialize_to_writer<W, F, E>(&self, writer: W, format: F) -> Result<(), E>
+    where
+        K: serde::Serialize,
+        BoxDT: serde::Serialize,
+        W: std::io::Write,
+        F: FnOnce(W, &Self) -> Result<(), E>,
+    {
+        format(writer, self)
+    }
+
+    /// Clears `target`, and clones this map's entries into it.
+    ///
+    /// This reuses `target`'s allocation, which avoids allocating when a map
+    /// is repeatedly snapshotted. Both known and unknown entries are cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let mut snapshot = TypeMap::<&'static str>::with_capacity(10);
+    /// snapshot.insert("two", 2u64);
+    /// type_map.clone_into(&mut snapshot);
+    ///
+    /// assert_eq!(Some(1), snapshot.get::<u32, _>("one").copied());
+    /// assert!(snapshot.get::<u64, _>("two").is_none());
+    /// ```
+    pub fn clone_into(&self, target: &mut Self)
+    where
+        K: Clone,
+    {
+        target.inner.clear();
+        target.inner.extend(
+            self.inner
+                .iter()
+                .map(|(k, v)| (k.clone(), DataTypeWrapper::clone(v))),
+        );
+        target.unknown_entries.clear();
+        target.unknown_entries.extend(
+            self.unknown_entries
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+        target.ephemeral.clone_from(&self.ephemeral);
+        target.registrations.clone_from(&self.registrations);
+    }
+
+    /// Removes all known entries, keeping the allocated memory for reuse.
+    ///
+    /// Like clearing the underlying map through `DerefMut`, unknown entries
+    /// are kept. This also clears which entries are ephemeral or registered,
+    /// so that values inserted later for the same keys are serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert_ephemeral("one", 1u32);
+    ///
+    /// type_map.clear();
+    ///
+    /// assert!(type_map.is_empty());
+    /// assert!(!type_map.is_ephemeral("one"));
+    /// ```
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.ephemeral.clear();
+        self.registrations.clear();
+    }
+
+    /// Removes all known and unknown entries, keeping the allocated memory
+    /// for reuse.
+    pub(crate) fn reset(&mut self) {
+        self.clear();
+        self.unknown_entries.clear();
+    }
+
+    /// Returns an iterator over each key and a [`DataRef`] to its value.
+    ///
+    /// This allows values to be processed the same way regardless of this
+    /// map's box type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDtDisplay, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str, BoxDtDisplay>::new_typed();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let one = type_map
+    ///     .iter_data()
+    ///     .find_map(|(_k, data_ref)| data_ref.downcast_ref::<u32>());
+    /// assert_eq!(Some(&1), one);
+    /// ```
+    pub fn iter_data(&self) -> impl Iterator<Item = (&K, DataRef<'_>)> + '_ {
+        self.inner.iter().map(|(k, v)| (k, DataRef::new(v.inner())))
+    }
+
+    /// Returns an iterator over each key and the type name of its value.
+    ///
+    /// This is useful to inspect which types are stored in the map, without
+    /// serializing the values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{untagged::TypeMap, TypeNameLit};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let type_names = type_map.type_names().collect::<Vec<_>>();
+    /// assert_eq!(vec![(&"one", TypeNameLit("u32"))], type_names);
+    /// ```
+    pub fn type_names(&self) -> impl Iterator<Item = (&K, TypeNameLit)> + '_ {
+        self.inner
+            .iter()
+            .map(|(k, v)| (k, DataTypeWrapper::type_name(v)))
+    }
+
+    /// Folds over every value of type `R`, skipping values of other types.
+    ///
+    /// Values are visited in the map's iteration order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u64);
+    /// type_map.insert("two", 2u64);
+    /// type_map.insert("name", String::from("metrics"));
+    ///
+    /// let max = type_map.fold_typed::<u64, _, _>(0, |max, n| max.max(*n));
+    /// assert_eq!(2, max);
+    /// ```
+    pub fn fold_typed<R, B, F>(&self, init: B, mut f: F) -> B
+    where
+        BoxDT: BoxDataTypeDowncast<R>,
+        F: FnMut(B, &R) -> B,
+    {
+        self.inner.values().fold(
+            init,
+            |acc, v| match BoxDataTypeDowncast::<R>::downcast_ref(v) {
+                Some(r) => f(acc, r),
+                None => acc,
+            },
+        )
+    }
+
+    /// Returns whether this map has the same keys and values as `other`,
+    /// treating numeric values within `epsilon` of each other as equal.
+    ///
+    /// Values are compared by their serialized form, so values of different
+    /// types that serialize to the same form are equal. Structural
+    /// differences, such as a missing field or a sequence of a different
+    /// length, are never equal. Unknown entries are not compared.
+    ///
+    /// Values that fail to serialize are not equal to anything.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map_a = TypeMap::<&'static str>::new();
+    /// type_map_a.insert("ratio", 0.1f64 + 0.2f64);
+    ///
+    /// let mut type_map_b = TypeMap::<&'static str>::new();
+    /// type_map_b.insert("ratio", 0.3f64);
+    ///
+    /// assert!(type_map_a.approx_eq(&type_map_b, 1e-9));
+    /// assert!(!type_map_a.approx_eq(&type_map_b, 0.0));
+    /// ```
+    #[cfg(feature = "approx_eq")]
+    pub fn approx_eq(&self, other: &Self, epsilon: f64) -> bool {
+        self.inner.len() == other.inner.len()
+            && self.inner.iter().all(|(k, v)| {
+                other.inner.get(k).is_some_and(|v_other| {
+                    match (
+                        serde_value::to_value(v.inner()),
+                        serde_value::to_value(v_other.inner()),
+                    ) {
+                        (Ok(value), Ok(value_other)) => {
+                            value_approx_eq(&value, &value_other, epsilon)
+                        }
+                        _ => false,
+                    }
+                })
+            })
+    }
+
+    /// Returns the sum of every value of type `R`, skipping values of other
+    /// types.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u64);
+    /// type_map.insert("two", 2u64);
+    /// type_map.insert("three", 3u32);
+    ///
+    /// assert_eq!(3, type_map.sum_typed::<u64>());
+    /// ```
+    pub fn sum_typed<R>(&self) -> R
+    where
+        BoxDT: BoxDataTypeDowncast<R>,
+        R: for<'r> std::iter::Sum<&'r R>,
+    {
+        self.inner
+            .values()
+            .filter_map(BoxDataTypeDowncast::<R>::downcast_ref)
+            .sum()
+    }
+
+    /// Returns the first entry whose value has the data type `R`.
+    ///
+    /// This is useful when a map holds at most one value of each type, such as
+    /// a service locator.
+    ///
+    /// Without the `"ordered"` feature, iteration order is unspecified, so if
+    /// multiple values have the data type `R`, which one is returned is not
+    /// deterministic.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("port", 8080u16);
+    /// type_map.insert("name", String::from("server"));
+    ///
+    /// assert_eq!(Some((&"port", &8080u16)), type_map.get_by_type::<u16>());
+    /// assert_eq!(None, type_map.get_by_type::<u32>());
+    /// ```
+    pub fn get_by_type<R>(&self) -> Option<(&K, &R)>
+    where
+        BoxDT: BoxDataTypeDowncast<R>,
+    {
+        self.get_all_by_type::<R>().next()
+    }
+
+    /// Returns an iterator over the entries whose values have the data type
+    /// `R`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u64);
+    /// type_map.insert("two", 2u32);
+    /// type_map.insert("three", 3u64);
+    ///
+    /// let mut entries = type_map.get_all_by_type::<u64>().collect::<Vec<_>>();
+    /// entries.sort();
+    ///
+    /// assert_eq!(vec![(&"one", &1u64), (&"three", &3u64)], entries);
+    /// ```
+    pub fn get_all_by_type<'f, R>(&'f self) -> impl Iterator<Item = (&'f K, &'f R)> + 'f
+    where
+        BoxDT: BoxDataTypeDowncast<R>,
+        R: 'f,
+    {
+        self.inner
+            .iter()
+            .filter_map(|(k, v)| BoxDataTypeDowncast::<R>::downcast_ref(v).map(|r| (k, r)))
+    }
+
+    /// Returns an error if no value has the data type `R`.
+    ///
+    /// This is useful to validate a deserialized map, such as when at least
+    /// one entry of a type must be configured.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{untagged::TypeMap, TypeNameLit};
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("port", 8080u16);
+    ///
+    /// assert!(type_map.require_type::<u16>().is_ok());
+    ///
+    /// let error = type_map.require_type::<String>().unwrap_err();
+    /// assert_eq!(
+    ///     &[TypeNameLit(std::any::type_name::<String>())],
+    ///     error.type_names()
+    /// );
+    /// ```
+    pub fn require_type<R>(&self) -> Result<(), MissingTypeError>
+    where
+        BoxDT: BoxDataTypeDowncast<R>,
+    {
+        let present = self
+            .inner
+            .values()
+            .any(|v| BoxDataTypeDowncast::<R>::downcast_ref(v).is_some());
+
+        if present {
+            Ok(())
+        } else {
+            Err(MissingTypeError {
+                type_names: vec![TypeNameLit::of::<R>()],
+            })
+        }
+    }
+
+    /// Returns an error listing each required type that no value has.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{
+    ///     untagged::{RequiredType, TypeMap},
+    ///     TypeNameLit,
+    /// };
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("port", 8080u16);
+    ///
+    /// let error = type_map
+    ///     .require_types(&[
+    ///         RequiredType::of::<u16>(),
+    ///         RequiredType::of::<u32>(),
+    ///         RequiredType::of::<bool>(),
+    ///     ])
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     &[TypeNameLit("u32"), TypeNameLit("bool")],
+    ///     error.type_names()
+    /// );
+    /// ```
+    pub fn require_types(&self, required: &[RequiredType]) -> Result<(), MissingTypeError> {
+        let type_names = required
+            .iter()
+            .filter(|required_type| {
+                !self
+                    .inner
+                    .values()
+                    .any(|v| DataTypeWrapper::type_id_inner(v) == required_type.type_id)
+            })
+            .map(RequiredType::type_name)
+            .collect::<Vec<TypeNameLit>>();
+
+        if type_names.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingTypeError { type_names })
+        }
+    }
+
+    /// Returns a table of each key and the type name of its value, sorted by
+    /// key.
+    ///
+    /// Columns are aligned to the widest entry in each column. When the
+    /// `"debug"` feature is enabled, a third column contains each value's
+    /// `Debug` string, truncated to 40 characters.
+    ///
+    /// This is intended for human readable output, such as a CLI command that
+    /// inspects the map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("two", 2u64);
+    /// type_map.insert("one", 1u32);
+    ///
+    /// let table = type_map.to_table_string();
+    /// # #[cfg(not(feature = "debug"))]
+    /// assert_eq!(
+    ///     "key  type\n\
+    ///     one  u32\n\
+    ///     two  u64\n",
+    ///     table
+    /// );
+    /// ```
+    pub fn to_table_string(&self) -> String
+    where
+        K: Ord + Display,
+    {
+        let mut entries = self.inner.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(k, _)| *k);
+
+        let header = vec![
+            String::from("key"),
+            String::from("type"),
+            #[cfg(feature = "debug")]
+            String::from("value"),
+        ];
+        let rows = std::iter::once(header)
+            .chain(entries.into_iter().map(|(k, v)| {
+                vec![
+                    k.to_string(),
+                    v.type_name().to_string(),
+                    #[cfg(feature = "debug")]
+                    table_value_truncate(format!("{:?}", v.debug())),
+                ]
+            }))
+            .collect::<Vec<_>>();
+
+        let column_widths = rows.iter().fold(Vec::<usize>::new(), |mut widths, row| {
+            widths.resize(row.len(), 0);
+            widths
+                .iter_mut()
+                .zip(row.iter())
+                .for_each(|(width, cell)| *width = (*width).max(cell.chars().count()));
+            widths
+        });
+
+        rows.iter().fold(String::new(), |mut table, row| {
+            let column_count = row.len();
+            row.iter()
+                .zip(column_widths.iter())
+                .enumerate()
+                .for_each(|(index, (cell, width))| {
+                    if index + 1 < column_count {
+                        table.push_str(&format!("{cell:<width$}  "));
+                    } else {
+                        table.push_str(cell);
+                    }
+                });
+            table.push('\n');
+            table
+        })
+    }
+
+    /// Returns a serializable view of this map that only includes entries for
+    /// which `f` returns `true`.
+    ///
+    /// The map is not modified. This is useful to omit entries, such as
+    /// secrets, when exporting the map.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert("password", String::from("hunter2"));
+    ///
+    /// let serialized =
+    ///     serde_yaml::to_string(&type_map.serialize_filtered(|k, _| *k != "password")).unwrap();
+    /// assert_eq!("one: 1\n", serialized);
+    /// ```
+    pub fn serialize_filtered<'f, F>(&'f self, f: F) -> impl serde::Serialize + 'f
+    where
+        K: serde::Serialize,
+        F: Fn(&K, &dyn DataType) -> bool + 'f,
+    {
+        TypeMapFiltered {
+            inner: &self.inner,
+            ephemeral: &self.ephemeral,
+            f,
+        }
+    }
+}
+
+/// Registers `R` in the given `TypeReg<K, BoxDT>`.
+fn register_type<K, BoxDT, R>(type_reg: &mut dyn Any, key: K)
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+    BoxDT: DataTypeWrapper + FromDataType<R> + 'static,
+    R: serde::de::DeserializeOwned + DataType + 'static,
+{
+    type_reg
+        .downcast_mut::<TypeReg<K, BoxDT>>()
+        .expect("Expected `TypeReg` to match the `TypeMap`'s key and value types.")
+        .register::<R>(key);
+}
+
+/// Writer that counts the number of bytes written to it.
+#[cfg(feature = "estimated_size")]
+struct ByteCounter(usize);
+
+#[cfg(feature = "estimated_size")]
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "anymap2")]
+impl<K, UnknownEntriesT> TypeMap<K, BoxDt, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    /// Returns an [`anymap2::AnyMap`] with each value keyed by its concrete
+    /// type, discarding the keys.
+    ///
+    /// If multiple values have the same type, only one is kept. With the
+    /// `"ordered"` feature, this is the last value of that type in this map;
+    /// otherwise, which value is kept is unspecified. Unknown entries are
+    /// discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("port", 8080u16);
+    /// type_map.insert("name", String::from("server"));
+    ///
+    /// let any_map = type_map.into_any_map();
+    ///
+    /// assert_eq!(Some(&8080u16), any_map.get::<u16>());
+    /// assert_eq!(Some("server"), any_map.get::<String>().map(String::as_str));
+    /// ```
+    pub fn into_any_map(self) -> anymap2::AnyMap {
+        let mut any_map = anymap2::AnyMap::new();
+        self.inner.into_iter().for_each(|(_k, box_dt)| {
+            box_dt.into_inner().insert_into_any_map(&mut any_map);
+        });
+        any_map
+    }
+}
+
+impl<K, UnknownEntriesT> TypeMap<K, BoxDtDisplay, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    /// Returns a map of each key to the `Display` string of its value.
+    ///
+    /// This is useful to produce human readable output, such as for a status
+    /// command.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDtDisplay, TypeMap};
+    ///
+    /// let mut type_map = TypeMap::<&'static str, BoxDtDisplay>::new_typed();
+    /// type_map.insert("one", 1u32);
+    /// type_map.insert("two", String::from("two"));
+    ///
+    /// let string_map = type_map.to_string_map();
+    ///
+    /// assert_eq!(Some("1"), string_map.get("one").map(String::as_str));
+    /// assert_eq!(Some("two"), string_map.get("two").map(String::as_str));
+    /// ```
+    pub fn to_string_map(&self) -> Map<K, String>
+    where
+        K: Clone,
+    {
+        self.inner
+            .iter()
+            .map(|(k, v)| (k.clone(), v.to_string()))
+            .collect::<Map<K, String>>()
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> serde::Serialize for TypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash + serde::Serialize,
+    BoxDT: serde::Serialize,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if self.ephemeral.is_empty() {
+            return serde::Serialize::serialize(&self.inner, serializer);
+        }
+
+        // Keys may remain in `ephemeral` after their entries are removed
+        // through `DerefMut`, so only present keys are excluded from the length.
+        let ephemeral_count = self
+            .ephemeral
+            .iter()
+            .filter(|k| self.inner.contains_key(*k))
+            .count();
+        let mut serialize_map =
+            serializer.serialize_map(Some(self.inner.len() - ephemeral_count))?;
+        self.inner
+            .iter()
+            .filter(|(k, _)| !self.ephemeral.contains(*k))
+            .try_for_each(|(k, v)| serialize_map.serialize_entry(k, v))?;
+        serialize_map.end()
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> Clone for TypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Clone + Eq + Hash,
+    BoxDT: DataTypeWrapper,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn clone(&self) -> Self {
+        let mut type_map = TypeMap::<K, BoxDT, UnknownEntriesT> {
+            inner: Map::with_capacity(self.inner.len()),
+            unknown_entries: Map::with_capacity(self.unknown_entries.len()),
+            ephemeral: self.ephemeral.clone(),
+            registrations: self.registrations.clone(),
+        };
+        self.inner.iter().for_each(|(k, v)| {
+            let value = v.clone();
+            type_map.inner.insert(k.clone(), value);
+        });
+        self.unknown_entries.iter().for_each(|(k, v)| {
+            let k = k.clone();
+            let v = v.clone();
+            type_map.unknown_entries.insert(k, v);
+        });
+        type_map
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        source.clone_into(self);
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> Default for TypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn default() -> Self {
+        Self {
+            inner: Map::default(),
+            unknown_entries: Map::default(),
+            ephemeral: HashSet::default(),
+            registrations: HashMap::default(),
+        }
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> Deref for TypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    type Target = Map<K, BoxDT>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesT> DerefMut for TypeMap<K, BoxDT, UnknownEntriesT>
+where
+    K: Eq + Hash,
+    UnknownEntriesT: UnknownEntries,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<K, BoxDT> Debug for TypeMap<K, BoxDT, UnknownEntriesNone>
+where
+    K: Eq + Hash + Debug,
+    BoxDT: DataTypeWrapper,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.inner
+                    .iter()
+                    .map(|(k, resource)| (k, crate::typed_value(resource))),
+            )
+            .finish()
+    }
+}
+
+/// Maximum number of characters of a value's `Debug` string in
+/// [`TypeMap::to_table_string`].
+#[cfg(feature = "debug")]
+const TABLE_VALUE_LEN_MAX: usize = 40;
+
+/// Truncates a value's `Debug` string to [`TABLE_VALUE_LEN_MAX`] characters.
+#[cfg(feature = "debug")]
+fn table_value_truncate(value: String) -> String {
+    if value.chars().count() > TABLE_VALUE_LEN_MAX {
+        let mut truncated = value
+            .chars()
+            .take(TABLE_VALUE_LEN_MAX - 3)
+            .collect::<String>();
+        truncated.push_str("...");
+        truncated
+    } else {
+        value
+    }
+}
+
+struct InnerWrapper<'inner, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+    BoxDT: DataTypeWrapper,
+{
+    inner: &'inner Map<K, BoxDT>,
+}
+
+impl<K, BoxDT> Debug for InnerWrapper<'_, K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+    BoxDT: DataTypeWrapper,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.inner
+                    .iter()
+                    .map(|(k, resource)| (k, crate::typed_value(resource))),
+            )
+            .finish()
+    }
+}
+
+impl<K, BoxDT, ValueT> Debug for TypeMap<K, BoxDT, UnknownEntriesSome<ValueT>>
+where
+    K: Eq + Hash + Debug,
+    BoxDT: DataTypeWrapper,
+    ValueT: Clone + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TypeMap")
+            .field("inner", &InnerWrapper { inner: &self.inner })
+            .field("unknown_entries", &self.unknown_entries)
+            .finish()
+    }
+}
+
+/// Moves the value for `from` to the key `to`, removing any existing value for
+/// `to`.
+///
+/// With the `"ordered"` feature, the value keeps the position of `from`.
+fn map_rename<K, V, Q>(map: &mut Map<K, V>, from: &Q, to: K)
+where
+    K: Borrow<Q> + Eq + Hash,
+    Q: Hash + Eq + ?Sized,
+{
+    #[cfg(not(feature = "ordered"))]
+    {
+        map.remove::<K>(&to);
+        if let Some(v) = map.remove(from) {
+            map.insert(to, v);
+        }
+    }
+
+    #[cfg(feature = "ordered")]
+    {
+        map.shift_remove::<K>(&to);
+        if let Some((index, _from, v)) = map.shift_remove_full(from) {
+            map.shift_insert(index, to, v);
+        }
+    }
+}
+
+/// Serializes the entries of a map for which the filter function returns
+/// `true`.
+struct TypeMapFiltered<'inner, K, BoxDT, F> {
+    inner: &'inner Map<K, BoxDT>,
+    ephemeral: &'inner HashSet<K>,
+    f: F,
+}
+
+impl<K, BoxDT, F> serde::Serialize for TypeMapFiltered<'_, K, BoxDT, F>
+where
+    K: Eq + Hash + serde::Serialize,
+    BoxDT: DataTypeWrapper,
+    F: Fn(&K, &dyn DataType) -> bool,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_map(
+            self.inner
+                .iter()
+                .filter(|(k, _)| !self.ephemeral.contains(*k))
+                .map(|(k, v)| (k, v.inner()))
+                .filter(|(k, v)| (self.f)(k, *v)),
+        )
+    }
+}
+
+/// Serializes the known entries of a map, followed by its unknown entries.
+struct TypeMapWithUnknowns<'inner, K, BoxDT, ValueT> {
+    inner: &'inner Map<K, BoxDT>,
+    ephemeral: &'inner HashSet<K>,
+    unknown_entries: &'inner Map<K, ValueT>,
+}
+
+impl<K, BoxDT, ValueT> serde::Serialize for TypeMapWithUnknowns<'_, K, BoxDT, ValueT>
+where
+    K: Eq + Hash + serde::Serialize,
+    BoxDT: serde::Serialize,
+    ValueT: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        self.inner
+            .iter()
+            .filter(|(k, _)| !self.ephemeral.contains(*k))
+            .try_for_each(|(k, v)| map.serialize_entry(k, v))?;
+        self.unknown_entries
+            .iter()
+            .filter(|(k, _)| !self.inner.contains_key(*k))
+            .try_for_each(|(k, v)| map.serialize_entry(k, v))?;
+        map.end()
+    }
+}
+
+/// Returns whether two values are equal, treating numeric leaves within
+/// `epsilon` of each other as equal.
+#[cfg(feature = "approx_eq")]
+fn value_approx_eq(a: &serde_value::Value, b: &serde_value::Value, epsilon: f64) -> bool {
+    use serde_value::Value;
+
+    match (a, b) {
+        (Value::Option(Some(a)), Value::Option(Some(b)))
+        | (Value::Newtype(a), Value::Newtype(b)) => value_approx_eq(a, b, epsilon),
+        (Value::Seq(a), Value::Seq(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| value_approx_eq(a, b, epsilon))
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(k, a)| b.get(k).is_some_and(|b| value_approx_eq(a, b, epsilon)))
+        }
+        _ => match (value_as_f64(a), value_as_f64(b)) {
+            (Some(a), Some(b)) => a == b || (a - b).abs() <= epsilon,
+            _ => a == b,
+        },
+    }
+}
+
+/// Returns the value as an `f64` if it is numeric.
+#[cfg(feature = "approx_eq")]
+fn value_as_f64(value: &serde_value::Value) -> Option<f64> {
+    use serde_value::Value;
+
+    match *value {
+        Value::U8(n) => Some(f64::from(n)),
+        Value::U16(n) => Some(f64::from(n)),
+        Value::U32(n) => Some(f64::from(n)),
+        Value::U64(n) => Some(n as f64),
+        Value::I8(n) => Some(f64::from(n)),
+        Value::I16(n) => Some(f64::from(n)),
+        Value::I32(n) => Some(f64::from(n)),
+        Value::I64(n) => Some(n as f64),
+        Value::F32(n) => Some(f64::from(n)),
+        Value::F64(n) => Some(n),
+        _ => None,
+    }
+}
+
+/// Serializes known entries and unknown entries together, sorted by key.
+struct TypeMapWithUnknownsSorted<'inner, K, BoxDT, ValueT> {
+    inner: &'inner Map<K, BoxDT>,
+    ephemeral: &'inner HashSet<K>,
+    unknown_entries: &'inner Map<K, ValueT>,
+}
+
+/// Value of either a known or unknown entry.
+enum KnownOrUnknown<'inner, BoxDT, ValueT> {
+    Known(&'inner BoxDT),
+    Unknown(&'inner ValueT),
+}
+
+impl<BoxDT, ValueT> serde::Serialize for KnownOrUnknown<'_, BoxDT, ValueT>
+where
+    BoxDT: serde::Serialize,
+    ValueT: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Known(box_dt) => box_dt.serialize(serializer),
+            Self::Unknown(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl<K, BoxDT, ValueT> serde::Serialize for TypeMapWithUnknownsSorted<'_, K, BoxDT, ValueT>
+where
+    K: Eq + Hash + Ord + serde::Serialize,
+    BoxDT: serde::Serialize,
+    ValueT: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let known = self
+            .inner
+            .iter()
+            .filter(|(k, _)| !self.ephemeral.contains(*k))
+            .map(|(k, v)| (k, KnownOrUnknown::Known(v)));
+        let unknown = self
+            .unknown_entries
+            .iter()
+            .filter(|(k, _)| !self.inner.contains_key(*k))
+            .map(|(k, v)| (k, KnownOrUnknown::Unknown(v)));
+
+        let mut entries = known.chain(unknown).collect::<Vec<_>>();
+        entries.sort_by_key(|(k, _)| *k);
+
+        serializer.collect_map(entries)
+    }
+}
+
+/// Serializes the entries of a map as a sequence of `[key, value]` pairs.
+struct TypeMapSeq<'inner, K, BoxDT> {
+    inner: &'inner Map<K, BoxDT>,
+    ephemeral: &'inner HashSet<K>,
+}
+
+impl<K, BoxDT> serde::Serialize for TypeMapSeq<'_, K, BoxDT>
+where
+    K: Eq + Hash + serde::Serialize,
+    BoxDT: DataTypeWrapper,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(
+            self.inner
+                .iter()
+                .filter(|(k, _)| !self.ephemeral.contains(*k))
+                .map(|(k, v)| (k, v.inner())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        any::{type_name, TypeId},
+        fmt::{self, Write},
+    };
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        common::{EntryKind, GetResult, UnknownEntriesSome},
+        untagged::{BoxDataTypeDowncast, BoxDt, BoxDtDisplay, RequiredType, TypeMap, TypeReg},
+        TypeNameLit,
+    };
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn serialize() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", A(3));
+
+        let serialized = serde_yaml::to_string(&type_map).expect("Failed to serialize `type_map`.");
+        let expected = r#"one: 1
+two: 2
+three: 3
+"#
+        .to_string();
+        assert_eq!(expected, serialized);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn to_json_object_skips_ephemeral_entries() {
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert(String::from("one"), 1u32);
+        type_map.insert_ephemeral(String::from("two"), 2u32);
+
+        let json_object = type_map.to_json_object().unwrap();
+
+        assert_eq!(1, json_object.len());
+        assert_eq!(Some(&serde_json::json!(1)), json_object.get("one"));
+    }
+
+    #[cfg(feature = "estimated_size")]
+    #[test]
+    fn estimated_size() {
+        let mut type_map = TypeMap::<u16>::new();
+        assert_eq!(0, type_map.estimated_size());
+
+        type_map.insert(1, A(123));
+        type_map.insert(2, vec![1u8, 2u8]);
+
+        // 2 bytes per key, `123` and `[1,2]`.
+        assert_eq!(12, type_map.estimated_size());
+    }
+
+    #[test]
+    fn partition() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", A(3));
+
+        let (type_map_matching, type_map_rest) = type_map.partition(|k, _| *k != "two");
+
+        assert_eq!(2, type_map_matching.len());
+        assert_eq!(Some(1u32), type_map_matching.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(3)), type_map_matching.get::<A, _>("three").copied());
+        assert_eq!(1, type_map_rest.len());
+        assert_eq!(Some(2u64), type_map_rest.get::<u64, _>("two").copied());
+    }
+
+    #[test]
+    fn partition_type() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", A(3));
+
+        let (type_map_a, type_map_rest) = type_map.partition_type::<A>();
+
+        assert_eq!(1, type_map_a.len());
+        assert_eq!(Some(A(3)), type_map_a.get::<A, _>("three").copied());
+        assert_eq!(2, type_map_rest.len());
+    }
+
+    #[test]
+    fn partition_moves_unknown_entries_to_rest() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map.insert("one", A(1));
+        type_map.insert_unknown("two", 2);
+
+        let (type_map_matching, type_map_rest) = type_map.partition(|_, _| true);
+
+        assert_eq!(1, type_map_matching.len());
+        assert!(type_map_matching.unknown_entries().is_empty());
+        assert_eq!(0, type_map_rest.len());
+        assert_eq!(Some(&2), type_map_rest.get_unknown_entry("two"));
+    }
+
+    #[test]
+    fn serialize_as_seq() {
+        let mut type_map = TypeMap::new();
+        type_map.insert(1u32, A(1));
+
+        let serialized = serde_json::to_string(&type_map.serialize_as_seq())
+            .expect("Failed to serialize `type_map`.");
+        assert_eq!("[[1,1]]", serialized);
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn serialize_as_seq_multiple_entries() {
+        let mut type_map = TypeMap::new();
+        type_map.insert(1u32, A(1));
+        type_map.insert(2u32, String::from("two"));
+
+        let serialized = serde_yaml::to_string(&type_map.serialize_as_seq())
+            .expect("Failed to serialize `type_map`.");
+        assert_eq!("- - 1\n  - 1\n- - 2\n  - two\n", serialized);
+    }
+
+    #[test]
+    fn clone_into() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map.insert("one", A(1));
+        type_map.insert_unknown("two", 2);
+
+        let mut target = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::with_capacity_typed(10);
+        target.insert("three", A(3));
+        target.insert_unknown("four", 4);
+        let capacity = target.capacity();
+        type_map.clone_into(&mut target);
+
+        assert_eq!(1, target.len());
+        assert_eq!(Some(A(1)), target.get::<A, _>("one").copied());
+        assert_eq!(1, target.unknown_entries().len());
+        assert_eq!(Some(&2), target.get_unknown_entry("two"));
+        assert_eq!(capacity, target.capacity());
+    }
+
+    #[test]
+    fn clone_from() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        let mut target = TypeMap::new();
+        target.insert("two", A(2));
+        target.clone_from(&type_map);
+
+        assert_eq!(1, target.len());
+        assert_eq!(Some(A(1)), target.get::<A, _>("one").copied());
+    }
+
+    #[test]
+    fn fold_typed() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u64);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", A(3));
+
+        let mut values = type_map.fold_typed::<u64, _, _>(Vec::new(), |mut values, n| {
+            values.push(*n);
+            values
+        });
+        values.sort_unstable();
+
+        assert_eq!(vec![1, 2], values);
+        assert_eq!(0, type_map.fold_typed::<u32, _, _>(0, |count, _| count + 1));
+    }
+
+    #[test]
+    fn sum_typed() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u64);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", 3u32);
+        type_map.insert("four", A(4));
+
+        assert_eq!(3u64, type_map.sum_typed::<u64>());
+        assert_eq!(3u32, type_map.sum_typed::<u32>());
+        assert_eq!(0u16, type_map.sum_typed::<u16>());
+    }
+
+    #[test]
+    fn serialize_with_unknowns_preserves_raw_value() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let serialized = r#"{"one":1,"two":{ "b": [ 1 ],  "a": 1.50 }}"#;
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, Box<serde_json::value::RawValue>, _, _>(
+                &mut deserializer,
+            )
+            .unwrap();
+
+        assert_eq!(
+            serialized,
+            serde_json::to_string(&type_map.serialize_with_unknowns()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn serialize_with_unknowns_preserves_order() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let serialized = "\
+            one: 1\n\
+            three:\n  \
+              z: 1\n  \
+              y: [2, 3]\n\
+            two: 2\n\
+        ";
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let mut type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+        type_map.insert_unknown(String::from("one"), serde_yaml::Value::Null);
+
+        assert_eq!(
+            "\
+            one: 1\n\
+            three:\n  \
+              z: 1\n  \
+              y:\n  \
+              - 2\n  \
+              - 3\n\
+            two: 2\n\
+            ",
+            serde_yaml::to_string(&type_map.serialize_with_unknowns()).unwrap()
+        );
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn get_by_type_returns_first_match() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", A(2));
+        type_map.insert("three", 3u32);
+
+        assert_eq!(Some((&"two", &A(2))), type_map.get_by_type::<A>());
+        assert_eq!(Some((&"one", &1u32)), type_map.get_by_type::<u32>());
+        assert_eq!(
+            vec![(&"one", &1u32), (&"three", &3u32)],
+            type_map.get_all_by_type::<u32>().collect::<Vec<_>>()
+        );
+        assert_eq!(None, type_map.get_by_type::<u64>());
+    }
+
+    #[test]
+    fn serialize_with_unknowns_sorted_interleaves_entries() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("b"));
+        type_reg.register::<u32>(String::from("d"));
+
+        let deserializer = serde_yaml::Deserializer::from_str(
+            "e: 5
+d: 4
+c: 3
+b: 2
+a: 1
+",
+        );
+        let mut type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+        type_map.insert_unknown(String::from("b"), serde_yaml::Value::Null);
+
+        assert_eq!(
+            "a: 1\nb: 2\nc: 3\nd: 4\ne: 5\n",
+            serde_yaml::to_string(&type_map.serialize_with_unknowns_sorted()).unwrap()
+        );
+    }
+
+    #[test]
+    fn retain_unknown_and_drain_unknown_into() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\nthree: 3\n");
+        let mut type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        type_map.retain_unknown(|k, _| k != "three");
+        let mut unknowns = Vec::new();
+        type_map.drain_unknown_into(&mut unknowns);
+
+        assert_eq!(
+            vec![(String::from("two"), serde_yaml::Value::from(2))],
+            unknowns
+        );
+        assert!(type_map.unknown_entries().is_empty());
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    }
+
+    #[cfg(feature = "approx_eq")]
+    #[test]
+    fn approx_eq_compares_nested_numeric_leaves() {
+        #[derive(Clone, Debug, Serialize)]
+        struct Point {
+            x: f32,
+            ys: Vec<f64>,
+        }
+
+        let mut type_map_a = TypeMap::new();
+        type_map_a.insert("count", 3u32);
+        type_map_a.insert(
+            "point",
+            Point {
+                x: 1.0,
+                ys: vec![0.1 + 0.2],
+            },
+        );
+        let mut type_map_b = TypeMap::new();
+        type_map_b.insert("count", 3u32);
+        type_map_b.insert(
+            "point",
+            Point {
+                x: 1.0,
+                ys: vec![0.3],
+            },
+        );
+
+        assert!(type_map_a.approx_eq(&type_map_b, 1e-9));
+        assert!(!type_map_a.approx_eq(&type_map_b, 0.0));
+
+        type_map_b.insert(
+            "point",
+            Point {
+                x: 1.0,
+                ys: vec![0.3, 0.3],
+            },
+        );
+        assert!(!type_map_a.approx_eq(&type_map_b, 1.0));
+
+        type_map_b.insert(
+            "point",
+            Point {
+                x: 1.0,
+                ys: vec![0.3],
+            },
+        );
+        type_map_b.insert("count", 4u32);
+        assert!(!type_map_a.approx_eq(&type_map_b, 0.5));
+        assert!(type_map_a.approx_eq(&type_map_b, 1.0));
+    }
+
+    #[cfg(all(feature = "anymap2", feature = "ordered"))]
+    #[test]
+    fn into_any_map_keeps_last_value_of_each_type() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", A(2));
+        type_map.insert("three", 3u32);
+
+        let any_map = type_map.into_any_map();
+
+        assert_eq!(2, any_map.len());
+        assert_eq!(Some(&3u32), any_map.get::<u32>());
+        assert_eq!(Some(&A(2)), any_map.get::<A>());
+    }
+
+    #[test]
+    fn serialize_to_writer_streams_entries() {
+        /// Writer that records the largest single write.
+        struct MaxWriteWriter {
+            len: usize,
+            max_write: usize,
+        }
+
+        impl std::io::Write for MaxWriteWriter {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.len += buf.len();
+                self.max_write = self.max_write.max(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut type_map = TypeMap::<String>::new();
+        (0..10_000).for_each(|n| {
+            type_map.insert(format!("key_{n}"), A(n));
+        });
+
+        let mut writer = MaxWriteWriter {
+            len: 0,
+            max_write: 0,
+        };
+        type_map
+            .serialize_to_writer(&mut writer, serde_json::to_writer)
+            .unwrap();
+        assert!(writer.len > 100_000, "{}", writer.len);
+        assert!(writer.max_write < 1024, "{}", writer.max_write);
+
+        let mut writer = MaxWriteWriter {
+            len: 0,
+            max_write: 0,
+        };
+        type_map
+            .serialize_to_writer(&mut writer, serde_yaml::to_writer)
+            .unwrap();
+        assert!(writer.len > 100_000, "{}", writer.len);
+        // `serde_yaml` writes in chunks of its emitter's buffer size.
+        assert!(writer.max_write <= 16 * 1024, "{}", writer.max_write);
+    }
+
+    #[test]
+    fn serialize_unknowns_only() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\n");
+        let type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        assert_eq!(
+            "two: 2\n",
+            serde_yaml::to_string(&type_map.serialize_unknowns_only()).unwrap()
+        );
+    }
+
+    #[test]
+    fn iter_data() {
+        let mut type_map = TypeMap::<_, BoxDtDisplay>::new_typed();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", ADisplay(2));
+
+        let mut type_names = type_map
+            .iter_data()
+            .map(|(k, data_ref)| (*k, data_ref.type_name()))
+            .collect::<Vec<_>>();
+        type_names.sort_by_key(|(k, _)| *k);
+
+        assert_eq!(
+            vec![
+                ("one", TypeNameLit("u32")),
+                ("two", TypeNameLit(type_name::<ADisplay>())),
+            ],
+            type_names
+        );
+        assert_eq!(
+            Some(&ADisplay(2)),
+            type_map
+                .iter_data()
+                .find_map(|(_k, data_ref)| data_ref.downcast_ref::<ADisplay>())
+        );
+    }
+
+    #[test]
+    fn push_typed_skips_used_keys() {
+        let mut type_map = TypeMap::<usize>::new();
+        type_map.insert(0, 0u32);
+        type_map.insert(2, 2u32);
+
+        assert_eq!(3, type_map.push_typed(A(3)));
+        assert_eq!(4, type_map.push_typed(4u32));
+        assert_eq!(Some(A(3)), type_map.get::<A, _>(&3).copied());
+        assert_eq!(Some(4), type_map.get::<u32, _>(&4).copied());
+    }
+
+    #[test]
+    fn rename_key_moves_known_unknown_and_ephemeral() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<serde_yaml::Value>>::new_typed();
+        type_map.insert_ephemeral("one", 1u32);
+        type_map.insert_unknown("one", serde_yaml::Value::Bool(true));
+        type_map.insert("two", A(2));
+        type_map.insert_unknown("two", serde_yaml::Value::Null);
+
+        assert!(type_map.rename_key("one", "two"));
+
+        assert_eq!(None, type_map.get::<u32, _>("one"));
+        assert_eq!(None, type_map.get_unknown_entry("one"));
+        assert_eq!(Some(1), type_map.get::<u32, _>("two").copied());
+        assert_eq!(None, type_map.get::<A, _>("two"));
+        assert_eq!(
+            Some(&serde_yaml::Value::Bool(true)),
+            type_map.get_unknown_entry("two")
+        );
+        assert!(type_map.is_ephemeral("two"));
+        assert_eq!(1, type_map.len());
+    }
+
+    #[test]
+    fn rename_key_returns_false_when_absent() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+
+        assert!(!type_map.rename_key("two", "one"));
+        assert!(type_map.rename_key("one", "one"));
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn rename_key_keeps_position() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", 2u32);
+        type_map.insert("three", 3u32);
+
+        assert!(type_map.rename_key("three", "one"));
+        assert!(type_map.rename_key("two", "zwei"));
+
+        assert_eq!(vec![&"zwei", &"one"], type_map.keys().collect::<Vec<_>>());
+        assert_eq!(Some(3), type_map.get::<u32, _>("one").copied());
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let mut type_map = TypeMap::new();
+        type_map.insert_ephemeral("one", 1u64);
+
+        let (one, old) = type_map.insert_and_get("one", 1u32);
+        *one += 1;
+
+        assert_eq!(Some(1u64), old.and_then(|old| old.downcast_ref().copied()));
+        assert_eq!(Some(2), type_map.get::<u32, _>("one").copied());
+        assert!(!type_map.is_ephemeral("one"));
+
+        let (two, old) = type_map.insert_and_get("two", A(2));
+        assert_eq!(A(2), *two);
+        assert!(old.is_none());
+    }
+
+    #[test]
+    fn require_type() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", A(2));
+
+        assert_eq!(Ok(()), type_map.require_type::<A>());
+        assert_eq!(
+            vec![TypeNameLit("u64")],
+            type_map
+                .require_type::<u64>()
+                .unwrap_err()
+                .into_type_names()
+        );
+    }
+
+    #[test]
+    fn require_types_lists_each_missing_type() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", A(2));
+
+        assert_eq!(
+            Ok(()),
+            type_map.require_types(&[RequiredType::of::<u32>(), RequiredType::of::<A>()])
+        );
+
+        let error = type_map
+            .require_types(&[
+                RequiredType::of::<u64>(),
+                RequiredType::of::<A>(),
+                RequiredType::of::<bool>(),
+            ])
+            .unwrap_err();
+        assert_eq!(
+            &[TypeNameLit("u64"), TypeNameLit("bool")],
+            error.type_names()
+        );
+        assert_eq!(
+            "No entries exist for required types: `u64`, `bool`",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn erased_value() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("three", A(3));
+
+        let three = type_map.erased_value("three").unwrap();
+        let mut serialized = Vec::new();
+        let mut serializer = serde_json::Serializer::new(&mut serialized);
+        erased_serde::serialize(three, &mut serializer).unwrap();
+
+        assert_eq!(b"3", serialized.as_slice());
+        assert!(type_map.erased_value("four").is_none());
+    }
+
+    #[test]
+    fn type_names() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("three", A(3));
+
+        let mut type_names = type_map.type_names().collect::<Vec<_>>();
+        type_names.sort_by_key(|(k, _)| **k);
+
+        assert_eq!(
+            vec![
+                (&"one", TypeNameLit(type_name::<u32>())),
+                (&"three", TypeNameLit(type_name::<A>())),
+            ],
+            type_names
+        );
+    }
+
+    #[cfg(not(feature = "debug"))]
+    #[test]
+    fn to_table_string() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("three", String::from("3"));
+        type_map.insert("one", 1u32);
+
+        assert_eq!(
+            "key    type\n\
+            one    u32\n\
+            three  alloc::string::String\n",
+            type_map.to_table_string()
+        );
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn to_table_string() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("three", String::from("3"));
+        type_map.insert("one", 1u32);
+        type_map.insert("long", "a".repeat(50));
+
+        assert_eq!(
+            format!(
+                "key    type                   value\n\
+                long   alloc::string::String  \"{a}...\n\
+                one    u32                    1\n\
+                three  alloc::string::String  \"3\"\n",
+                a = "a".repeat(36)
+            ),
+            type_map.to_table_string()
+        );
+    }
+
+    #[test]
+    fn to_table_string_empty() {
+        let type_map = TypeMap::<&'static str>::new();
+
+        #[cfg(not(feature = "debug"))]
+        assert_eq!("key  type\n", type_map.to_table_string());
+        #[cfg(feature = "debug")]
+        assert_eq!("key  type  value\n", type_map.to_table_string());
+    }
+
+    #[test]
+    fn serialize_filtered() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", 2u64);
+        type_map.insert("three", A(3));
+
+        let serialized = serde_yaml::to_string(
+            &type_map.serialize_filtered(|_k, v| v.type_id_inner() == TypeId::of::<A>()),
+        )
+        .expect("Failed to serialize `type_map`.");
+        assert_eq!("three: 3\n", serialized);
+    }
+
+    #[test]
+    fn insert_ephemeral_is_skipped_when_serializing() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert_ephemeral("two", A(2));
+
+        assert!(type_map.is_ephemeral("two"));
+        assert!(!type_map.is_ephemeral("one"));
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+        assert_eq!("one: 1\n", serde_yaml::to_string(&type_map).unwrap());
+        assert_eq!(
+            "- - one\n  - 1\n",
+            serde_yaml::to_string(&type_map.serialize_as_seq()).unwrap()
+        );
+        assert_eq!(
+            "one: 1\n",
+            serde_yaml::to_string(&type_map.serialize_filtered(|_, _| true)).unwrap()
+        );
+    }
+
+    #[test]
+    fn clear_keeps_unknown_entries() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map.insert_ephemeral("one", 1u32);
+        type_map.insert_unknown("two", 2u32);
+
+        type_map.clear();
+
+        assert!(type_map.is_empty());
+        assert!(!type_map.is_ephemeral("one"));
+        assert_eq!(Some(&2), type_map.get_unknown_entry("two"));
+    }
+
+    #[test]
+    fn serialize_passes_map_length_hint() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert("two", A(2));
+
+        assert_eq!(Some(2), map_len_hint(&type_map));
+
+        type_map.insert_ephemeral("three", 3u32);
+        type_map.insert_ephemeral("four", 4u32);
+        // Leaves "four" in the ephemeral keys.
+        type_map.retain(|k, _| *k != "four");
+
+        assert_eq!(Some(2), map_len_hint(&type_map));
+    }
+
+    #[test]
+    fn insert_clears_ephemeral() {
+        let mut type_map = TypeMap::new();
+        type_map.insert_ephemeral("one", 1u32);
+        type_map.insert("one", 2u32);
+
+        assert!(!type_map.is_ephemeral("one"));
+        assert_eq!("one: 2\n", serde_yaml::to_string(&type_map).unwrap());
+    }
+
+    #[test]
+    fn entry_typed_and_get_or_try_insert_with_replacing_clear_ephemeral() {
+        let mut type_map = TypeMap::new();
+        type_map.insert_ephemeral("one", 1u32);
+        type_map.insert_ephemeral("two", A(2));
+        type_map.insert_ephemeral("three", 3u32);
+        *type_map.entry_typed("one").or_insert(0u64) += 1;
+        type_map
+            .get_or_try_insert_with::<u64, _, ()>("two", || Ok(2))
+            .unwrap();
+        *type_map.entry_typed("three").or_insert(0u32) += 1;
+
+        assert!(!type_map.is_ephemeral("one"));
+        assert!(!type_map.is_ephemeral("two"));
+        assert!(type_map.is_ephemeral("three"));
+        let serialized = serde_yaml::to_string(&type_map).unwrap();
+        assert!(serialized.contains("one: 1\n"), "{serialized}");
+        assert!(serialized.contains("two: 2\n"), "{serialized}");
+        assert!(!serialized.contains("three"), "{serialized}");
+    }
+
+    #[test]
+    fn remove_then_entry_reinsert_clears_ephemeral() {
+        let mut type_map = TypeMap::new();
+        type_map.insert_ephemeral("one", 1u32);
+
+        assert_eq!(
+            Some(1),
+            type_map
+                .remove("one")
+                .and_then(|box_dt| BoxDataTypeDowncast::<u32>::downcast_ref(&box_dt).copied())
+        );
+        assert!(!type_map.is_ephemeral("one"));
+
+        type_map.entry("one").or_insert_with(|| BoxDt::new(2u32));
+
+        assert!(!type_map.is_ephemeral("one"));
+        assert_eq!("one: 2\n", serde_yaml::to_string(&type_map).unwrap());
+    }
+
+    #[test]
+    fn clone_preserves_ephemeral() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+        type_map.insert_ephemeral("two", A(2));
+
+        let type_map_clone = type_map.clone();
+        let mut type_map_clone_into = TypeMap::new();
+        type_map.clone_into(&mut type_map_clone_into);
+
+        assert!(type_map_clone.is_ephemeral("two"));
+        assert!(type_map_clone_into.is_ephemeral("two"));
+        assert_eq!("one: 1\n", serde_yaml::to_string(&type_map_clone).unwrap());
+    }
+
+    #[test]
+    fn build_matching_type_reg() {
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert_registerable(String::from("one"), 1u32);
+        type_map.insert_registerable(String::from("two"), A(2));
+
+        let type_reg = type_map.build_matching_type_reg();
+        let serialized = serde_yaml::to_string(&type_map).unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str(&serialized);
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+        assert_eq!(2, type_reg.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn build_matching_type_reg_skips_entries_not_registerable() {
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert_registerable(String::from("one"), 1u32);
+        type_map.insert(String::from("three"), 3u64);
+
+        let type_reg = type_map.clone().build_matching_type_reg();
+        let serialized = serde_yaml::to_string(&type_map).unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str(&serialized);
+        let error = type_reg
+            .deserialize_map::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert_eq!(1, type_reg.len());
+        assert!(error.to_string().contains("`\"three\"`"), "{error}");
+    }
+
+    #[test]
+    fn build_matching_type_reg_skips_replaced_entries() {
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert_registerable(String::from("one"), 1u32);
+        type_map.insert_registerable(String::from("two"), A(2));
+        type_map.insert(String::from("two"), 2u64);
+
+        let type_reg = type_map.build_matching_type_reg();
+
+        assert_eq!(1, type_reg.len());
+        assert!(type_reg.contains_key("one"));
+    }
+
+    #[test]
+    fn build_matching_type_reg_skips_entries_replaced_through_entry_typed() {
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert_registerable(String::from("one"), 1u32);
+        type_map.insert_registerable(String::from("two"), A(2));
+        *type_map.entry_typed(String::from("two")).or_insert(0u64) += 2;
+
+        let mut type_reg = type_map.build_matching_type_reg();
+        assert!(!type_reg.contains_key("two"));
+        type_reg.register::<u64>(String::from("two"));
+
+        let serialized = serde_yaml::to_string(&type_map).unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str(&serialized);
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(2u64), type_map.get::<u64, _>("two").copied());
+    }
+
+    #[test]
+    fn build_matching_type_reg_skips_entries_replaced_through_get_or_try_insert_with() {
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert_registerable(String::from("one"), 1u32);
+        type_map
+            .get_or_try_insert_with::<u64, _, ()>(String::from("one"), || Ok(1))
+            .unwrap();
+
+        let type_reg = type_map.build_matching_type_reg();
+
+        assert!(type_reg.is_empty());
+    }
+
+    #[test]
+    fn partition_preserves_ephemeral() {
+        let mut type_map = TypeMap::new();
+        type_map.insert_ephemeral("one", 1u32);
+        type_map.insert_ephemeral("two", A(2));
+
+        let (type_map_u32, type_map_rest) = type_map.partition_type::<u32>();
+
+        assert!(type_map_u32.is_ephemeral("one"));
+        assert!(type_map_rest.is_ephemeral("two"));
+        assert!(!type_map_rest.is_ephemeral("one"));
+    }
+
+    #[test]
+    fn from_homogeneous() {
+        let mut map = std::collections::HashMap::new();
+        map.insert("one", A(1));
+        map.insert("two", A(2));
+
+        let type_map = TypeMap::from_homogeneous(map);
+
+        assert_eq!(2, type_map.len());
+        assert_eq!(Some(A(1)), type_map.get::<A, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn at() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        assert_eq!(A(1), *type_map.at::<A, _>("one"));
+    }
+
+    #[test]
+    #[should_panic(expected = "no entry for key `\"two\"`")]
+    fn at_panics_when_key_missing() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        type_map.at::<A, _>("two");
     }
-}
 
-impl<K, BoxDT, UnknownEntriesT> Default for TypeMap<K, BoxDT, UnknownEntriesT>
-where
-    K: Eq + Hash,
-    UnknownEntriesT: UnknownEntries,
-{
-    fn default() -> Self {
-        Self {
-            inner: Map::default(),
-            unknown_entries: Map::default(),
-        }
+    #[test]
+    #[should_panic(
+        expected = "entry `\"one\"` is a `type_reg::untagged::type_map::tests::A`, not a `u32`"
+    )]
+    fn at_panics_when_type_mismatch() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        type_map.at::<u32, _>("one");
     }
-}
 
-impl<K, BoxDT, UnknownEntriesT> Deref for TypeMap<K, BoxDT, UnknownEntriesT>
-where
-    K: Eq + Hash,
-    UnknownEntriesT: UnknownEntries,
-{
-    type Target = Map<K, BoxDT>;
+    #[test]
+    fn at_mut() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
 
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+        type_map.at_mut::<A, _>("one").0 = 2;
+
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("one").copied());
     }
-}
 
-impl<K, BoxDT, UnknownEntriesT> DerefMut for TypeMap<K, BoxDT, UnknownEntriesT>
-where
-    K: Eq + Hash,
-    UnknownEntriesT: UnknownEntries,
-{
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
+    #[test]
+    #[should_panic(
+        expected = "entry `\"one\"` is a `type_reg::untagged::type_map::tests::A`, not a `u32`"
+    )]
+    fn at_mut_panics_when_type_mismatch() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        type_map.at_mut::<u32, _>("one");
     }
-}
 
-impl<K, BoxDT> Debug for TypeMap<K, BoxDT, UnknownEntriesNone>
-where
-    K: Eq + Hash + Debug,
-    BoxDT: DataTypeWrapper,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn sort_keys() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+        type_map.insert_unknown("four", 4);
+        type_map.insert_unknown("three", 3);
 
-        self.inner.iter().for_each(|(k, resource)| {
-            // At runtime, we are unable to determine if the resource is `Debug`.
-            #[cfg(not(feature = "debug"))]
-            let value = &"..";
+        type_map.sort_keys();
 
-            #[cfg(feature = "debug")]
-            let value = resource.debug();
+        assert_eq!(vec![&"one", &"two"], type_map.keys().collect::<Vec<_>>());
+        assert_eq!(
+            vec![&"four", &"three"],
+            type_map.unknown_entries().keys().collect::<Vec<_>>()
+        );
+    }
 
-            let type_name = resource.type_name();
-            let debug_value = crate::TypedValue {
-                r#type: type_name,
-                value,
-            };
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn sort_by() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+        type_map.insert("two", A(2));
+        type_map.insert("three", A(3));
 
-            debug_map.key(&k);
-            debug_map.value(&debug_value);
+        type_map.sort_by(|_k1, v1, _k2, v2| {
+            let a1 = BoxDataTypeDowncast::<A>::downcast_ref(v1).unwrap();
+            let a2 = BoxDataTypeDowncast::<A>::downcast_ref(v2).unwrap();
+            a2.0.cmp(&a1.0)
         });
 
-        debug_map.finish()
+        assert_eq!(
+            vec![&"three", &"two", &"one"],
+            type_map.keys().collect::<Vec<_>>()
+        );
     }
-}
 
-struct InnerWrapper<'inner, K, BoxDT>
-where
-    K: Eq + Hash + Debug,
-    BoxDT: DataTypeWrapper,
-{
-    inner: &'inner Map<K, BoxDT>,
-}
+    #[test]
+    fn into_entries_from_entries_round_trip() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map.insert("one", A(1));
+        type_map.insert("two", 2u32);
+        type_map.insert_unknown("three", 3);
 
-impl<K, BoxDT> Debug for InnerWrapper<'_, K, BoxDT>
-where
-    K: Eq + Hash + Debug,
-    BoxDT: DataTypeWrapper,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
+        let entries = type_map.into_entries();
+        assert_eq!(2, entries.len());
 
-        self.inner.iter().for_each(|(k, resource)| {
-            // At runtime, we are unable to determine if the resource is `Debug`.
-            #[cfg(not(feature = "debug"))]
-            let value = &"..";
+        let type_map = TypeMap::<_, BoxDt>::from_entries(entries);
+        assert_eq!(Some(A(1)), type_map.get::<A, _>("one").copied());
+        assert_eq!(Some(2u32), type_map.get::<u32, _>("two").copied());
+    }
 
-            #[cfg(feature = "debug")]
-            let value = resource.debug();
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn into_entries_preserves_insertion_order() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+        type_map.insert("three", A(3));
 
-            let type_name = resource.type_name();
-            let debug_value = crate::TypedValue {
-                r#type: type_name,
-                value,
-            };
+        let keys = type_map
+            .into_entries()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
 
-            debug_map.key(&k);
-            debug_map.value(&debug_value);
-        });
+        assert_eq!(vec!["two", "one", "three"], keys);
+    }
+
+    #[test]
+    fn get_or_try_insert_with_returns_existing_value() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
 
-        debug_map.finish()
+        let one = type_map
+            .get_or_try_insert_with::<A, _, ()>("one", || panic!("`f` should not be called."))
+            .unwrap();
+        one.0 = 2;
+
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("one").copied());
     }
-}
 
-impl<K, BoxDT, ValueT> Debug for TypeMap<K, BoxDT, UnknownEntriesSome<ValueT>>
-where
-    K: Eq + Hash + Debug,
-    BoxDT: DataTypeWrapper,
-    ValueT: Clone + Debug + PartialEq + Eq,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("TypeMap")
-            .field("inner", &InnerWrapper { inner: &self.inner })
-            .field("unknown_entries", &self.unknown_entries)
-            .finish()
+    #[test]
+    fn get_or_try_insert_with_inserts_value() {
+        let mut type_map = TypeMap::new();
+
+        let one = type_map.get_or_try_insert_with::<A, _, ()>("one", || Ok(A(1)));
+
+        assert_eq!(Ok(&mut A(1)), one);
+        assert_eq!(Some(A(1)), type_map.get::<A, _>("one").copied());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::fmt::{self, Write};
+    #[test]
+    fn get_or_try_insert_with_replaces_mismatched_value() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
 
-    use serde::{Deserialize, Serialize};
+        let one = type_map.get_or_try_insert_with::<A, _, ()>("one", || Ok(A(1)));
 
-    use crate::{
-        common::UnknownEntriesSome,
-        untagged::{BoxDataTypeDowncast, BoxDt, BoxDtDisplay, TypeMap},
-    };
+        assert_eq!(Ok(&mut A(1)), one);
+        assert_eq!(None, type_map.get::<u32, _>("one"));
+    }
 
-    #[cfg(feature = "ordered")]
     #[test]
-    fn serialize() {
+    fn get_or_try_insert_with_leaves_map_unchanged_on_error() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", 1u32);
+
+        let one = type_map.get_or_try_insert_with::<A, _, _>("one", || Err("failed"));
+        assert_eq!(Err("failed"), one);
+        let two = type_map.get_or_try_insert_with::<A, _, _>("two", || Err("failed"));
+        assert_eq!(Err("failed"), two);
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(1, type_map.len());
+    }
+
+    #[test]
+    fn serialize_entry_to_vec() {
         let mut type_map = TypeMap::new();
         type_map.insert("one", 1u32);
-        type_map.insert("two", 2u64);
         type_map.insert("three", A(3));
 
-        let serialized = serde_yaml::to_string(&type_map).expect("Failed to serialize `type_map`.");
-        let expected = r#"one: 1
-two: 2
-three: 3
-"#
-        .to_string();
-        assert_eq!(expected, serialized);
+        let one = type_map
+            .serialize_entry_to_vec("one", serde_json::to_vec)
+            .transpose()
+            .expect("Failed to serialize `one`.");
+        let three = type_map
+            .serialize_entry_to_vec("three", |v| {
+                serde_yaml::to_string(v).map(String::into_bytes)
+            })
+            .transpose()
+            .expect("Failed to serialize `three`.");
+        let two = type_map.serialize_entry_to_vec("two", serde_json::to_vec);
+
+        assert_eq!(Some(b"1".to_vec()), one);
+        assert_eq!(Some(b"3\n".to_vec()), three);
+        assert!(two.is_none());
     }
 
     #[test]
@@ -638,11 +3857,29 @@ three: 3
         assert_eq!(Some(A(2)), type_map_clone.get("one").copied());
     }
 
+    #[test]
+    fn location_of() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<serde_yaml::Value>>::new_typed();
+        type_map.insert("one", A(1));
+        type_map.insert_unknown("two", serde_yaml::Value::Bool(true));
+        type_map.insert("three", A(3));
+        type_map.insert_unknown("three", serde_yaml::Value::Bool(true));
+
+        assert!(type_map.contains_known("one"));
+        assert!(!type_map.contains_unknown("one"));
+        assert!(!type_map.contains_known("two"));
+        assert!(type_map.contains_unknown("two"));
+        assert_eq!(Some(EntryKind::Known), type_map.location_of("one"));
+        assert_eq!(Some(EntryKind::Unknown), type_map.location_of("two"));
+        assert_eq!(Some(EntryKind::Known), type_map.location_of("three"));
+        assert_eq!(None, type_map.location_of("four"));
+    }
+
     #[test]
     fn clone_with_unknown_entries() {
         let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<serde_yaml::Value>>::new_typed();
         type_map.insert("one", A(1));
-        type_map.insert_unknown_entry("two", serde_yaml::Value::Bool(true));
+        type_map.insert_unknown("two", serde_yaml::Value::Bool(true));
 
         let mut type_map_clone = type_map.clone();
         type_map_clone.insert("one", A(2));
@@ -726,6 +3963,58 @@ three: 3
         );
     }
 
+    #[cfg(not(feature = "debug"))]
+    #[test]
+    fn debug_sorted() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+        type_map.insert("three", A(3));
+
+        assert_eq!(
+            "{\
+                \"one\": TypedValue { type: \"type_reg::untagged::type_map::tests::A\", value: \"..\" }, \
+                \"three\": TypedValue { type: \"type_reg::untagged::type_map::tests::A\", value: \"..\" }, \
+                \"two\": TypedValue { type: \"type_reg::untagged::type_map::tests::A\", value: \"..\" }\
+            }",
+            format!("{:?}", type_map.debug_sorted())
+        );
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn debug_sorted() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("two", A(2));
+        type_map.insert("one", A(1));
+        type_map.insert("three", A(3));
+
+        assert_eq!(
+            "{\
+                \"one\": TypedValue { type: \"type_reg::untagged::type_map::tests::A\", value: A(1) }, \
+                \"three\": TypedValue { type: \"type_reg::untagged::type_map::tests::A\", value: A(3) }, \
+                \"two\": TypedValue { type: \"type_reg::untagged::type_map::tests::A\", value: A(2) }\
+            }",
+            format!("{:?}", type_map.debug_sorted())
+        );
+    }
+
+    #[test]
+    fn debug_sorted_with_unknown_entries_some() {
+        let mut type_map = TypeMap::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map.insert_unknown("two", 2);
+        type_map.insert_unknown("one", 1);
+        type_map.insert_unknown("three", 3);
+
+        assert_eq!(
+            "TypeMap { \
+                inner: {}, \
+                unknown_entries: {\"one\": 1, \"three\": 3, \"two\": 2} \
+            }",
+            format!("{:?}", type_map.debug_sorted())
+        );
+    }
+
     #[test]
     fn into_inner_unknown_entries_none() {
         let mut type_map = TypeMap::new();
@@ -755,6 +4044,20 @@ three: 3
         assert!(unknown_entries.is_empty());
     }
 
+    #[test]
+    fn get_typed() {
+        let mut type_map = TypeMap::new();
+        type_map.insert("one", A(1));
+
+        assert_eq!(GetResult::Present(&A(1)), type_map.get_typed::<A, _>("one"));
+        assert_eq!(
+            GetResult::WrongType(TypeNameLit(type_name::<A>())),
+            type_map.get_typed::<u32, _>("one")
+        );
+        assert_eq!(GetResult::Absent, type_map.get_typed::<A, _>("two"));
+        assert_eq!(None, type_map.get_typed::<u32, _>("one").ok());
+    }
+
     #[test]
     fn get_mut() {
         let mut type_map = TypeMap::new();
@@ -783,6 +4086,22 @@ three: 3
         assert_eq!(Some(1), one);
     }
 
+    #[test]
+    fn get_raw_cloned() {
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("one", 1u32);
+        let boxed_one = type_map.get_raw_cloned("one");
+        type_map.insert("one", 2u32);
+
+        let one = boxed_one
+            .as_ref()
+            .and_then(BoxDataTypeDowncast::<u32>::downcast_ref)
+            .copied();
+
+        assert_eq!(Some(1), one);
+        assert!(type_map.get_raw_cloned("two").is_none());
+    }
+
     #[test]
     fn get_raw_mut() {
         let mut type_map = TypeMap::<&'static str>::new();
@@ -840,6 +4159,19 @@ three: 3
         Ok(())
     }
 
+    #[test]
+    fn to_string_map() {
+        let mut type_map = TypeMap::<_, BoxDtDisplay>::new_typed();
+        type_map.insert("one", ADisplay(1));
+        type_map.insert("two", 2u32);
+
+        let string_map = type_map.to_string_map();
+
+        assert_eq!(2, string_map.len());
+        assert_eq!(Some("1"), string_map.get("one").map(String::as_str));
+        assert_eq!(Some("2"), string_map.get("two").map(String::as_str));
+    }
+
     #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
     struct A(u32);
 
@@ -867,4 +4199,122 @@ three: 3
         assert!(serde_yaml::to_string(&a).is_ok());
         assert_eq!(ADisplay(0), serde_yaml::from_str("0").unwrap());
     }
+
+    /// Returns the length passed to `Serializer::serialize_map` when
+    /// serializing `value`.
+    fn map_len_hint<T>(value: &T) -> Option<usize>
+    where
+        T: Serialize,
+    {
+        match value.serialize(MapLenSerializer) {
+            Err(MapLenError::Len(len)) => len,
+            Err(MapLenError::Unsupported) | Ok(()) => panic!("Expected value to serialize a map."),
+        }
+    }
+
+    /// Serializer that only records the length hint of a map.
+    struct MapLenSerializer;
+
+    #[derive(Debug)]
+    enum MapLenError {
+        Len(Option<usize>),
+        Unsupported,
+    }
+
+    impl fmt::Display for MapLenError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{self:?}")
+        }
+    }
+
+    impl std::error::Error for MapLenError {}
+
+    impl serde::ser::Error for MapLenError {
+        fn custom<T>(_msg: T) -> Self
+        where
+            T: fmt::Display,
+        {
+            MapLenError::Unsupported
+        }
+    }
+
+    macro_rules! unsupported {
+        ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+            $(
+                fn $method(self, $(_: $arg),*) -> Result<$ok, MapLenError> {
+                    Err(MapLenError::Unsupported)
+                }
+            )*
+        };
+    }
+
+    impl serde::Serializer for MapLenSerializer {
+        type Error = MapLenError;
+        type Ok = ();
+        type SerializeMap = serde::ser::Impossible<(), MapLenError>;
+        type SerializeSeq = serde::ser::Impossible<(), MapLenError>;
+        type SerializeStruct = serde::ser::Impossible<(), MapLenError>;
+        type SerializeStructVariant = serde::ser::Impossible<(), MapLenError>;
+        type SerializeTuple = serde::ser::Impossible<(), MapLenError>;
+        type SerializeTupleStruct = serde::ser::Impossible<(), MapLenError>;
+        type SerializeTupleVariant = serde::ser::Impossible<(), MapLenError>;
+
+        unsupported! {
+            serialize_bool(bool) -> ();
+            serialize_i8(i8) -> ();
+            serialize_i16(i16) -> ();
+            serialize_i32(i32) -> ();
+            serialize_i64(i64) -> ();
+            serialize_u8(u8) -> ();
+            serialize_u16(u16) -> ();
+            serialize_u32(u32) -> ();
+            serialize_u64(u64) -> ();
+            serialize_f32(f32) -> ();
+            serialize_f64(f64) -> ();
+            serialize_char(char) -> ();
+            serialize_str(&str) -> ();
+            serialize_bytes(&[u8]) -> ();
+            serialize_none() -> ();
+            serialize_unit() -> ();
+            serialize_unit_struct(&'static str) -> ();
+            serialize_unit_variant(&'static str, u32, &'static str) -> ();
+            serialize_seq(Option<usize>) -> Self::SerializeSeq;
+            serialize_tuple(usize) -> Self::SerializeTuple;
+            serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+            serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+            serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+            serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+        }
+
+        fn serialize_some<T>(self, _: &T) -> Result<(), MapLenError>
+        where
+            T: ?Sized + Serialize,
+        {
+            Err(MapLenError::Unsupported)
+        }
+
+        fn serialize_newtype_struct<T>(self, _: &'static str, _: &T) -> Result<(), MapLenError>
+        where
+            T: ?Sized + Serialize,
+        {
+            Err(MapLenError::Unsupported)
+        }
+
+        fn serialize_newtype_variant<T>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<(), MapLenError>
+        where
+            T: ?Sized + Serialize,
+        {
+            Err(MapLenError::Unsupported)
+        }
+
+        fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, MapLenError> {
+            Err(MapLenError::Len(len))
+        }
+    }
 }
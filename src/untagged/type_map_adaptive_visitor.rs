@@ -0,0 +1,62 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that can be used to deserialize a map of untagged or externally
+/// tagged values.
+///
+/// Each value is buffered, then deserialized using the [`DeserializeSeed`]
+/// registered for its key, falling back to the externally tagged form if the
+/// untagged form fails to deserialize. Thus it will return an error if the
+/// visited type is not a map.
+///
+/// [`DeserializeSeed`]: serde::de::DeserializeSeed
+pub struct TypeMapAdaptiveVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'r, K, BoxDT> TypeMapAdaptiveVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`].
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>) -> Self {
+        TypeMapAdaptiveVisitor { type_reg }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapAdaptiveVisitor<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = match map_access.size_hint() {
+            Some(n) => TypeMap::with_capacity_typed(n),
+            _ => TypeMap::new_typed(),
+        };
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            let value = map_access.next_value::<serde_value::Value>()?;
+            let value = self.type_reg.deserialize_adaptive(&key, value)?;
+            type_map.insert_raw(key, value);
+        }
+
+        Ok(type_map)
+    }
+}
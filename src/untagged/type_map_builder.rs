@@ -0,0 +1,149 @@
+use std::hash::Hash;
+
+use crate::untagged::{BoxDt, DataTypeWrapper, FromDataType, MissingKeysError, TypeMap};
+
+/// Builds a [`TypeMap`] in code, checking that required keys are inserted.
+///
+/// # Examples
+///
+/// ```rust
+/// use type_reg::untagged::TypeMapBuilder;
+///
+/// let type_map = TypeMapBuilder::<&'static str>::new()
+///     .require("host")
+///     .require("port")
+///     .insert("host", String::from("localhost"))
+///     .insert("port", 8080u16)
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(Some(8080), type_map.get::<u16, _>("port").copied());
+///
+/// let error = TypeMapBuilder::<&'static str>::new()
+///     .require("host")
+///     .require("port")
+///     .insert("host", String::from("localhost"))
+///     .build()
+///     .unwrap_err();
+///
+/// assert_eq!(&["port"], error.keys());
+/// ```
+pub struct TypeMapBuilder<K, BoxDT = BoxDt>
+where
+    K: Eq + Hash,
+{
+    /// Map being built.
+    type_map: TypeMap<K, BoxDT>,
+    /// Keys that must be inserted before building, in the order they were
+    /// required.
+    required: Vec<K>,
+}
+
+impl<K> TypeMapBuilder<K, BoxDt>
+where
+    K: Eq + Hash,
+{
+    /// Returns a new `TypeMapBuilder` with no entries and no required keys.
+    pub fn new() -> Self {
+        Self::new_typed()
+    }
+}
+
+impl<K, BoxDT> TypeMapBuilder<K, BoxDT>
+where
+    K: Eq + Hash,
+    BoxDT: DataTypeWrapper,
+{
+    /// Returns a new `TypeMapBuilder` with no entries and no required keys.
+    pub fn new_typed() -> Self {
+        Self {
+            type_map: TypeMap::new_typed(),
+            required: Vec::new(),
+        }
+    }
+
+    /// Marks the key as required, so [`build`] fails if it is not inserted.
+    ///
+    /// [`build`]: Self::build
+    #[must_use]
+    pub fn require(mut self, k: K) -> Self {
+        if !self.required.contains(&k) {
+            self.required.push(k);
+        }
+        self
+    }
+
+    /// Inserts a key-value pair into the map being built.
+    ///
+    /// If the key was already inserted, the value is replaced.
+    #[must_use]
+    pub fn insert<R>(mut self, k: K, r: R) -> Self
+    where
+        BoxDT: FromDataType<R>,
+    {
+        self.type_map.insert(k, r);
+        self
+    }
+
+    /// Returns the built [`TypeMap`], or an error listing the required keys
+    /// that were not inserted.
+    pub fn build(self) -> Result<TypeMap<K, BoxDT>, MissingKeysError<K>> {
+        let TypeMapBuilder { type_map, required } = self;
+        let keys = required
+            .into_iter()
+            .filter(|k| !type_map.contains_key(k))
+            .collect::<Vec<K>>();
+
+        if keys.is_empty() {
+            Ok(type_map)
+        } else {
+            Err(MissingKeysError { keys })
+        }
+    }
+}
+
+impl<K, BoxDT> Default for TypeMapBuilder<K, BoxDT>
+where
+    K: Eq + Hash,
+    BoxDT: DataTypeWrapper,
+{
+    fn default() -> Self {
+        Self::new_typed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeMapBuilder;
+
+    #[test]
+    fn build_returns_map_when_required_keys_inserted() {
+        let type_map = TypeMapBuilder::<&'static str>::new()
+            .require("one")
+            .insert("one", 1u32)
+            .insert("two", 2u64)
+            .build()
+            .unwrap();
+
+        assert_eq!(2, type_map.len());
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    }
+
+    #[test]
+    fn build_returns_error_listing_missing_keys_in_required_order() {
+        let error = TypeMapBuilder::<&'static str>::new()
+            .require("three")
+            .require("one")
+            .require("two")
+            .require("three")
+            .insert("two", 2u32)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(&["three", "one"], error.keys());
+        assert_eq!(
+            "Required keys were not inserted: `\"three\"`, `\"one\"`",
+            error.to_string()
+        );
+    }
+}
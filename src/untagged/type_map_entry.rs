@@ -0,0 +1,181 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::untagged::{type_map::RegisterFn, BoxDataTypeDowncast, FromDataType};
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::hash_map::Entry;
+
+#[cfg(feature = "ordered")]
+use indexmap::map::Entry;
+
+/// A view into a single entry of a [`TypeMap`], which may be vacant or
+/// occupied.
+///
+/// This is returned by [`TypeMap::entry_typed`], and downcasts the entry's
+/// value to the requested type.
+///
+/// [`TypeMap`]: crate::untagged::TypeMap
+/// [`TypeMap::entry_typed`]: crate::untagged::TypeMap::entry_typed
+pub struct TypeMapEntry<'m, K, BoxDT> {
+    /// Entry of the underlying map.
+    entry: Entry<'m, K, BoxDT>,
+    /// Ephemeral keys of the map, which are removed when the value is
+    /// replaced.
+    ephemeral: &'m mut HashSet<K>,
+    /// Registrations of the map, which are removed when the value is replaced.
+    registrations: &'m mut HashMap<K, RegisterFn<K>>,
+}
+
+impl<'m, K, BoxDT> TypeMapEntry<'m, K, BoxDT>
+where
+    K: Eq + Hash,
+{
+    /// Returns a new `TypeMapEntry` wrapping the underlying map's entry.
+    pub(crate) fn new(
+        entry: Entry<'m, K, BoxDT>,
+        ephemeral: &'m mut HashSet<K>,
+        registrations: &'m mut HashMap<K, RegisterFn<K>>,
+    ) -> Self {
+        Self {
+            entry,
+            ephemeral,
+            registrations,
+        }
+    }
+
+    /// Returns a reference to this entry's key.
+    pub fn key(&self) -> &K {
+        self.entry.key()
+    }
+
+    /// Calls `f` with the entry's value if the entry is occupied, and its data
+    /// type is `R`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    /// type_map.insert("count", 1u32);
+    ///
+    /// let count = type_map
+    ///     .entry_typed("count")
+    ///     .and_modify::<u32, _>(|count| *count += 1)
+    ///     .or_insert(1u32);
+    ///
+    /// assert_eq!(2, *count);
+    /// ```
+    pub fn and_modify<R, F>(self, f: F) -> Self
+    where
+        BoxDT: BoxDataTypeDowncast<R>,
+        F: FnOnce(&mut R),
+    {
+        let Self {
+            entry,
+            ephemeral,
+            registrations,
+        } = self;
+        let entry = entry.and_modify(|box_dt| {
+            if let Some(r) = BoxDataTypeDowncast::<R>::downcast_mut(box_dt) {
+                f(r);
+            }
+        });
+
+        Self {
+            entry,
+            ephemeral,
+            registrations,
+        }
+    }
+
+    /// Returns a mutable reference to the entry's value, inserting `default`
+    /// if the entry is vacant.
+    ///
+    /// If the entry is occupied, but the data type is not `R`, it is replaced
+    /// with `default`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMap;
+    ///
+    /// let mut type_map = TypeMap::<&'static str>::new();
+    ///
+    /// *type_map.entry_typed("count").or_insert(0u32) += 1;
+    /// *type_map.entry_typed("count").or_insert(0u32) += 1;
+    ///
+    /// assert_eq!(Some(2), type_map.get::<u32, _>("count").copied());
+    /// ```
+    pub fn or_insert<R>(self, default: R) -> &'m mut R
+    where
+        BoxDT: BoxDataTypeDowncast<R> + FromDataType<R>,
+    {
+        let value = match self.entry {
+            Entry::Occupied(mut entry) => {
+                if BoxDataTypeDowncast::<R>::downcast_ref(entry.get()).is_none() {
+                    self.ephemeral.remove(entry.key());
+                    self.registrations.remove(entry.key());
+                    entry.insert(<BoxDT as FromDataType<R>>::from(default));
+                }
+                entry.into_mut()
+            }
+            Entry::Vacant(entry) => {
+                self.ephemeral.remove(entry.key());
+                self.registrations.remove(entry.key());
+                entry.insert(<BoxDT as FromDataType<R>>::from(default))
+            }
+        };
+
+        BoxDataTypeDowncast::<R>::downcast_mut(value)
+            .expect("Expected value to be `R` after insertion.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::untagged::TypeMap;
+
+    #[test]
+    fn and_modify_or_insert_counts() {
+        let mut type_map = TypeMap::<&'static str>::new();
+
+        (0..3).for_each(|_| {
+            type_map
+                .entry_typed("count")
+                .and_modify::<u32, _>(|count| *count += 1)
+                .or_insert(1u32);
+        });
+
+        assert_eq!(Some(3), type_map.get::<u32, _>("count").copied());
+    }
+
+    #[test]
+    fn and_modify_skips_value_of_different_type() {
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("count", 1u64);
+
+        let mut called = false;
+        let entry = type_map
+            .entry_typed("count")
+            .and_modify::<u32, _>(|_| called = true);
+
+        assert_eq!("count", *entry.key());
+        assert!(!called);
+        assert_eq!(Some(1), type_map.get::<u64, _>("count").copied());
+    }
+
+    #[test]
+    fn or_insert_replaces_value_of_different_type() {
+        let mut type_map = TypeMap::<&'static str>::new();
+        type_map.insert("count", 1u64);
+
+        let count = type_map.entry_typed("count").or_insert(5u32);
+
+        assert_eq!(5, *count);
+        assert_eq!(None, type_map.get::<u64, _>("count"));
+    }
+}
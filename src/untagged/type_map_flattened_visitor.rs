@@ -0,0 +1,147 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::{DeserializeSeed, Error};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that can be used to deserialize nested maps into a flat
+/// [`TypeMap`], whose keys are the nested keys joined with a separator.
+///
+/// For each entry, if the flattened key is registered, the value is
+/// deserialized as the registered type, even if it is a map. Otherwise the
+/// value must be a map, whose entries are flattened under the key.
+///
+/// An error is returned if a flattened key appears more than once, such as
+/// when a document contains both `"db.host"` and `db: { host: .. }`.
+pub struct TypeMapFlattenedVisitor<'r, 's, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Separator used to join nested keys.
+    separator: &'s str,
+}
+
+impl<'r, 's, K, BoxDT> TypeMapFlattenedVisitor<'r, 's, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and separator.
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>, separator: &'s str) -> Self {
+        TypeMapFlattenedVisitor {
+            type_reg,
+            separator,
+        }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapFlattenedVisitor<'_, '_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + From<String> + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = TypeMap::new_typed();
+        NestedSeed {
+            type_reg: self.type_reg,
+            separator: self.separator,
+            prefix: None,
+            type_map: &mut type_map,
+        }
+        .visit_map(map_access)?;
+
+        Ok(type_map)
+    }
+}
+
+/// Deserializes the entries of a nested map into the [`TypeMap`].
+struct NestedSeed<'r, 's, 'm, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    separator: &'s str,
+    /// Flattened key of the map being visited, `None` for the top level map.
+    prefix: Option<String>,
+    type_map: &'m mut TypeMap<K, BoxDT>,
+}
+
+impl<'de, K, BoxDT> DeserializeSeed<'de> for NestedSeed<'_, '_, '_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + From<String> + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for NestedSeed<'_, '_, '_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + From<String> + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.prefix.as_deref() {
+            Some(prefix) => write!(
+                f,
+                "`{prefix}` to be a registered type key, or a map of nested entries"
+            ),
+            None => write!(f, "a map of arbitrary data types"),
+        }
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        while let Some(key) = map_access.next_key::<String>()? {
+            let key_flat = match self.prefix.as_deref() {
+                Some(prefix) => format!("{prefix}{}{key}", self.separator),
+                None => key,
+            };
+            let key = self.type_reg.fold_key(K::from(key_flat.clone()));
+
+            if self.type_reg.deserialize_seed_opt(&key).is_some() {
+                if self.type_map.contains_key(&key) {
+                    return Err(A::Error::custom(format!(
+                        "Key `{key:?}` appears more than once after flattening."
+                    )));
+                }
+
+                let value = map_access
+                    .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+                    .map_err(|error| self.type_reg.value_error(&key, error))?;
+                self.type_map.insert_raw(key, value);
+            } else {
+                map_access.next_value_seed(NestedSeed {
+                    type_reg: self.type_reg,
+                    separator: self.separator,
+                    prefix: Some(key_flat),
+                    type_map: &mut *self.type_map,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
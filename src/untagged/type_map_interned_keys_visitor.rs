@@ -0,0 +1,68 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::untagged::{DataTypeWrapper, KeyInterner, TypeMap, TypeReg};
+
+/// A visitor that deserializes a map of untagged values, replacing each key
+/// with its interned copy.
+pub(crate) struct TypeMapInternedKeysVisitor<'r, 'i, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Cache of keys shared across deserialized maps.
+    key_interner: &'i mut KeyInterner<K>,
+}
+
+impl<'r, 'i, K, BoxDT> TypeMapInternedKeysVisitor<'r, 'i, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and [`KeyInterner`].
+    pub(crate) fn new(
+        type_reg: &'r TypeReg<K, BoxDT>,
+        key_interner: &'i mut KeyInterner<K>,
+    ) -> Self {
+        Self {
+            type_reg,
+            key_interner,
+        }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapInternedKeysVisitor<'_, '_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = match map_access.size_hint() {
+            Some(n) => TypeMap::with_capacity_typed(n),
+            None => TypeMap::new_typed(),
+        };
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            let value = map_access
+                .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
+
+            // Only intern keys that were deserialized, so that unregistered or
+            // invalid entries do not grow the interner.
+            let key = self.key_interner.intern(key);
+            type_map.insert_raw(key, value);
+        }
+
+        Ok(type_map)
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::DeserializeSeed;
+use serde_value::{Value, ValueDeserializer};
+
+use crate::untagged::{DataTypeWrapper, TypeReg};
+
+/// Iterator over the entries of a deserialized map, which deserializes each
+/// value when it is reached.
+///
+/// This is returned by [`TypeReg::deserialize_map_iter`].
+///
+/// The whole map is buffered as [`serde_value::Value`]s when this iterator is
+/// created, as serde's `MapAccess` cannot outlive the deserializer call.
+/// Breaking out of iteration early skips deserializing the remaining values
+/// into their registered types, but not parsing them.
+///
+/// [`TypeReg::deserialize_map_iter`]: crate::untagged::TypeReg::deserialize_map_iter
+pub struct TypeMapIter<'r, K, BoxDT, E>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Registry used to deserialize each value.
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Buffered entries that have not been deserialized.
+    entries: std::vec::IntoIter<(K, Value)>,
+    /// Error encountered while buffering the map, returned on the first call
+    /// to `next`.
+    error: Option<E>,
+}
+
+impl<'r, K, BoxDT, E> TypeMapIter<'r, K, BoxDT, E>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Returns a new `TypeMapIter` over the given buffered entries.
+    pub(crate) fn new(type_reg: &'r TypeReg<K, BoxDT>, entries: Vec<(K, Value)>) -> Self {
+        Self {
+            type_reg,
+            entries: entries.into_iter(),
+            error: None,
+        }
+    }
+
+    /// Returns a new `TypeMapIter` that only returns the given error.
+    pub(crate) fn from_error(type_reg: &'r TypeReg<K, BoxDT>, error: E) -> Self {
+        Self {
+            type_reg,
+            entries: Vec::new().into_iter(),
+            error: Some(error),
+        }
+    }
+}
+
+impl<K, BoxDT, E> Iterator for TypeMapIter<'_, K, BoxDT, E>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+    E: serde::de::Error,
+{
+    type Item = Result<(K, BoxDT), E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+
+        let (key, value) = self.entries.next()?;
+        let value = self
+            .type_reg
+            .deserialize_seed::<E>(&key)
+            .and_then(|deserialize_seed| {
+                deserialize_seed
+                    .deserialize(ValueDeserializer::<E>::new(value))
+                    .map_err(|error| self.type_reg.value_error(&key, error))
+            });
+
+        Some(value.map(|value| (key, value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let error_count = usize::from(self.error.is_some());
+        let (lower, upper) = self.entries.size_hint();
+        (lower + error_count, upper.map(|upper| upper + error_count))
+    }
+}
+
+/// Visitor that buffers each map entry in document order.
+pub(crate) struct EntriesBufferVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Registry used to transform each key.
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'r, K, BoxDT> EntriesBufferVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`].
+    pub(crate) fn new(type_reg: &'r TypeReg<K, BoxDT>) -> Self {
+        Self { type_reg }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for EntriesBufferVisitor<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = Vec<(K, Value)>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(map_access.size_hint().unwrap_or(0));
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            let value = map_access.next_value::<Value>()?;
+            entries.push((key, value));
+        }
+
+        Ok(entries)
+    }
+}
@@ -6,7 +6,10 @@ use std::{
 };
 
 use crate::{
-    common::{UnknownEntries, UnknownEntriesNone, UnknownEntriesSome},
+    common::{
+        DebugMapSorted, DebugTypeMapSorted, EntryKind, UnknownEntries, UnknownEntriesNone,
+        UnknownEntriesSome,
+    },
     untagged::{BoxDataTypeDowncast, BoxDt, DataTypeWrapper, FromDataType, TypeMap},
 };
 
@@ -85,19 +88,68 @@ where
     pub fn into_inner(self) -> Map<K, Option<BoxDT>> {
         self.inner
     }
+
+    /// Returns a `Debug` view of this map with entries sorted by key.
+    ///
+    /// Without the `"ordered"` feature, the `Debug` output of a `TypeMapOpt`
+    /// is in an unspecified order, which makes it unsuitable for snapshot
+    /// tests.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMapOpt;
+    ///
+    /// let mut type_map_opt = TypeMapOpt::<&'static str>::new();
+    /// type_map_opt.insert("b", Some(2u32));
+    /// type_map_opt.insert("a", None::<u32>);
+    ///
+    /// let debug_str = format!("{:?}", type_map_opt.debug_sorted());
+    /// assert!(debug_str.find("\"a\"") < debug_str.find("\"b\""));
+    /// ```
+    pub fn debug_sorted(&self) -> impl Debug + '_
+    where
+        K: Ord + Debug,
+    {
+        DebugMapSorted::new(
+            self.inner
+                .iter()
+                .map(|(k, v)| (k, v.as_ref().map(crate::typed_value))),
+        )
+    }
 }
 
 impl<K, BoxDT, ValueT> TypeMapOpt<K, BoxDT, UnknownEntriesSome<ValueT>>
 where
     K: Eq + Hash,
     BoxDT: DataTypeWrapper,
-    ValueT: Clone + Debug + PartialEq + Eq,
+    ValueT: Clone + Debug,
 {
     /// Returns the underlying map and unknown entries.
     pub fn into_inner(self) -> (Map<K, Option<BoxDT>>, Map<K, Option<ValueT>>) {
         (self.inner, self.unknown_entries)
     }
 
+    /// Returns a `Debug` view of this map with entries sorted by key.
+    ///
+    /// Without the `"ordered"` feature, the `Debug` output of a `TypeMapOpt`
+    /// is in an unspecified order, which makes it unsuitable for snapshot
+    /// tests.
+    pub fn debug_sorted(&self) -> impl Debug + '_
+    where
+        K: Ord + Debug,
+    {
+        DebugTypeMapSorted {
+            name: "TypeMapOpt",
+            inner: DebugMapSorted::new(
+                self.inner
+                    .iter()
+                    .map(|(k, v)| (k, v.as_ref().map(crate::typed_value))),
+            ),
+            unknown_entries: DebugMapSorted::new(self.unknown_entries.iter()),
+        }
+    }
+
     /// Returns a [`TypeMap`] for non-`None` entries within this map, discarding
     /// unknown entries.
     ///
@@ -189,22 +241,148 @@ where
             .map(|value_opt| value_opt.as_ref())
     }
 
+    /// Returns whether the map contains a known entry for the key.
+    ///
+    /// This is the same as `contains_key`, but does not rely on `Deref`.
+    pub fn contains_known<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.contains_key(q)
+    }
+
+    /// Returns whether the map contains an unknown entry for the key.
+    pub fn contains_unknown<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.unknown_entries.contains_key(q)
+    }
+
+    /// Returns whether the entry for the key is known or unknown, or `None` if
+    /// there is no entry for the key.
+    ///
+    /// If the key is present as both a known and unknown entry, which may
+    /// happen when entries are inserted manually, [`EntryKind::Known`] is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{
+    ///     common::EntryKind,
+    ///     untagged::{TypeMapOpt, TypeReg},
+    /// };
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let type_map = type_reg
+    ///     .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(
+    ///         serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }"),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Some(EntryKind::Known), type_map.location_of("one"));
+    /// assert_eq!(Some(EntryKind::Unknown), type_map.location_of("two"));
+    /// assert_eq!(None, type_map.location_of("three"));
+    /// ```
+    pub fn location_of<Q>(&self, q: &Q) -> Option<EntryKind>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.inner.contains_key(q) {
+            Some(EntryKind::Known)
+        } else if self.unknown_entries.contains_key(q) {
+            Some(EntryKind::Unknown)
+        } else {
+            None
+        }
+    }
+
     /// Inserts an unknown entry into the map.
     ///
-    /// This is only used during deserialization.
+    /// Unknown entries are usually only populated during deserialization.
+    /// This is primarily useful to construct expected values in tests.
     ///
     /// If the map did not have this key present, `None` is returned.
     ///
     /// If the map did have this key present, the value is updated, and the old
     /// value is returned. The key is not updated, though; this matters for
     /// types that can be `==` without being identical.
-    pub(crate) fn insert_unknown_entry(
-        &mut self,
-        k: K,
-        v: Option<ValueT>,
-    ) -> Option<Option<ValueT>> {
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMapOpt};
+    ///
+    /// let mut type_map_opt = TypeMapOpt::<&'static str, BoxDt, _>::new_typed();
+    /// type_map_opt.insert_unknown("one", Some(serde_yaml::Value::Bool(true)));
+    /// type_map_opt.insert_unknown("two", None);
+    ///
+    /// assert_eq!(
+    ///     Some(Some(&serde_yaml::Value::Bool(true))),
+    ///     type_map_opt.get_unknown_entry("one")
+    /// );
+    /// assert_eq!(Some(None), type_map_opt.get_unknown_entry("two"));
+    /// ```
+    pub fn insert_unknown(&mut self, k: K, v: Option<ValueT>) -> Option<Option<ValueT>> {
         self.unknown_entries.insert(k, v)
     }
+
+    /// Returns whether the unknown entry for the key is `null`.
+    ///
+    /// * `None`: there is no unknown entry for the key.
+    /// * `Some(true)`: the unknown entry is present, and is `null`.
+    /// * `Some(false)`: the unknown entry is present, and has a value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMapOpt};
+    ///
+    /// let mut type_map_opt = TypeMapOpt::<&'static str, BoxDt, _>::new_typed();
+    /// type_map_opt.insert_unknown("one", Some(serde_yaml::Value::Bool(true)));
+    /// type_map_opt.insert_unknown("two", None);
+    ///
+    /// assert_eq!(Some(false), type_map_opt.unknown_is_null("one"));
+    /// assert_eq!(Some(true), type_map_opt.unknown_is_null("two"));
+    /// assert_eq!(None, type_map_opt.unknown_is_null("three"));
+    /// ```
+    pub fn unknown_is_null<Q>(&self, q: &Q) -> Option<bool>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.unknown_entries.get(q).map(Option::is_none)
+    }
+
+    /// Returns whether there is an unknown entry for the key, including
+    /// entries whose value is `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDt, TypeMapOpt};
+    ///
+    /// let mut type_map_opt = TypeMapOpt::<&'static str, BoxDt, _>::new_typed();
+    /// type_map_opt.insert_unknown("one", Some(serde_yaml::Value::Bool(true)));
+    /// type_map_opt.insert_unknown("two", None);
+    ///
+    /// assert!(type_map_opt.unknown_is_present("one"));
+    /// assert!(type_map_opt.unknown_is_present("two"));
+    /// assert!(!type_map_opt.unknown_is_present("three"));
+    /// ```
+    pub fn unknown_is_present<Q>(&self, q: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.unknown_entries.contains_key(q)
+    }
 }
 
 impl<K, BoxDT, UnknownEntriesT> TypeMapOpt<K, BoxDT, UnknownEntriesT>
@@ -361,6 +539,36 @@ where
         self.inner.get(q).map(|box_dt| box_dt.as_ref())
     }
 
+    /// Returns a clone of the boxed value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDataTypeDowncast, TypeMapOpt};
+    ///
+    /// let mut type_map = TypeMapOpt::<&'static str>::new();
+    /// type_map.insert("one", Some(1u32));
+    /// type_map.insert("two", None::<u32>);
+    ///
+    /// let boxed_one = type_map.get_raw_cloned("one").flatten().unwrap();
+    /// let one = BoxDataTypeDowncast::<u32>::downcast_ref(&boxed_one).copied();
+    /// assert_eq!(Some(1), one);
+    ///
+    /// assert!(matches!(type_map.get_raw_cloned("two"), Some(None)));
+    /// ```
+    pub fn get_raw_cloned<Q>(&self, q: &Q) -> Option<Option<BoxDT>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner
+            .get(q)
+            .map(|box_dt| box_dt.as_ref().map(DataTypeWrapper::clone))
+    }
+
     /// Returns a mutable reference to the boxed value corresponding to the key.
     ///
     /// The key may be any borrowed form of the map’s key type, but `Hash` and
@@ -429,6 +637,99 @@ where
     pub fn insert_raw(&mut self, k: K, v: Option<BoxDT>) -> Option<Option<BoxDT>> {
         self.inner.insert(k, v)
     }
+
+    /// Moves all entries from `other` into this map, where `other`'s entries
+    /// always replace existing entries.
+    ///
+    /// A `None` in `other` replaces a `Some` in this map. Use
+    /// [`merge_present`] to only replace entries with `other`'s `Some` values.
+    ///
+    /// Unknown entries are merged the same way.
+    ///
+    /// [`merge_present`]: Self::merge_present
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMapOpt;
+    ///
+    /// let mut base = TypeMapOpt::<&'static str>::new();
+    /// base.insert("one", Some(1u32));
+    /// base.insert("two", Some(2u32));
+    ///
+    /// let mut layer = TypeMapOpt::<&'static str>::new();
+    /// layer.insert("one", None::<u32>);
+    ///
+    /// base.merge_override(layer);
+    ///
+    /// assert_eq!(Some(None), base.get::<u32, _>("one"));
+    /// assert_eq!(Some(Some(&2)), base.get::<u32, _>("two"));
+    /// ```
+    pub fn merge_override(&mut self, other: Self) {
+        let TypeMapOpt {
+            inner,
+            unknown_entries,
+        } = other;
+
+        self.inner.extend(inner);
+        self.unknown_entries.extend(unknown_entries);
+    }
+
+    /// Moves all entries from `other` into this map, where only `other`'s
+    /// `Some` values replace existing entries.
+    ///
+    /// A `None` in `other` leaves an existing entry in this map intact, and is
+    /// only inserted when this map has no entry for the key. Use
+    /// [`merge_override`] for `other`'s entries to always replace existing
+    /// entries.
+    ///
+    /// Unknown entries are merged the same way.
+    ///
+    /// [`merge_override`]: Self::merge_override
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeMapOpt;
+    ///
+    /// let mut base = TypeMapOpt::<&'static str>::new();
+    /// base.insert("one", Some(1u32));
+    /// base.insert("two", Some(2u32));
+    ///
+    /// let mut layer = TypeMapOpt::<&'static str>::new();
+    /// layer.insert("one", None::<u32>);
+    /// layer.insert("two", Some(22u32));
+    /// layer.insert("three", None::<u32>);
+    ///
+    /// base.merge_present(layer);
+    ///
+    /// assert_eq!(Some(Some(&1)), base.get::<u32, _>("one"));
+    /// assert_eq!(Some(Some(&22)), base.get::<u32, _>("two"));
+    /// assert_eq!(Some(None), base.get::<u32, _>("three"));
+    /// ```
+    pub fn merge_present(&mut self, other: Self) {
+        let TypeMapOpt {
+            inner,
+            unknown_entries,
+        } = other;
+
+        inner.into_iter().for_each(|(k, v)| match v {
+            Some(v) => {
+                self.inner.insert(k, Some(v));
+            }
+            None => {
+                self.inner.entry(k).or_insert(None);
+            }
+        });
+        unknown_entries.into_iter().for_each(|(k, v)| match v {
+            Some(v) => {
+                self.unknown_entries.insert(k, Some(v));
+            }
+            None => {
+                self.unknown_entries.entry(k).or_insert(None);
+            }
+        });
+    }
 }
 
 impl<K, BoxDT, UnknownEntriesT> Clone for TypeMapOpt<K, BoxDT, UnknownEntriesT>
@@ -496,30 +797,13 @@ where
     BoxDT: DataTypeWrapper,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
-
-        self.inner.iter().for_each(|(k, resource_opt)| {
-            // At runtime, we are unable to determine if the resource is `Debug`.
-            let debug_value = resource_opt.as_ref().map(|resource| {
-                let type_name = resource.type_name();
-
-                #[cfg(not(feature = "debug"))]
-                let value = &"..";
-
-                #[cfg(feature = "debug")]
-                let value = resource.debug();
-
-                crate::TypedValue {
-                    r#type: type_name,
-                    value,
-                }
-            });
-
-            debug_map.key(&k);
-            debug_map.value(&debug_value);
-        });
-
-        debug_map.finish()
+        f.debug_map()
+            .entries(
+                self.inner
+                    .iter()
+                    .map(|(k, resource_opt)| (k, resource_opt.as_ref().map(crate::typed_value))),
+            )
+            .finish()
     }
 }
 
@@ -537,29 +821,13 @@ where
     BoxDT: DataTypeWrapper,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
-
-        self.inner.iter().for_each(|(k, resource_opt)| {
-            let debug_value = resource_opt.as_ref().map(|resource| {
-                // At runtime, we are unable to determine if the resource is `Debug`.
-                #[cfg(not(feature = "debug"))]
-                let value = &"..";
-
-                #[cfg(feature = "debug")]
-                let value = resource.debug();
-
-                let type_name = resource.type_name();
-                crate::TypedValue {
-                    r#type: type_name,
-                    value,
-                }
-            });
-
-            debug_map.key(&k);
-            debug_map.value(&debug_value);
-        });
-
-        debug_map.finish()
+        f.debug_map()
+            .entries(
+                self.inner
+                    .iter()
+                    .map(|(k, resource_opt)| (k, resource_opt.as_ref().map(crate::typed_value))),
+            )
+            .finish()
     }
 }
 
@@ -567,7 +835,7 @@ impl<K, BoxDT, ValueT> Debug for TypeMapOpt<K, BoxDT, UnknownEntriesSome<ValueT>
 where
     K: Eq + Hash + Debug,
     BoxDT: DataTypeWrapper,
-    ValueT: Clone + Debug + PartialEq + Eq,
+    ValueT: Clone + Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("TypeMapOpt")
@@ -584,10 +852,52 @@ mod tests {
     use serde::{Deserialize, Serialize};
 
     use crate::{
-        common::UnknownEntriesSome,
+        common::{EntryKind, UnknownEntriesSome},
         untagged::{BoxDataTypeDowncast, BoxDt, BoxDtDisplay, TypeMapOpt},
     };
 
+    #[test]
+    fn merge_override() {
+        let mut type_map_opt = TypeMapOpt::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map_opt.insert("one", Some(A(1)));
+        type_map_opt.insert("two", Some(A(2)));
+        type_map_opt.insert_unknown("three", Some(3));
+        let mut other = TypeMapOpt::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        other.insert("one", None::<A>);
+        other.insert("four", Some(A(4)));
+        other.insert_unknown("three", None);
+
+        type_map_opt.merge_override(other);
+
+        assert_eq!(Some(None), type_map_opt.get::<A, _>("one"));
+        assert_eq!(Some(Some(&A(2))), type_map_opt.get::<A, _>("two"));
+        assert_eq!(Some(Some(&A(4))), type_map_opt.get::<A, _>("four"));
+        assert_eq!(Some(None), type_map_opt.get_unknown_entry("three"));
+    }
+
+    #[test]
+    fn merge_present() {
+        let mut type_map_opt = TypeMapOpt::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map_opt.insert("one", Some(A(1)));
+        type_map_opt.insert("two", Some(A(2)));
+        type_map_opt.insert_unknown("three", Some(3));
+        type_map_opt.insert_unknown("five", Some(5));
+        let mut other = TypeMapOpt::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        other.insert("one", None::<A>);
+        other.insert("two", Some(A(22)));
+        other.insert("four", None::<A>);
+        other.insert_unknown("three", None);
+        other.insert_unknown("five", Some(55));
+
+        type_map_opt.merge_present(other);
+
+        assert_eq!(Some(Some(&A(1))), type_map_opt.get::<A, _>("one"));
+        assert_eq!(Some(Some(&A(22))), type_map_opt.get::<A, _>("two"));
+        assert_eq!(Some(None), type_map_opt.get::<A, _>("four"));
+        assert_eq!(Some(Some(&3)), type_map_opt.get_unknown_entry("three"));
+        assert_eq!(Some(Some(&55)), type_map_opt.get_unknown_entry("five"));
+    }
+
     #[cfg(feature = "ordered")]
     #[test]
     fn serialize() {
@@ -618,12 +928,44 @@ three: 3
         assert_eq!(Some(Some(&A(2))), type_map_opt_clone.get("one"));
     }
 
+    #[test]
+    fn unknown_is_null_and_unknown_is_present() {
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: ~ }");
+        let type_map_opt = crate::untagged::TypeReg::<String>::new()
+            .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        assert_eq!(Some(false), type_map_opt.unknown_is_null("one"));
+        assert_eq!(Some(true), type_map_opt.unknown_is_null("two"));
+        assert_eq!(None, type_map_opt.unknown_is_null("three"));
+        assert!(type_map_opt.unknown_is_present("one"));
+        assert!(type_map_opt.unknown_is_present("two"));
+        assert!(!type_map_opt.unknown_is_present("three"));
+    }
+
+    #[test]
+    fn location_of_null_entries() {
+        let mut type_reg = crate::untagged::TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: ~, two: ~ }");
+        let type_map_opt = type_reg
+            .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        assert!(type_map_opt.contains_known("one"));
+        assert!(type_map_opt.contains_unknown("two"));
+        assert_eq!(Some(EntryKind::Known), type_map_opt.location_of("one"));
+        assert_eq!(Some(EntryKind::Unknown), type_map_opt.location_of("two"));
+        assert_eq!(None, type_map_opt.location_of("three"));
+    }
+
     #[test]
     fn clone_with_unknown_entries() {
         let mut type_map_opt =
             TypeMapOpt::<_, BoxDt, UnknownEntriesSome<serde_yaml::Value>>::new_typed();
         type_map_opt.insert("one", Some(A(1)));
-        type_map_opt.insert_unknown_entry("two", Some(serde_yaml::Value::Bool(true)));
+        type_map_opt.insert_unknown("two", Some(serde_yaml::Value::Bool(true)));
 
         let mut type_map_opt_clone = type_map_opt.clone();
         type_map_opt_clone.insert("one", Some(A(2)));
@@ -770,6 +1112,59 @@ three: 3
         assert!(fmt_debug.contains("unknown_entries: {}"));
     }
 
+    #[cfg(not(feature = "debug"))]
+    #[test]
+    fn debug_sorted() {
+        let mut type_map_opt = TypeMapOpt::new();
+        type_map_opt.insert("two", None::<A>);
+        type_map_opt.insert("one", Some(A(1)));
+
+        assert_eq!(
+            "{\
+                \"one\": Some(TypedValue { \
+                    type: \"type_reg::untagged::type_map_opt::tests::A\", \
+                    value: \"..\" \
+                }), \
+                \"two\": None\
+            }",
+            format!("{:?}", type_map_opt.debug_sorted())
+        );
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn debug_sorted() {
+        let mut type_map_opt = TypeMapOpt::new();
+        type_map_opt.insert("two", None::<A>);
+        type_map_opt.insert("one", Some(A(1)));
+
+        assert_eq!(
+            "{\
+                \"one\": Some(TypedValue { \
+                    type: \"type_reg::untagged::type_map_opt::tests::A\", \
+                    value: A(1) \
+                }), \
+                \"two\": None\
+            }",
+            format!("{:?}", type_map_opt.debug_sorted())
+        );
+    }
+
+    #[test]
+    fn debug_sorted_with_unknown_entries_some() {
+        let mut type_map_opt = TypeMapOpt::<_, BoxDt, UnknownEntriesSome<u32>>::new_typed();
+        type_map_opt.insert_unknown("two", None);
+        type_map_opt.insert_unknown("one", Some(1));
+
+        assert_eq!(
+            "TypeMapOpt { \
+                inner: {}, \
+                unknown_entries: {\"one\": Some(1), \"two\": None} \
+            }",
+            format!("{:?}", type_map_opt.debug_sorted())
+        );
+    }
+
     #[test]
     fn into_inner_unknown_entries_none() {
         let mut type_map_opt = TypeMapOpt::new();
@@ -845,6 +1240,25 @@ three: 3
         assert_eq!(None, four);
     }
 
+    #[cfg(not(feature = "debug"))]
+    #[test]
+    fn get_mut_without_debug() {
+        #[derive(Clone, Serialize)]
+        struct NotDebug(u32);
+
+        let mut type_map_opt = TypeMapOpt::new();
+        type_map_opt.insert("one", Some(NotDebug(1)));
+
+        if let Some(Some(one)) = type_map_opt.get_mut::<NotDebug, _>("one") {
+            one.0 += 1;
+        }
+
+        let one = type_map_opt
+            .get::<NotDebug, _>("one")
+            .and_then(|one| one.map(|one| one.0));
+        assert_eq!(Some(2), one);
+    }
+
     #[test]
     fn get_raw() {
         let mut type_map_opt = TypeMapOpt::<&'static str>::new();
@@ -860,6 +1274,26 @@ three: 3
         assert_eq!(Some(Some(1)), one);
     }
 
+    #[test]
+    fn get_raw_cloned() {
+        let mut type_map_opt = TypeMapOpt::<&'static str>::new();
+        type_map_opt.insert("one", Some(1u32));
+        type_map_opt.insert("two", None::<u32>);
+        let boxed_one_opt = type_map_opt.get_raw_cloned("one");
+        type_map_opt.insert("one", Some(2u32));
+
+        let one = boxed_one_opt.map(|boxed_one| {
+            boxed_one
+                .as_ref()
+                .and_then(BoxDataTypeDowncast::<u32>::downcast_ref)
+                .copied()
+        });
+
+        assert_eq!(Some(Some(1)), one);
+        assert!(matches!(type_map_opt.get_raw_cloned("two"), Some(None)));
+        assert!(type_map_opt.get_raw_cloned("three").is_none());
+    }
+
     #[test]
     fn get_raw_mut() {
         let mut type_map_opt = TypeMapOpt::<&'static str>::new();
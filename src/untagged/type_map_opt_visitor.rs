@@ -50,7 +50,7 @@ impl<
     > TypeMapOptVisitor<'r, K, BoxDT, BoxFnSeed<Option<ValueT>>>
 where
     K: Clone + Eq + Hash + fmt::Debug,
-    ValueT: Clone + Eq,
+    ValueT: Clone,
 {
     /// Creates a new visitor with the given [`TypeReg`].
     pub fn new(type_reg: &'r TypeReg<K, BoxDT>, fn_opt_seed: BoxFnSeed<Option<ValueT>>) -> Self {
@@ -82,8 +82,21 @@ where
             _ => TypeMapOpt::new_typed(),
         };
 
-        while let Some(key) = map_access.next_key::<K>()? {
-            let value = map_access.next_value_seed(self.type_reg.deserialize_opt_seed(&key)?)?;
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!(
+                "deserialize_entry",
+                key = ?key,
+                type_name = self
+                    .type_reg
+                    .registered_type_name(&key)
+                    .map(|type_name| type_name.0),
+            )
+            .entered();
+
+            let value = map_access
+                .next_value_seed(self.type_reg.deserialize_opt_seed(&key)?)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
             type_map.insert_raw(key, value);
         }
 
@@ -96,7 +109,7 @@ impl<'de, K, BoxDT, ValueT> serde::de::Visitor<'de>
 where
     K: Clone + Eq + Hash + fmt::Debug + serde::Deserialize<'de> + 'de + 'static,
     BoxDT: DataTypeWrapper + 'static,
-    ValueT: Clone + fmt::Debug + Eq,
+    ValueT: Clone + fmt::Debug,
 {
     type Value = TypeMapOpt<K, BoxDT, UnknownEntriesSome<ValueT>>;
 
@@ -114,15 +127,31 @@ where
             _ => TypeMapOpt::new_typed(),
         };
 
-        while let Some(key) = map_access.next_key::<K>()? {
-            match self.type_reg.deserialize_opt_seed_opt(&key) {
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!(
+                "deserialize_entry",
+                key = ?key,
+                type_name = self
+                    .type_reg
+                    .registered_type_name(&key)
+                    .map(|type_name| type_name.0),
+            )
+            .entered();
+
+            match self.type_reg.deserialize_opt_seed_opt(&key)? {
                 Some(deserialize_opt_seed) => {
-                    let value = map_access.next_value_seed(deserialize_opt_seed)?;
+                    let value = map_access
+                        .next_value_seed(deserialize_opt_seed)
+                        .map_err(|error| self.type_reg.value_error(&key, error))?;
                     type_map.insert_raw(key, value);
                 }
                 None => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(key = ?key, "Type key not registered, storing as unknown entry.");
+
                     let value = map_access.next_value_seed(&self.fn_opt_seed)?;
-                    type_map.insert_unknown_entry(key, value);
+                    type_map.insert_unknown(key, value);
                 }
             }
         }
@@ -0,0 +1,173 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::{Error, IntoDeserializer, Unexpected, Visitor};
+use serde_json::value::RawValue;
+
+use crate::{
+    common::UnknownEntriesSome,
+    untagged::{DataTypeWrapper, TypeMap, TypeReg},
+};
+
+/// Unknown entry value, and the byte offset of its key.
+pub(crate) type PositionedValue = (serde_json::Value, usize);
+
+/// A visitor that deserializes a JSON object into a [`TypeMap`], recording
+/// the byte offset of each unknown entry's key.
+///
+/// Keys are deserialized as [`RawValue`]s borrowed from `input`, so this must
+/// only be used with a `serde_json::Deserializer` that reads from `input`.
+/// Each raw key is then deserialized as `K` through a [`KeyDeserializer`], so
+/// that non-string keys are parsed like `serde_json` parses map keys.
+pub(crate) struct TypeMapPositionedVisitor<'r, 'i, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// The JSON string being deserialized.
+    input: &'i str,
+}
+
+impl<'r, 'i, K, BoxDT> TypeMapPositionedVisitor<'r, 'i, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and JSON input.
+    pub(crate) fn new(type_reg: &'r TypeReg<K, BoxDT>, input: &'i str) -> Self {
+        Self { type_reg, input }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapPositionedVisitor<'_, '_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT, UnknownEntriesSome<PositionedValue>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = TypeMap::new_typed();
+
+        while let Some(key_raw) = map_access.next_key::<&'de RawValue>()? {
+            let key_json = key_raw.get();
+            let key = serde_json::from_str::<String>(key_json)
+                .and_then(|key_str| K::deserialize(KeyDeserializer(key_str)))
+                .map_err(A::Error::custom)?;
+            let key = self.type_reg.fold_key(key);
+
+            match self.type_reg.deserialize_seed_opt(&key) {
+                Some(deserialize_seed) => {
+                    let value = map_access
+                        .next_value_seed(deserialize_seed)
+                        .map_err(|error| self.type_reg.value_error(&key, error))?;
+                    type_map.insert_raw(key, value);
+                }
+                None => {
+                    let offset = (key_json.as_ptr() as usize) - (self.input.as_ptr() as usize);
+                    let value = map_access.next_value::<serde_json::Value>()?;
+                    type_map.insert_unknown(key, (value, offset));
+                }
+            }
+        }
+
+        Ok(type_map)
+    }
+}
+
+/// Deserializes a JSON object key from its unescaped string.
+///
+/// Like the deserializer `serde_json` uses for map keys, numbers and booleans
+/// are parsed from the string, and all other types are deserialized from the
+/// string itself.
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_parsed {
+    ($($deserialize:ident => $visit:ident,)*) => {
+        $(
+            fn $deserialize<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(serde_json::Error::invalid_value(
+                        Unexpected::Str(&self.0),
+                        &visitor,
+                    )),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for KeyDeserializer {
+    type Error = serde_json::Error;
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        IntoDeserializer::<serde_json::Error>::into_deserializer(self.0)
+            .deserialize_enum(name, variants, visitor)
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that can be used to deserialize a map of untagged values, and
+/// passes each value through a reviver function before it is inserted.
+///
+/// This behaves the same as [`TypeMapVisitor`], and additionally replaces each
+/// deserialized value with the value returned by the reviver.
+///
+/// [`TypeMapVisitor`]: crate::untagged::TypeMapVisitor
+pub struct TypeMapReviverVisitor<'r, K, BoxDT, F>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Function to post-process each deserialized value.
+    reviver: F,
+}
+
+impl<'r, K, BoxDT, F> TypeMapReviverVisitor<'r, K, BoxDT, F>
+where
+    K: Clone + Debug + Eq + Hash,
+    F: FnMut(&K, BoxDT) -> BoxDT,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and reviver.
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>, reviver: F) -> Self {
+        TypeMapReviverVisitor { type_reg, reviver }
+    }
+}
+
+impl<'de, K, BoxDT, F> serde::de::Visitor<'de> for TypeMapReviverVisitor<'_, K, BoxDT, F>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+    F: FnMut(&K, BoxDT) -> BoxDT,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(mut self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = match map_access.size_hint() {
+            Some(n) => TypeMap::with_capacity_typed(n),
+            _ => TypeMap::new_typed(),
+        };
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            let value = map_access
+                .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
+            let value = (self.reviver)(&key, value);
+
+            type_map.insert_raw(key, value);
+        }
+
+        Ok(type_map)
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::{DeserializeSeed, Error};
+use serde_value::{Value, ValueDeserializer};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that can be used to deserialize a sequence of objects into a
+/// [`TypeMap`], where each object carries its own key and type key.
+///
+/// For each object, the `id_field` value is used as the key in the returned
+/// [`TypeMap`], and the `type_field` value selects which registered type to
+/// deserialize the object as. The remaining fields of the object are
+/// deserialized as the registered type.
+///
+/// Each object is buffered before deserialization, as the `id_field` and
+/// `type_field` may appear after the other fields.
+pub struct TypeMapSeqByIdVisitor<'r, 'f, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Registry used to deserialize each object.
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Name of the field whose value is the map key.
+    id_field: &'f str,
+    /// Name of the field whose value is the type key.
+    type_field: &'f str,
+}
+
+impl<'r, 'f, K, BoxDT> TypeMapSeqByIdVisitor<'r, 'f, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and field names.
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>, id_field: &'f str, type_field: &'f str) -> Self {
+        TypeMapSeqByIdVisitor {
+            type_reg,
+            id_field,
+            type_field,
+        }
+    }
+
+    /// Removes and deserializes the given field from an object.
+    fn field_remove<'de, E>(
+        fields: &mut std::collections::BTreeMap<Value, Value>,
+        field: &str,
+    ) -> Result<K, E>
+    where
+        K: serde::Deserialize<'de>,
+        E: Error,
+    {
+        let value = fields
+            .remove(&Value::String(String::from(field)))
+            .ok_or_else(|| E::custom(format!("missing field `{field}`")))?;
+        K::deserialize(ValueDeserializer::<E>::new(value))
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapSeqByIdVisitor<'_, '_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a sequence of objects with `{}` and `{}` fields",
+            self.id_field, self.type_field
+        )
+    }
+
+    fn visit_seq<A>(self, mut seq_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut type_map = match seq_access.size_hint() {
+            Some(n) => TypeMap::with_capacity_typed(n),
+            _ => TypeMap::new_typed(),
+        };
+
+        while let Some(element) = seq_access.next_element::<Value>()? {
+            let Value::Map(mut fields) = element else {
+                return Err(A::Error::custom(format!(
+                    "expected each element to be an object with `{}` and `{}` fields",
+                    self.id_field, self.type_field
+                )));
+            };
+
+            let key = Self::field_remove::<A::Error>(&mut fields, self.id_field)?;
+            let type_key = Self::field_remove::<A::Error>(&mut fields, self.type_field)?;
+            let type_key = self.type_reg.fold_key(type_key);
+
+            let value = self
+                .type_reg
+                .deserialize_seed(&type_key)?
+                .deserialize(ValueDeserializer::<A::Error>::new(Value::Map(fields)))
+                .map_err(|error| self.type_reg.value_error(&type_key, error))?;
+
+            if type_map.contains_key(&key) {
+                return Err(A::Error::custom(format!("duplicate id `{key:?}`")));
+            }
+            type_map.insert_raw(key, value);
+        }
+
+        Ok(type_map)
+    }
+}
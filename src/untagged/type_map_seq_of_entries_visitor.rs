@@ -0,0 +1,133 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::{DeserializeSeed, Error};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that can be used to deserialize a sequence of single-entry maps
+/// into a [`TypeMap`].
+///
+/// This accepts data such as:
+///
+/// ```yaml
+/// - one: 1
+/// - two: 2
+/// ```
+///
+/// Each element must be a map with exactly one entry. The entries of all
+/// elements are folded into one [`TypeMap`], with later entries replacing
+/// earlier entries with the same key.
+pub struct TypeMapSeqOfEntriesVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'r, K, BoxDT> TypeMapSeqOfEntriesVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`].
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>) -> Self {
+        TypeMapSeqOfEntriesVisitor { type_reg }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapSeqOfEntriesVisitor<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a sequence of single-entry maps of arbitrary data types")
+    }
+
+    fn visit_seq<A>(self, mut seq_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut type_map = match seq_access.size_hint() {
+            Some(n) => TypeMap::with_capacity_typed(n),
+            _ => TypeMap::new_typed(),
+        };
+
+        let mut index = 0;
+        while let Some((key, value)) = seq_access.next_element_seed(EntrySeed {
+            type_reg: self.type_reg,
+            index,
+        })? {
+            type_map.insert_raw(key, value);
+            index += 1;
+        }
+
+        Ok(type_map)
+    }
+}
+
+/// Deserializes a single-entry map into its key and value.
+struct EntrySeed<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Index of the element in the sequence, used in error messages.
+    index: usize,
+}
+
+impl<'de, K, BoxDT> DeserializeSeed<'de> for EntrySeed<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = (K, BoxDT);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for EntrySeed<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = (K, BoxDT);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map with a single entry")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let index = self.index;
+        let Some(key) = self.type_reg.next_key(&mut map_access)? else {
+            return Err(A::Error::custom(format!(
+                "Expected sequence element {index} to be a map with a single entry, \
+                but it has no entries."
+            )));
+        };
+        let value = map_access
+            .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+            .map_err(|error| self.type_reg.value_error(&key, error))?;
+
+        if map_access.next_key::<serde::de::IgnoredAny>()?.is_some() {
+            return Err(A::Error::custom(format!(
+                "Expected sequence element {index} to be a map with a single entry, \
+                but it has multiple entries."
+            )));
+        }
+
+        Ok((key, value))
+    }
+}
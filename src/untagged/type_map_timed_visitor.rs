@@ -0,0 +1,73 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+    time::{Duration, Instant},
+};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+#[cfg(not(feature = "ordered"))]
+use std::collections::HashMap as Map;
+
+#[cfg(feature = "ordered")]
+use indexmap::IndexMap as Map;
+
+/// A visitor that can be used to deserialize a map of untagged values, and
+/// records the time taken to deserialize each value.
+///
+/// This behaves the same as [`TypeMapVisitor`], and additionally returns the
+/// duration of each value's deserialization.
+///
+/// [`TypeMapVisitor`]: crate::untagged::TypeMapVisitor
+pub struct TypeMapTimedVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'r, K, BoxDT> TypeMapTimedVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`].
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>) -> Self {
+        TypeMapTimedVisitor { type_reg }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapTimedVisitor<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = (TypeMap<K, BoxDT>, Map<K, Duration>);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let (mut type_map, mut durations) = match map_access.size_hint() {
+            Some(n) => (TypeMap::with_capacity_typed(n), Map::with_capacity(n)),
+            _ => (TypeMap::new_typed(), Map::new()),
+        };
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            let deserialize_seed = self.type_reg.deserialize_seed(&key)?;
+
+            let start = Instant::now();
+            let value = map_access
+                .next_value_seed(deserialize_seed)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
+            durations.insert(key.clone(), start.elapsed());
+
+            type_map.insert_raw(key, value);
+        }
+
+        Ok((type_map, durations))
+    }
+}
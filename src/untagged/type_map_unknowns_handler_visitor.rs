@@ -0,0 +1,91 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use serde::de::{DeserializeOwned, Error};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that deserializes a map of untagged values, passing each unknown
+/// entry to a handler instead of storing it.
+///
+/// If the handler returns an error, deserialization stops, and the error is
+/// stored in `handler_error`, as it may not be the same type as the
+/// `MapAccess` error.
+pub(crate) struct TypeMapUnknownsHandlerVisitor<'r, 'h, K, BoxDT, ValueT, F, E>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+    /// Function called with each unknown entry.
+    handler: F,
+    /// Error returned by the handler, if any.
+    handler_error: &'h mut Option<E>,
+    /// Marker for the unknown entry value type.
+    marker: PhantomData<ValueT>,
+}
+
+impl<'r, 'h, K, BoxDT, ValueT, F, E> TypeMapUnknownsHandlerVisitor<'r, 'h, K, BoxDT, ValueT, F, E>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`] and handler.
+    pub(crate) fn new(
+        type_reg: &'r TypeReg<K, BoxDT>,
+        handler: F,
+        handler_error: &'h mut Option<E>,
+    ) -> Self {
+        Self {
+            type_reg,
+            handler,
+            handler_error,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, K, BoxDT, ValueT, F, E> serde::de::Visitor<'de>
+    for TypeMapUnknownsHandlerVisitor<'_, '_, K, BoxDT, ValueT, F, E>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+    ValueT: DeserializeOwned,
+    F: FnMut(&K, &ValueT) -> Result<(), E>,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of arbitrary data types")
+    }
+
+    fn visit_map<A>(mut self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = TypeMap::new_typed();
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            match self.type_reg.deserialize_seed_opt(&key) {
+                Some(deserialize_seed) => {
+                    let value = map_access
+                        .next_value_seed(deserialize_seed)
+                        .map_err(|error| self.type_reg.value_error(&key, error))?;
+                    type_map.insert_raw(key, value);
+                }
+                None => {
+                    let value = map_access.next_value::<ValueT>()?;
+                    if let Err(error) = (self.handler)(&key, &value) {
+                        *self.handler_error = Some(error);
+                        return Err(A::Error::custom(format!(
+                            "Unknown entry `{key:?}` was rejected by the handler."
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(type_map)
+    }
+}
@@ -1,8 +1,10 @@
 use std::{
     fmt::{self, Debug},
     hash::Hash,
+    time::Instant,
 };
 
+use serde::de::Error;
 use serde_tagged::de::BoxFnSeed;
 
 use crate::{
@@ -29,6 +31,12 @@ where
     type_reg: &'r TypeReg<K, BoxDT>,
     /// Function to deserialize an arbitrary value.
     fn_seed: UnknownEntriesFn,
+    /// Maximum number of entries to preallocate space for.
+    capacity_cap: Option<usize>,
+    /// Time after which no further entries are deserialized.
+    deadline: Option<Instant>,
+    /// Map to clear and deserialize entries into, reusing its allocation.
+    type_map: Option<TypeMap<K, BoxDT>>,
 }
 
 impl<'r, K, BoxDT> TypeMapVisitor<'r, K, BoxDT, UnknownEntriesNone>
@@ -40,18 +48,83 @@ where
         TypeMapVisitor {
             type_reg,
             fn_seed: UnknownEntriesNone,
+            capacity_cap: None,
+            deadline: None,
+            type_map: None,
         }
     }
+
+    /// Deserializes entries into the given map, instead of allocating a new
+    /// one.
+    ///
+    /// The map is cleared before deserializing, so its allocation is reused.
+    pub fn with_type_map(mut self, type_map: TypeMap<K, BoxDT>) -> Self {
+        self.type_map = Some(type_map);
+        self
+    }
 }
 
 impl<'r, K, BoxDT, ValueT> TypeMapVisitor<'r, K, BoxDT, BoxFnSeed<ValueT>>
 where
     K: Clone + Debug + Eq + Hash,
-    ValueT: Clone + Debug + Eq,
+    ValueT: Clone + Debug,
 {
     /// Creates a new visitor with the given [`TypeReg`].
     pub fn new(type_reg: &'r TypeReg<K, BoxDT>, fn_seed: BoxFnSeed<ValueT>) -> Self {
-        TypeMapVisitor { type_reg, fn_seed }
+        TypeMapVisitor {
+            type_reg,
+            fn_seed,
+            capacity_cap: None,
+            deadline: None,
+            type_map: None,
+        }
+    }
+}
+
+impl<K, BoxDT, UnknownEntriesFn> TypeMapVisitor<'_, K, BoxDT, UnknownEntriesFn>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Limits the number of entries preallocated from the deserializer's
+    /// size hint.
+    ///
+    /// The size hint comes from the input, so a malicious document may claim a
+    /// huge number of entries to trigger a large allocation. The map still
+    /// grows as needed beyond the cap.
+    pub fn with_capacity_cap(mut self, capacity_cap: usize) -> Self {
+        self.capacity_cap = Some(capacity_cap);
+        self
+    }
+
+    /// Returns an error if an entry is visited after `deadline`.
+    ///
+    /// The deadline is checked between entries, so it cannot interrupt the
+    /// deserialization of a single slow value.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns an error if the deadline has passed before deserializing the
+    /// value for `key`.
+    fn check_deadline<E>(&self, key: &K) -> Result<(), E>
+    where
+        E: Error,
+    {
+        match self.deadline {
+            Some(deadline) if Instant::now() > deadline => Err(E::custom(format!(
+                "Deadline exceeded before deserializing the value for `{key:?}`."
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of entries to preallocate space for.
+    fn capacity(&self, size_hint: Option<usize>) -> Option<usize> {
+        size_hint.map(|size_hint| match self.capacity_cap {
+            Some(capacity_cap) => size_hint.min(capacity_cap),
+            None => size_hint,
+        })
     }
 }
 
@@ -66,17 +139,40 @@ where
         write!(f, "a map of arbitrary data types")
     }
 
-    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    fn visit_map<A>(mut self, mut map_access: A) -> Result<Self::Value, A::Error>
     where
         A: serde::de::MapAccess<'de>,
     {
-        let mut type_map = match map_access.size_hint() {
-            Some(n) => TypeMap::with_capacity_typed(n),
-            _ => TypeMap::new_typed(),
+        let capacity = self.capacity(map_access.size_hint());
+        let mut type_map = match (self.type_map.take(), capacity) {
+            (Some(mut type_map), capacity) => {
+                type_map.reset();
+                if let Some(n) = capacity {
+                    type_map.reserve(n);
+                }
+                type_map
+            }
+            (None, Some(n)) => TypeMap::with_capacity_typed(n),
+            (None, None) => TypeMap::new_typed(),
         };
 
-        while let Some(key) = map_access.next_key::<K>()? {
-            let value = map_access.next_value_seed(self.type_reg.deserialize_seed(&key)?)?;
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            self.check_deadline(&key)?;
+
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!(
+                "deserialize_entry",
+                key = ?key,
+                type_name = self
+                    .type_reg
+                    .registered_type_name(&key)
+                    .map(|type_name| type_name.0),
+            )
+            .entered();
+
+            let value = map_access
+                .next_value_seed(self.type_reg.deserialize_seed(&key)?)
+                .map_err(|error| self.type_reg.value_error(&key, error))?;
             type_map.insert_raw(key, value);
         }
 
@@ -89,7 +185,7 @@ impl<'de, K, BoxDT, ValueT> serde::de::Visitor<'de>
 where
     K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
     BoxDT: DataTypeWrapper + 'static,
-    ValueT: Clone + Debug + Eq,
+    ValueT: Clone + Debug,
 {
     type Value = TypeMap<K, BoxDT, UnknownEntriesSome<ValueT>>;
 
@@ -101,20 +197,38 @@ where
     where
         A: serde::de::MapAccess<'de>,
     {
-        let mut type_map = match map_access.size_hint() {
+        let mut type_map = match self.capacity(map_access.size_hint()) {
             Some(n) => TypeMap::with_capacity_typed(n),
             _ => TypeMap::new_typed(),
         };
 
-        while let Some(key) = map_access.next_key::<K>()? {
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            self.check_deadline(&key)?;
+
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!(
+                "deserialize_entry",
+                key = ?key,
+                type_name = self
+                    .type_reg
+                    .registered_type_name(&key)
+                    .map(|type_name| type_name.0),
+            )
+            .entered();
+
             match self.type_reg.deserialize_seed_opt(&key) {
                 Some(deserialize_seed) => {
-                    let value = map_access.next_value_seed(deserialize_seed)?;
+                    let value = map_access
+                        .next_value_seed(deserialize_seed)
+                        .map_err(|error| self.type_reg.value_error(&key, error))?;
                     type_map.insert_raw(key, value);
                 }
                 None => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(key = ?key, "Type key not registered, storing as unknown entry.");
+
                     let value = map_access.next_value_seed(&self.fn_seed)?;
-                    type_map.insert_unknown_entry(key, value);
+                    type_map.insert_unknown(key, value);
                 }
             }
         }
@@ -122,3 +236,51 @@ where
         Ok(type_map)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::{
+        common::UnknownEntriesNone,
+        untagged::{BoxDt, TypeMapVisitor, TypeReg},
+    };
+
+    #[test]
+    fn capacity_uses_size_hint_when_uncapped() {
+        let type_reg = TypeReg::<String>::new();
+        let visitor = TypeMapVisitor::<_, BoxDt, UnknownEntriesNone>::new(&type_reg);
+
+        assert_eq!(Some(usize::MAX), visitor.capacity(Some(usize::MAX)));
+        assert_eq!(None, visitor.capacity(None));
+    }
+
+    #[test]
+    fn capacity_is_clamped_to_capacity_cap() {
+        let type_reg = TypeReg::<String>::new();
+        let visitor =
+            TypeMapVisitor::<_, BoxDt, UnknownEntriesNone>::new(&type_reg).with_capacity_cap(16);
+
+        assert_eq!(Some(16), visitor.capacity(Some(usize::MAX)));
+        assert_eq!(Some(2), visitor.capacity(Some(2)));
+        assert_eq!(None, visitor.capacity(None));
+    }
+
+    #[test]
+    fn check_deadline_errs_after_deadline() {
+        let type_reg = TypeReg::<String>::new();
+        let key = String::from("one");
+
+        let visitor = TypeMapVisitor::<_, BoxDt, UnknownEntriesNone>::new(&type_reg);
+        assert!(visitor.check_deadline::<serde_json::Error>(&key).is_ok());
+
+        let visitor = visitor.with_deadline(Instant::now() - Duration::from_secs(1));
+        let error = visitor
+            .check_deadline::<serde_json::Error>(&key)
+            .unwrap_err();
+        assert_eq!(
+            "Deadline exceeded before deserializing the value for `\"one\"`.",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    fmt::{self, Debug},
+    hash::Hash,
+};
+
+use serde::de::{DeserializeSeed, EnumAccess, VariantAccess};
+
+use crate::untagged::{DataTypeWrapper, TypeMap, TypeReg};
+
+/// A visitor that can be used to deserialize a map of YAML tagged values.
+///
+/// Each value's YAML tag, e.g. `!Task` in `one: !Task { .. }`, is used to
+/// select which registered type to deserialize the value as, instead of the
+/// map key. The map key is still used as the key in the returned
+/// [`TypeMap`].
+///
+/// This relies on `serde_yaml` presenting tagged values as enums, where the
+/// tag is the variant name.
+pub struct TypeMapYamlTagVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'r, K, BoxDT> TypeMapYamlTagVisitor<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    /// Creates a new visitor with the given [`TypeReg`].
+    pub fn new(type_reg: &'r TypeReg<K, BoxDT>) -> Self {
+        TypeMapYamlTagVisitor { type_reg }
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for TypeMapYamlTagVisitor<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = TypeMap<K, BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of YAML tagged values")
+    }
+
+    fn visit_map<A>(self, mut map_access: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut type_map = match map_access.size_hint() {
+            Some(n) => TypeMap::with_capacity_typed(n),
+            _ => TypeMap::new_typed(),
+        };
+
+        while let Some(key) = self.type_reg.next_key(&mut map_access)? {
+            let value = map_access.next_value_seed(YamlTagSeed {
+                type_reg: self.type_reg,
+            })?;
+            type_map.insert_raw(key, value);
+        }
+
+        Ok(type_map)
+    }
+}
+
+/// Deserializes a YAML tagged value as the type registered for its tag.
+struct YamlTagSeed<'r, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash,
+{
+    type_reg: &'r TypeReg<K, BoxDT>,
+}
+
+impl<'de, K, BoxDT> DeserializeSeed<'de> for YamlTagSeed<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = BoxDT;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, K, BoxDT> serde::de::Visitor<'de> for YamlTagSeed<'_, K, BoxDT>
+where
+    K: Clone + Debug + Eq + Hash + serde::Deserialize<'de> + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Value = BoxDT;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a YAML tagged value, e.g. `!Type value`")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (tag, variant_access) = data.variant::<K>()?;
+        let tag = self.type_reg.fold_key(tag);
+
+        variant_access
+            .newtype_variant_seed(self.type_reg.deserialize_seed(&tag)?)
+            .map_err(|error| self.type_reg.value_error(&tag, error))
+    }
+}
@@ -1,20 +1,53 @@
 use std::{
+    borrow::Borrow,
     fmt::{self, Debug},
     hash::Hash,
     ops::{Deref, DerefMut},
+    sync::Arc,
+    time::Instant,
 };
 
 use serde::de::DeserializeOwned;
 use serde_tagged::de::{BoxFnSeed, SeedFactory};
 
 use crate::{
-    common::{UnknownEntriesNone, UnknownEntriesSome},
+    common::{DepthLimitDeserializer, UnknownEntriesNone, UnknownEntriesSome},
     untagged::{
-        BoxDt, DataType, DataTypeWrapper, FromDataType, TypeMap, TypeMapOpt, TypeMapOptVisitor,
+        struct_with_extras::{self, StructWithExtrasVisitor},
+        type_map_interned_keys_visitor::TypeMapInternedKeysVisitor,
+        type_map_unknowns_handler_visitor::TypeMapUnknownsHandlerVisitor,
+        BoxDt, DataType, DataTypeWrapper, FromDataType, KeyInterner, RegistrationHandle,
+        ScopedRegistration, StructWithExtras, TraitObjectReg, TypeMap, TypeMapFlattenedVisitor,
+        TypeMapOpt, TypeMapOptVisitor, TypeMapReviverVisitor, TypeMapSeqOfEntriesVisitor,
         TypeMapVisitor,
     },
+    TypeNameLit,
 };
 
+#[cfg(feature = "adaptive")]
+use crate::untagged::TypeMapAdaptiveVisitor;
+
+#[cfg(feature = "json")]
+use crate::untagged::type_map_positioned_visitor::{PositionedValue, TypeMapPositionedVisitor};
+
+#[cfg(feature = "interned")]
+use crate::untagged::ArcDt;
+
+#[cfg(feature = "iter")]
+use crate::untagged::{type_map_iter::EntriesBufferVisitor, TypeMapIter};
+
+#[cfg(feature = "id_field")]
+use crate::untagged::TypeMapSeqByIdVisitor;
+
+#[cfg(feature = "lazy")]
+use crate::untagged::{lazy_type_map::LazyTypeMapVisitor, LazyTypeMap};
+
+#[cfg(feature = "instrument")]
+use crate::untagged::TypeMapTimedVisitor;
+
+#[cfg(feature = "yaml_tag")]
+use crate::untagged::TypeMapYamlTagVisitor;
+
 #[cfg(not(feature = "ordered"))]
 use std::collections::HashMap as Map;
 
@@ -28,8 +61,45 @@ where
 {
     fn_seeds: Map<K, BoxFnSeed<BoxDT>>,
     fn_opt_seeds: Map<K, BoxFnSeed<Option<BoxDT>>>,
+    /// Type names of the types registered against each key.
+    type_names: Map<K, TypeNameLit>,
+    /// Size and alignment of the types registered against each key.
+    ///
+    /// This is absent for keys registered with a function that may
+    /// deserialize any type, such as [`TypeReg::register_boxed`].
+    type_layouts: Map<K, (usize, usize)>,
+    /// Functions to generate the JSON schema of each registered type.
+    ///
+    /// This is `None` for types registered without a schema.
+    #[cfg(feature = "schemars")]
+    schema_fns: Map<K, Option<SchemaFn>>,
+    /// Transforms keys when registering types and deserializing maps.
+    key_transform: Option<KeyTransformFn<K>>,
+    /// Registry to look up keys that are not registered in this registry.
+    parent: Option<ParentTypeReg<K, BoxDT>>,
+    /// Type to deserialize values as when their key is not registered.
+    catch_all: Option<CatchAll<BoxDT>>,
+    /// Messages for keys registered using [`TypeReg::register_deprecated`].
+    deprecations: Map<K, &'static str>,
+}
+
+/// Type registered using [`TypeReg::register_catch_all`].
+struct CatchAll<BoxDT> {
+    fn_seed: BoxFnSeed<BoxDT>,
+    fn_opt_seed: BoxFnSeed<Option<BoxDT>>,
+    type_name: TypeNameLit,
 }
 
+/// Parent registry, set by [`TypeReg::with_parent`].
+type ParentTypeReg<K, BoxDT> = Box<dyn Deref<Target = TypeReg<K, BoxDT>> + Sync>;
+
+/// Function to transform a key, set by [`TypeReg::with_key_transform`].
+type KeyTransformFn<K> = Box<dyn Fn(K) -> K + Send + Sync>;
+
+/// Function to generate the JSON schema of a registered type.
+#[cfg(feature = "schemars")]
+type SchemaFn = fn(&mut schemars::SchemaGenerator) -> schemars::Schema;
+
 impl<K> TypeReg<K, BoxDt>
 where
     K: Eq + Hash + Debug,
@@ -49,6 +119,14 @@ where
         Self {
             fn_seeds: Map::new(),
             fn_opt_seeds: Map::new(),
+            type_names: Map::new(),
+            type_layouts: Map::new(),
+            #[cfg(feature = "schemars")]
+            schema_fns: Map::new(),
+            key_transform: None,
+            parent: None,
+            catch_all: None,
+            deprecations: Map::new(),
         }
     }
 
@@ -67,6 +145,14 @@ where
         Self {
             fn_seeds: Map::with_capacity(capacity),
             fn_opt_seeds: Map::with_capacity(capacity),
+            type_names: Map::with_capacity(capacity),
+            type_layouts: Map::with_capacity(capacity),
+            #[cfg(feature = "schemars")]
+            schema_fns: Map::with_capacity(capacity),
+            key_transform: None,
+            parent: None,
+            catch_all: None,
+            deprecations: Map::new(),
         }
     }
 }
@@ -91,6 +177,14 @@ where
         Self {
             fn_seeds: Map::new(),
             fn_opt_seeds: Map::new(),
+            type_names: Map::new(),
+            type_layouts: Map::new(),
+            #[cfg(feature = "schemars")]
+            schema_fns: Map::new(),
+            key_transform: None,
+            parent: None,
+            catch_all: None,
+            deprecations: Map::new(),
         }
     }
 
@@ -109,6 +203,178 @@ where
         Self {
             fn_seeds: Map::with_capacity(capacity),
             fn_opt_seeds: Map::with_capacity(capacity),
+            type_names: Map::with_capacity(capacity),
+            type_layouts: Map::with_capacity(capacity),
+            #[cfg(feature = "schemars")]
+            schema_fns: Map::with_capacity(capacity),
+            key_transform: None,
+            parent: None,
+            catch_all: None,
+            deprecations: Map::new(),
+        }
+    }
+
+    /// Returns the number of types registered in this type registry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// assert_eq!(1, type_reg.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        debug_assert!(
+            self.fn_opt_seeds.len() <= self.fn_seeds.len(),
+            "`fn_opt_seeds` must only contain keys in `fn_seeds`."
+        );
+        self.fn_seeds.len()
+    }
+
+    /// Returns `true` if no types are registered in this type registry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let type_reg = TypeReg::<String>::new();
+    ///
+    /// assert!(type_reg.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the name of the type registered for the key.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// The name is captured from [`std::any::type_name`] when the type is
+    /// registered, so it is subject to the same caveats: it is intended for
+    /// diagnostics, and its format may change between compiler versions.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::{untagged::TypeReg, TypeNameLit};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// assert_eq!(
+    ///     Some(TypeNameLit("u32")),
+    ///     type_reg.registered_type_name("one")
+    /// );
+    /// assert_eq!(None, type_reg.registered_type_name("two"));
+    /// ```
+    pub fn registered_type_name<Q>(&self, key: &Q) -> Option<TypeNameLit>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.owner(key)?
+            .type_names
+            .get(key)
+            .map(|type_name| TypeNameLit(type_name.0))
+    }
+
+    /// Returns the size and alignment in bytes of the type registered for the
+    /// key.
+    ///
+    /// This is the size of each value's heap allocation when it is
+    /// deserialized into a [`BoxDt`], which is useful to diagnose large
+    /// allocations in a big map. `None` is returned if the key is not
+    /// registered, or was registered using [`register_boxed`], as the
+    /// concrete type is not known.
+    ///
+    /// [`register_boxed`]: Self::register_boxed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// assert_eq!(Some((4, 4)), type_reg.type_layout("one"));
+    /// assert_eq!(None, type_reg.type_layout("two"));
+    /// ```
+    pub fn type_layout<Q>(&self, key: &Q) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.owner(key)?.type_layouts.get(key).copied()
+    }
+
+    /// Returns this registry with a parent registry, which is used to look up
+    /// keys that are not registered in this registry.
+    ///
+    /// This allows a shared base registry to be extended per context, without
+    /// merging the base registry into each extension. Types registered in this
+    /// registry take precedence over types registered in the parent.
+    ///
+    /// The parent may be any `Sync` pointer to a registry, such as a
+    /// `&'static TypeReg` from [`Box::leak`]. `Arc<TypeReg>` is not accepted,
+    /// as `TypeReg` is not `Send`, so sharing it through an `Arc` would make
+    /// this registry not `Sync`.
+    ///
+    /// [`len`] and iteration only include types registered in this registry.
+    ///
+    /// [`len`]: Self::len
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg_base = TypeReg::<String>::new();
+    /// type_reg_base.register::<u32>(String::from("one"));
+    /// let type_reg_base: &'static TypeReg<String> = Box::leak(Box::new(type_reg_base));
+    ///
+    /// let mut type_reg = TypeReg::<String>::new().with_parent(type_reg_base);
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// assert_eq!(Some(2), type_map.get::<u64, _>("two").copied());
+    /// ```
+    pub fn with_parent<P>(mut self, parent: P) -> Self
+    where
+        P: Deref<Target = TypeReg<K, BoxDT>> + Sync + 'static,
+    {
+        self.parent = Some(Box::new(parent));
+        self
+    }
+
+    /// Returns the registry that the key is registered in, which is either
+    /// this registry or one of its ancestors.
+    fn owner<Q>(&self, key: &Q) -> Option<&Self>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.fn_seeds.contains_key(key) {
+            Some(self)
+        } else {
+            self.parent.as_deref()?.owner(key)
+        }
+    }
+
+    /// Returns the catch-all type of this registry, or of the nearest parent
+    /// registry that has one.
+    fn catch_all(&self) -> Option<&CatchAll<BoxDT>> {
+        match self.catch_all.as_ref() {
+            Some(catch_all) => Some(catch_all),
+            None => self.parent.as_deref()?.catch_all(),
         }
     }
 
@@ -153,6 +419,17 @@ where
     /// println!("{data_u32:?}"); // prints "1"
     /// ```
     pub fn register<R>(&mut self, key: K)
+    where
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        BoxDT: FromDataType<R>,
+    {
+        let key = self.fold_key(key);
+        self.register_folded::<R>(key);
+    }
+
+    /// Registers a type against a key that the key transform has already
+    /// been applied to.
+    fn register_folded<R>(&mut self, key: K)
     where
         R: serde::de::DeserializeOwned + DataType + 'static,
         BoxDT: FromDataType<R>,
@@ -160,114 +437,221 @@ where
         self.fn_seeds
             .insert(key.clone(), BoxFnSeed::new(Self::deserialize::<R>));
         self.fn_opt_seeds
-            .insert(key, BoxFnSeed::new(Self::deserialize_opt::<R>));
+            .insert(key.clone(), BoxFnSeed::new(Self::deserialize_opt::<R>));
+        #[cfg(feature = "schemars")]
+        self.schema_fns.insert(key.clone(), None);
+        self.type_layouts.insert(
+            key.clone(),
+            (std::mem::size_of::<R>(), std::mem::align_of::<R>()),
+        );
+        map_remove(&mut self.deprecations, &key);
+        self.type_names
+            .insert(key, TypeNameLit(std::any::type_name::<R>()));
     }
 
-    fn deserialize<R>(
-        deserializer: &mut dyn erased_serde::Deserializer<'_>,
-    ) -> Result<BoxDT, erased_serde::Error>
+    /// Registers a type in this type registry, marking the key as deprecated.
+    ///
+    /// The key still deserializes as `R`. When it is encountered by
+    /// [`deserialize_map_collecting_warnings`], `message` is added to the
+    /// returned warnings.
+    ///
+    /// [`deserialize_map_collecting_warnings`]: Self::deserialize_map_collecting_warnings
+    pub fn register_deprecated<R>(&mut self, key: K, message: &'static str)
     where
         R: serde::de::DeserializeOwned + DataType + 'static,
         BoxDT: FromDataType<R>,
     {
-        Ok(<BoxDT as FromDataType<R>>::from(R::deserialize(
-            deserializer,
-        )?))
+        let key = self.fold_key(key);
+        self.register_folded::<R>(key.clone());
+        self.deprecations.insert(key, message);
     }
 
-    fn deserialize_opt<R>(
-        deserializer: &mut dyn erased_serde::Deserializer<'_>,
-    ) -> Result<Option<BoxDT>, erased_serde::Error>
+    /// Registers a type in this type registry, without support for
+    /// deserializing it into a [`TypeMapOpt`].
+    ///
+    /// This is the same as [`register`], but only stores the function to
+    /// deserialize `R`, and not `Option<R>`, which halves the memory used per
+    /// registration. This is useful for large registries that are only used
+    /// with [`deserialize_map`] and similar methods.
+    ///
+    /// The key cannot be used with [`deserialize_map_opt`] or
+    /// [`deserialize_map_opt_with_unknowns`], which return an error when the
+    /// key is encountered.
+    ///
+    /// [`register`]: Self::register
+    /// [`deserialize_map`]: Self::deserialize_map
+    /// [`deserialize_map_opt`]: Self::deserialize_map_opt
+    /// [`deserialize_map_opt_with_unknowns`]: Self::deserialize_map_opt_with_unknowns
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeMapOpt, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register_no_opt::<u32>(String::from("one"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    /// let type_map_opt: Result<TypeMapOpt<String>, _> = type_reg.deserialize_map_opt(deserializer);
+    /// assert!(type_map_opt.is_err());
+    /// ```
+    pub fn register_no_opt<R>(&mut self, key: K)
     where
-        Option<R>: serde::de::DeserializeOwned,
-        R: DataType + 'static,
+        R: serde::de::DeserializeOwned + DataType + 'static,
         BoxDT: FromDataType<R>,
     {
-        use serde::de::Deserialize;
-        Ok(Option::<R>::deserialize(deserializer)?.map(<BoxDT as FromDataType<R>>::from))
+        let key = self.fold_key(key);
+        self.fn_seeds
+            .insert(key.clone(), BoxFnSeed::new(Self::deserialize::<R>));
+        map_remove(&mut self.fn_opt_seeds, &key);
+        #[cfg(feature = "schemars")]
+        self.schema_fns.insert(key.clone(), None);
+        self.type_layouts.insert(
+            key.clone(),
+            (std::mem::size_of::<R>(), std::mem::align_of::<R>()),
+        );
+        map_remove(&mut self.deprecations, &key);
+        self.type_names
+            .insert(key, TypeNameLit(std::any::type_name::<R>()));
     }
 
-    /// Deserializes a map of arbitrary values into a [`TypeMap`].
+    /// Registers a type to deserialize values as when their key is not
+    /// registered.
     ///
-    /// Each type must be registered in this type registry before attempting to
-    /// deserialize the type.
+    /// Keys are looked up in the following order:
+    ///
+    /// 1. Types registered against the key in this registry.
+    /// 2. Types registered against the key in the [parent] registries.
+    /// 3. The catch-all type of this registry, then of the parent registries.
+    ///
+    /// Values deserialized using the catch-all type are stored as typed
+    /// entries, so when a catch-all type is registered, maps deserialized with
+    /// unknown entries will not have any unknown entries.
+    ///
+    /// Calling this again replaces the previous catch-all type.
+    ///
+    /// [parent]: Self::with_parent
     ///
     /// # Examples
     ///
     /// ```rust
+    /// use std::collections::BTreeMap;
+    ///
     /// use type_reg::untagged::{TypeMap, TypeReg};
     ///
     /// let mut type_reg = TypeReg::<String>::new();
-    /// type_reg.register::<u32>(String::from("one"));
-    /// type_reg.register::<u64>(String::from("two"));
+    /// type_reg.register::<u32>(String::from("port"));
+    /// type_reg.register_catch_all::<BTreeMap<String, String>>();
     ///
-    /// // This may be any deserializer.
     /// let deserializer = serde_yaml::Deserializer::from_str(
     ///     "---\n\
-    ///     one: 1\n\
-    ///     two: 2\n\
+    ///     port: 8080\n\
+    ///     '*': { user: admin }\n\
     ///     ",
     /// );
-    ///
     /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
-    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
-    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
     ///
-    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// assert_eq!(Some(8080), type_map.get::<u32, _>("port").copied());
+    /// assert_eq!(
+    ///     Some("admin"),
+    ///     type_map
+    ///         .get::<BTreeMap<String, String>, _>("*")
+    ///         .and_then(|wildcard| wildcard.get("user"))
+    ///         .map(String::as_str)
+    /// );
     /// ```
-    pub fn deserialize_map<'de, D, E>(&self, deserializer: D) -> Result<TypeMap<K, BoxDT>, E>
+    pub fn register_catch_all<R>(&mut self)
     where
-        K: serde::de::Deserialize<'de> + 'de,
-        D: serde::de::Deserializer<'de, Error = E>,
-        E: serde::de::Error,
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        BoxDT: FromDataType<R>,
     {
-        let visitor = TypeMapVisitor::<K, BoxDT, UnknownEntriesNone>::new(self);
-        deserializer.deserialize_map(visitor)
+        self.catch_all = Some(CatchAll {
+            fn_seed: BoxFnSeed::new(Self::deserialize::<R>),
+            fn_opt_seed: BoxFnSeed::new(Self::deserialize_opt::<R>),
+            type_name: TypeNameLit(std::any::type_name::<R>()),
+        });
     }
 
-    /// Deserializes a map of arbitrary values into a [`TypeMapOpt`].
+    /// Registers a type in this type registry, returning a handle to remove
+    /// exactly this registration.
     ///
-    /// Each type must be registered in this type registry before attempting to
-    /// deserialize the type.
+    /// See [`unregister_handle`] for details.
+    ///
+    /// [`unregister_handle`]: Self::unregister_handle
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use type_reg::untagged::{TypeMapOpt, TypeReg};
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// let handle = type_reg.register_handle::<u32>(String::from("one"));
+    /// assert_eq!(1, type_reg.len());
+    ///
+    /// assert!(type_reg.unregister_handle(handle));
+    /// assert!(type_reg.is_empty());
+    /// ```
+    pub fn register_handle<R>(&mut self, key: K) -> RegistrationHandle<K>
+    where
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        BoxDT: FromDataType<R>,
+    {
+        let key = self.fold_key(key);
+        self.register_folded::<R>(key.clone());
+        RegistrationHandle {
+            key,
+            type_name: TypeNameLit(std::any::type_name::<R>()),
+        }
+    }
+
+    /// Registers a type in this type registry, which is unregistered when the
+    /// returned guard is dropped.
+    ///
+    /// The guard dereferences to this registry, so further types may be
+    /// registered and maps deserialized through it while it is alive.
+    ///
+    /// See [`unregister_handle`] for details.
+    ///
+    /// [`unregister_handle`]: Self::unregister_handle
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
     ///
     /// let mut type_reg = TypeReg::<String>::new();
     /// type_reg.register::<u32>(String::from("one"));
-    /// type_reg.register::<u64>(String::from("two"));
     ///
-    /// // This may be any deserializer.
-    /// let deserializer = serde_yaml::Deserializer::from_str(
-    ///     "---\n\
-    ///     one: 1\n\
-    ///     two: null\n\
-    ///     ",
-    /// );
+    /// {
+    ///     let type_reg = type_reg.register_scoped::<u64>(String::from("two"));
     ///
-    /// let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
-    /// let data_u32 = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
-    /// let data_u64 = type_map_opt.get::<u64, _>("two").map(|two| two.copied());
+    ///     let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2");
+    ///     let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///     assert_eq!(Some(2), type_map.get::<u64, _>("two").copied());
+    /// }
     ///
-    /// assert_eq!(Some(Some(1)), data_u32);
-    /// assert_eq!(Some(None), data_u64);
+    /// assert_eq!(1, type_reg.len());
     /// ```
-    pub fn deserialize_map_opt<'de, D, E>(&self, deserializer: D) -> Result<TypeMapOpt<K, BoxDT>, E>
+    pub fn register_scoped<R>(&mut self, key: K) -> ScopedRegistration<'_, K, BoxDT>
     where
-        K: serde::de::Deserialize<'de> + 'de,
-        D: serde::de::Deserializer<'de, Error = E>,
-        E: serde::de::Error,
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        BoxDT: FromDataType<R>,
     {
-        let visitor = TypeMapOptVisitor::<K, BoxDT, UnknownEntriesNone>::new(self);
-        deserializer.deserialize_map(visitor)
+        let handle = self.register_handle::<R>(key);
+        ScopedRegistration::new(self, handle)
     }
 
-    /// Deserializes an arbitrary value into a [`DataType`].
+    /// Registers a type in this type registry, along with its JSON schema.
     ///
-    /// Each type must be registered in this type registry before attempting to
-    /// deserialize the type.
+    /// This is the same as [`register`], and additionally allows the type's
+    /// schema to be included in [`json_schema_fragment`].
+    ///
+    /// [`register`]: Self::register
+    /// [`json_schema_fragment`]: Self::json_schema_fragment
     ///
     /// # Examples
     ///
@@ -275,70 +659,401 @@ where
     /// use type_reg::untagged::TypeReg;
     ///
     /// let mut type_reg = TypeReg::<String>::new();
-    /// type_reg.register::<u32>(String::from("one"));
-    ///
-    /// // This may be any deserializer.
-    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    /// type_reg.register_with_schema::<u32>(String::from("one"));
     ///
-    /// let data_u32 = type_reg.deserialize_single(deserializer).unwrap();
-    /// let data_u32 = data_u32.downcast_ref::<u32>().copied();
+    /// let schema = type_reg.json_schema_fragment();
     ///
-    /// println!("{data_u32:?}"); // prints "1"
+    /// assert_eq!(
+    ///     Some(&serde_json::json!({ "type": "integer", "format": "uint32", "minimum": 0 })),
+    ///     schema.pointer("/properties/one"),
+    /// );
     /// ```
-    pub fn deserialize_single<'de, D, E>(&self, deserializer: D) -> Result<BoxDT, E>
+    #[cfg(feature = "schemars")]
+    pub fn register_with_schema<R>(&mut self, key: K)
     where
-        K: serde::de::Deserialize<'de> + 'de,
-        D: serde::de::Deserializer<'de, Error = E>,
-        E: serde::de::Error,
+        R: serde::de::DeserializeOwned + DataType + schemars::JsonSchema + 'static,
+        BoxDT: FromDataType<R>,
     {
-        serde_tagged::de::external::deserialize(deserializer, self)
+        let key = self.fold_key(key);
+        self.register_folded::<R>(key.clone());
+        self.schema_fns.insert(
+            key,
+            Some(|schema_generator: &mut schemars::SchemaGenerator| {
+                schema_generator.subschema_for::<R>()
+            }),
+        );
     }
 
-    pub(crate) fn deserialize_seed<E>(&self, type_key: &K) -> Result<&BoxFnSeed<BoxDT>, E>
+    /// Registers a function to deserialize the value for a key.
+    ///
+    /// This is the most general way to register a type, as the function may
+    /// choose which concrete type to deserialize at runtime, such as based on
+    /// the input. A `Box<dyn Fn(..) + Send + Sync>` may be passed in as well.
+    ///
+    /// The function is also used to deserialize the key's value in
+    /// [`TypeMapOpt`]s, where a null value is deserialized as `None` without
+    /// calling the function.
+    ///
+    /// As the concrete type is not known, the registered type name is the
+    /// type name of `BoxDT`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use type_reg::untagged::{BoxDt, TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register_boxed(String::from("port"), |deserializer| {
+    ///     // Accept either a number or a string.
+    ///     #[derive(Deserialize)]
+    ///     #[serde(untagged)]
+    ///     enum Port {
+    ///         Number(u16),
+    ///         Name(String),
+    ///     }
+    ///
+    ///     match Port::deserialize(deserializer)? {
+    ///         Port::Number(port) => Ok(BoxDt::new(port)),
+    ///         Port::Name(name) => Ok(BoxDt::new(name)),
+    ///     }
+    /// });
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("port: http");
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///
+    /// assert_eq!(
+    ///     Some("http"),
+    ///     type_map.get::<String, _>("port").map(String::as_str)
+    /// );
+    /// ```
+    pub fn register_boxed<F>(&mut self, key: K, deserialize: F)
     where
-        E: serde::de::Error,
+        F: Fn(&mut dyn erased_serde::Deserializer<'_>) -> Result<BoxDT, erased_serde::Error>
+            + Send
+            + Sync
+            + 'static,
+        BoxDT: 'static,
     {
-        self.fn_seeds
-            .get(type_key)
-            .ok_or_else(|| self.unknown_type_error(type_key))
+        let key = self.fold_key(key);
+        self.register_boxed_folded(key, deserialize);
     }
 
-    pub(crate) fn deserialize_opt_seed<E>(
-        &self,
-        type_key: &K,
-    ) -> Result<&BoxFnSeed<Option<BoxDT>>, E>
+    /// Registers a function to deserialize the value for a key that the key
+    /// transform has already been applied to.
+    fn register_boxed_folded<F>(&mut self, key: K, deserialize: F)
     where
-        E: serde::de::Error,
+        F: Fn(&mut dyn erased_serde::Deserializer<'_>) -> Result<BoxDT, erased_serde::Error>
+            + Send
+            + Sync
+            + 'static,
+        BoxDT: 'static,
     {
-        self.fn_opt_seeds
-            .get(type_key)
-            .ok_or_else(|| self.unknown_type_error(type_key))
+        let deserialize = Arc::new(deserialize);
+        let deserialize_opt = Arc::clone(&deserialize);
+        self.fn_seeds.insert(
+            key.clone(),
+            BoxFnSeed::new(
+                move |deserializer: &mut dyn erased_serde::Deserializer<'_>| {
+                    deserialize(deserializer)
+                },
+            ),
+        );
+        self.fn_opt_seeds.insert(
+            key.clone(),
+            BoxFnSeed::new(
+                move |deserializer: &mut dyn erased_serde::Deserializer<'_>| {
+                    serde::Deserializer::deserialize_option(
+                        deserializer,
+                        BoxedOptVisitor(&*deserialize_opt),
+                    )
+                },
+            ),
+        );
+        #[cfg(feature = "schemars")]
+        self.schema_fns.insert(key.clone(), None);
+        map_remove(&mut self.type_layouts, &key);
+        map_remove(&mut self.deprecations, &key);
+        self.type_names
+            .insert(key, TypeNameLit(std::any::type_name::<BoxDT>()));
     }
 
-    fn unknown_type_error<E>(&self, type_key: &K) -> E
+    /// Registers a trait object type in this type registry, whose concrete
+    /// type is selected by a type tag within the value.
+    ///
+    /// The value for the key is deserialized as one of the concrete types
+    /// registered in `trait_object_reg`, and upcast to a `Box<T>`. The
+    /// `Box<T>` must implement [`DataType`], so `T` usually has
+    /// `DynClone + erased_serde::Serialize + Send + Sync` as supertraits.
+    ///
+    /// The registered type name is the type name of `Box<T>`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TraitObjectReg, TypeMap, TypeReg};
+    ///
+    /// trait Describe: dyn_clone::DynClone + erased_serde::Serialize + Send + Sync {
+    ///     fn describe(&self) -> String;
+    /// }
+    /// # #[cfg(feature = "debug")]
+    /// # impl std::fmt::Debug for dyn Describe {
+    /// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// #         f.write_str(&self.describe())
+    /// #     }
+    /// # }
+    /// dyn_clone::clone_trait_object!(Describe);
+    /// erased_serde::serialize_trait_object!(Describe);
+    ///
+    /// impl Describe for u32 {
+    ///     fn describe(&self) -> String {
+    ///         format!("{self} items")
+    ///     }
+    /// }
+    ///
+    /// impl Describe for bool {
+    ///     fn describe(&self) -> String {
+    ///         String::from(if *self { "enabled" } else { "disabled" })
+    ///     }
+    /// }
+    ///
+    /// let mut trait_object_reg = TraitObjectReg::<dyn Describe>::new();
+    /// trait_object_reg.register::<u32>(|n| Box::new(n));
+    /// trait_object_reg.register::<bool>(|b| Box::new(b));
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register_trait_object(String::from("one"), trait_object_reg);
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: { bool: true }");
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///
+    /// let one = type_map.get::<Box<dyn Describe>, _>("one").unwrap();
+    /// assert_eq!("enabled", one.describe());
+    /// ```
+    pub fn register_trait_object<T>(&mut self, key: K, trait_object_reg: TraitObjectReg<T>)
     where
-        E: serde::de::Error,
+        T: ?Sized + 'static,
+        Box<T>: DataType,
+        BoxDT: FromDataType<Box<T>> + 'static,
     {
-        use std::fmt::Write;
-        let mut message = String::with_capacity(256);
-        write!(
-            message,
-            "Type key `{type_key:?}` not registered in type registry."
-        )
-        .expect("Failed to write error message");
+        let key = self.fold_key(key);
+        self.register_boxed_folded(key.clone(), move |deserializer| {
+            trait_object_reg
+                .deserialize_single(deserializer)
+                .map(<BoxDT as FromDataType<Box<T>>>::from)
+        });
+        self.type_names
+            .insert(key, TypeNameLit(std::any::type_name::<Box<T>>()));
+    }
 
-        message.push_str("\nAvailable types are:\n\n");
-        let mut message = self
+    /// Returns a JSON schema object describing the keys accepted by this
+    /// registry, and the schema of each key's value.
+    ///
+    /// Types registered using [`register`] instead of
+    /// [`register_with_schema`] accept any value in the returned schema.
+    /// Schemas shared between types are placed under `$defs`.
+    ///
+    /// [`register`]: Self::register
+    /// [`register_with_schema`]: Self::register_with_schema
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register_with_schema::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let schema = type_reg.json_schema_fragment();
+    ///
+    /// assert_eq!(Some(&serde_json::json!("object")), schema.get("type"));
+    /// assert_eq!(
+    ///     Some(&serde_json::json!(true)),
+    ///     schema.pointer("/properties/two")
+    /// );
+    /// ```
+    #[cfg(feature = "schemars")]
+    pub fn json_schema_fragment(&self) -> serde_json::Value
+    where
+        K: fmt::Display,
+    {
+        let mut schema_generator = schemars::generate::SchemaSettings::default()
+            .for_deserialize()
+            .into_generator();
+
+        let properties = self
+            .schema_fns
+            .iter()
+            .map(|(key, schema_fn)| {
+                let schema = match schema_fn {
+                    Some(schema_fn) => schema_fn(&mut schema_generator),
+                    None => schemars::Schema::from(true),
+                };
+                (key.to_string(), schema.to_value())
+            })
+            .collect::<serde_json::Map<String, serde_json::Value>>();
+
+        let mut fragment = serde_json::Map::new();
+        fragment.insert(String::from("type"), serde_json::Value::from("object"));
+        fragment.insert(
+            String::from("properties"),
+            serde_json::Value::Object(properties),
+        );
+
+        let definitions = schema_generator.take_definitions(true);
+        if !definitions.is_empty() {
+            fragment.insert(
+                String::from("$defs"),
+                serde_json::Value::Object(definitions),
+            );
+        }
+
+        serde_json::Value::Object(fragment)
+    }
+
+    /// Moves all registrations from `other` into this registry.
+    ///
+    /// Registrations in `other` replace existing registrations with the same
+    /// key. Use [`try_merge`] to detect collisions instead.
+    ///
+    /// [`try_merge`]: Self::try_merge
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let mut type_reg_plugin = TypeReg::<String>::new();
+    /// type_reg_plugin.register::<u64>(String::from("two"));
+    ///
+    /// type_reg.merge(type_reg_plugin);
+    ///
+    /// assert_eq!(2, type_reg.len());
+    /// ```
+    pub fn merge(&mut self, other: TypeReg<K, BoxDT>) {
+        let TypeReg {
+            fn_seeds,
+            fn_opt_seeds,
+            type_names,
+            type_layouts,
+            #[cfg(feature = "schemars")]
+            schema_fns,
+            key_transform: _,
+            parent: _,
+            catch_all,
+            deprecations,
+        } = other;
+
+        // Keys registered with `register_no_opt` in `other` must not keep this
+        // registry's `Option` seed for a different type.
+        if fn_seeds.len() != fn_opt_seeds.len() {
+            fn_seeds
+                .keys()
+                .filter(|key| !fn_opt_seeds.contains_key(*key))
+                .for_each(|key| {
+                    map_remove(&mut self.fn_opt_seeds, key);
+                });
+        }
+
+        // Likewise, keys registered with `register_boxed` in `other` must not
+        // keep this registry's layout for a different type.
+        if fn_seeds.len() != type_layouts.len() {
+            fn_seeds
+                .keys()
+                .filter(|key| !type_layouts.contains_key(*key))
+                .for_each(|key| {
+                    map_remove(&mut self.type_layouts, key);
+                });
+        }
+
+        // Keys registered without a deprecation in `other` are no longer
+        // deprecated.
+        if !self.deprecations.is_empty() {
+            fn_seeds
+                .keys()
+                .filter(|key| !deprecations.contains_key(*key))
+                .for_each(|key| {
+                    map_remove(&mut self.deprecations, key);
+                });
+        }
+
+        self.fn_seeds.extend(fn_seeds);
+        self.fn_opt_seeds.extend(fn_opt_seeds);
+        self.type_names.extend(type_names);
+        self.type_layouts.extend(type_layouts);
+        #[cfg(feature = "schemars")]
+        self.schema_fns.extend(schema_fns);
+        if catch_all.is_some() {
+            self.catch_all = catch_all;
+        }
+        self.deprecations.extend(deprecations);
+    }
+
+    /// Moves all registrations from `other` into this registry, failing if
+    /// any key is registered in both.
+    ///
+    /// On failure, the colliding keys are returned, and this registry is not
+    /// modified.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let mut type_reg_plugin = TypeReg::<String>::new();
+    /// type_reg_plugin.register::<u32>(String::from("one"));
+    /// type_reg_plugin.register::<u64>(String::from("two"));
+    ///
+    /// let collisions = type_reg.try_merge(type_reg_plugin).unwrap_err();
+    ///
+    /// assert_eq!(vec![String::from("one")], collisions);
+    /// assert_eq!(1, type_reg.len());
+    /// ```
+    pub fn try_merge(&mut self, other: TypeReg<K, BoxDT>) -> Result<(), Vec<K>> {
+        let collisions = other
             .fn_seeds
             .keys()
-            .try_fold(message, |mut message, key| {
-                writeln!(message, "- {key:?}")?;
-                Result::<_, fmt::Error>::Ok(message)
-            })
-            .expect("Failed to write error message");
-        message.push('\n');
+            .filter(|key| self.fn_seeds.contains_key(*key))
+            .cloned()
+            .collect::<Vec<K>>();
 
-        serde::de::Error::custom(message)
+        if collisions.is_empty() {
+            self.merge(other);
+            Ok(())
+        } else {
+            Err(collisions)
+        }
+    }
+
+    fn deserialize<R>(
+        deserializer: &mut dyn erased_serde::Deserializer<'_>,
+    ) -> Result<BoxDT, erased_serde::Error>
+    where
+        R: serde::de::DeserializeOwned + DataType + 'static,
+        BoxDT: FromDataType<R>,
+    {
+        Ok(<BoxDT as FromDataType<R>>::from(R::deserialize(
+            deserializer,
+        )?))
+    }
+
+    fn deserialize_opt<R>(
+        deserializer: &mut dyn erased_serde::Deserializer<'_>,
+    ) -> Result<Option<BoxDT>, erased_serde::Error>
+    where
+        Option<R>: serde::de::DeserializeOwned,
+        R: DataType + 'static,
+        BoxDT: FromDataType<R>,
+    {
+        use serde::de::Deserialize;
+        Ok(Option::<R>::deserialize(deserializer)?.map(<BoxDT as FromDataType<R>>::from))
     }
 
     /// Deserializes a map of arbitrary values into a [`TypeMap`].
@@ -360,513 +1075,4142 @@ where
     ///     "---\n\
     ///     one: 1\n\
     ///     two: 2\n\
-    ///     three: 3\n\
     ///     ",
     /// );
     ///
-    /// let type_map: TypeMap<String, _, _> = type_reg
-    ///     .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
-    ///     .unwrap();
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
     /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
     /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
     ///
     /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// ```
+    pub fn deserialize_map<'de, D, E>(&self, deserializer: D) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapVisitor::<K, BoxDT, UnknownEntriesNone>::new(self);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes each document into a [`TypeMap`], returning an iterator
+    /// of the maps.
+    ///
+    /// `documents` may be any iterator of deserializers, such as
+    /// `serde_yaml::Deserializer`, which iterates over each `---` separated
+    /// document of a multi-document YAML string. Each document is
+    /// deserialized when the iterator reaches it, so iteration may continue
+    /// after a document fails to deserialize.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let documents = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     ---\n\
+    ///     two: 2\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_maps = type_reg
+    ///     .deserialize_maps(documents)
+    ///     .collect::<Result<Vec<TypeMap<String>>, serde_yaml::Error>>()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(2, type_maps.len());
+    /// assert_eq!(Some(1), type_maps[0].get::<u32, _>("one").copied());
+    /// assert_eq!(Some(2), type_maps[1].get::<u64, _>("two").copied());
+    /// ```
+    pub fn deserialize_maps<'r, 'de, I, D, E>(
+        &'r self,
+        documents: I,
+    ) -> impl Iterator<Item = Result<TypeMap<K, BoxDT>, E>> + 'r
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        I: IntoIterator<Item = D>,
+        I::IntoIter: 'r,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        documents
+            .into_iter()
+            .map(|deserializer| self.deserialize_map(deserializer))
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], passing each
+    /// value through `reviver` before it is inserted.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// The reviver is called with each entry's key and deserialized value, and
+    /// the value it returns is inserted into the map. This is useful to
+    /// post-process values generically, without registering a separate type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{BoxDataTypeDowncast, TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<String>(String::from("name"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str("name: '  padded  '");
+    ///
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_map_with_reviver(deserializer, |_key, mut value| {
+    ///         if let Some(s) = BoxDataTypeDowncast::<String>::downcast_mut(&mut value) {
+    ///             *s = s.trim().to_string();
+    ///         }
+    ///         value
+    ///     })
+    ///     .unwrap();
     ///
     /// assert_eq!(
-    ///     Some(serde_yaml::Value::Number(serde_yaml::Number::from(3u64))),
-    ///     type_map.get_unknown_entry("three").cloned(),
+    ///     Some("padded"),
+    ///     type_map.get::<String, _>("name").map(String::as_str)
     /// );
     /// ```
-    pub fn deserialize_map_with_unknowns<'de, ValueT, D, E>(
-        &'de self,
+    pub fn deserialize_map_with_reviver<'de, D, E, F>(
+        &self,
         deserializer: D,
-    ) -> Result<TypeMap<K, BoxDT, UnknownEntriesSome<ValueT>>, E>
+        reviver: F,
+    ) -> Result<TypeMap<K, BoxDT>, E>
     where
-        K: serde::de::Deserialize<'de> + 'de + 'static,
-        ValueT: Clone + Debug + Eq + DeserializeOwned + 'static,
+        K: serde::de::Deserialize<'de> + 'de,
         D: serde::de::Deserializer<'de, Error = E>,
         E: serde::de::Error,
+        F: FnMut(&K, BoxDT) -> BoxDT,
     {
-        let visitor = TypeMapVisitor::<K, BoxDT, BoxFnSeed<ValueT>>::new(
-            self,
-            BoxFnSeed::new(Self::deserialize_value::<ValueT>),
-        );
+        let visitor = TypeMapReviverVisitor::new(self, reviver);
         deserializer.deserialize_map(visitor)
     }
 
-    /// Deserializes a map of arbitrary values into a [`TypeMapOpt`].
+    /// Deserializes a sequence of single-entry maps into a [`TypeMap`].
     ///
     /// Each type must be registered in this type registry before attempting to
     /// deserialize the type.
     ///
+    /// This accepts producers that emit each entry as its own map, instead of
+    /// one map with all entries. Each element must have exactly one entry, and
+    /// later entries replace earlier entries with the same key.
+    ///
     /// # Examples
     ///
     /// ```rust
-    /// use type_reg::untagged::{TypeMapOpt, TypeReg};
+    /// use type_reg::untagged::{TypeMap, TypeReg};
     ///
     /// let mut type_reg = TypeReg::<String>::new();
     /// type_reg.register::<u32>(String::from("one"));
     /// type_reg.register::<u64>(String::from("two"));
     ///
     /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str("- one: 1\n- two: 2\n");
+    ///
+    /// let type_map: TypeMap<String> = type_reg.deserialize_seq_of_entries(deserializer).unwrap();
+    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
+    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
+    ///
+    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// ```
+    pub fn deserialize_seq_of_entries<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapSeqOfEntriesVisitor::new(self);
+        deserializer.deserialize_seq(visitor)
+    }
+
+    /// Deserializes nested maps into a flat [`TypeMap`], whose keys are the
+    /// nested keys joined with `separator`.
+    ///
+    /// Each type must be registered in this type registry against its
+    /// flattened key before attempting to deserialize the type.
+    ///
+    /// For each entry, if its flattened key is registered, the value is
+    /// deserialized as the registered type, even if the value is a map.
+    /// Otherwise the value must be a map, and its entries are flattened under
+    /// the key. An error is returned if an unregistered key has a non-map
+    /// value, or if a flattened key appears more than once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<String>(String::from("db.host"));
+    /// type_reg.register::<u16>(String::from("db.port"));
+    ///
+    /// // This may be any deserializer.
     /// let deserializer = serde_yaml::Deserializer::from_str(
     ///     "---\n\
-    ///     one: 1\n\
-    ///     two: null\n\
-    ///     three: 3\n\
+    ///     db:\n\
+    ///     \x20 host: localhost\n\
+    ///     \x20 port: 5432\n\
     ///     ",
     /// );
     ///
-    /// let type_map_opt: TypeMapOpt<String, _, _> = type_reg
-    ///     .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_map_flattened(deserializer, ".")
     ///     .unwrap();
-    /// let data_u32 = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
-    /// let data_u64 = type_map_opt.get::<u64, _>("two").map(|two| two.copied());
-    ///
-    /// assert_eq!(Some(Some(1)), data_u32);
-    /// assert_eq!(Some(None), data_u64);
     ///
-    /// assert_eq!(
-    ///     Some(Some(serde_yaml::Value::Number(serde_yaml::Number::from(
-    ///         3u64
-    ///     )))),
-    ///     type_map_opt
-    ///         .get_unknown_entry("three")
-    ///         .map(|three| three.cloned()),
-    /// );
+    /// assert_eq!(Some(5432), type_map.get::<u16, _>("db.port").copied());
     /// ```
-    pub fn deserialize_map_opt_with_unknowns<'de, ValueT, D, E>(
-        &'de self,
+    pub fn deserialize_map_flattened<'de, D, E>(
+        &self,
         deserializer: D,
-    ) -> Result<TypeMapOpt<K, BoxDT, UnknownEntriesSome<ValueT>>, E>
+        separator: &str,
+    ) -> Result<TypeMap<K, BoxDT>, E>
     where
-        K: serde::de::Deserialize<'de> + 'de + 'static,
-        ValueT: Clone + Debug + Eq + DeserializeOwned + 'static,
+        K: From<String>,
         D: serde::de::Deserializer<'de, Error = E>,
         E: serde::de::Error,
     {
-        let visitor = TypeMapOptVisitor::<K, BoxDT, BoxFnSeed<Option<ValueT>>>::new(
-            self,
-            BoxFnSeed::new(Self::deserialize_opt_value::<ValueT>),
-        );
+        let visitor = TypeMapFlattenedVisitor::new(self, separator);
         deserializer.deserialize_map(visitor)
     }
 
-    pub(crate) fn deserialize_seed_opt(&self, type_key: &K) -> Option<&BoxFnSeed<BoxDT>> {
-        self.fn_seeds.get(type_key)
-    }
+    /// Deserializes a map into a struct of fixed typed fields, plus a
+    /// [`TypeMap`] of the remaining entries.
+    ///
+    /// Entries whose keys match a field name of `Fixed` are deserialized into
+    /// `Fixed`. All other entries are deserialized using this type registry,
+    /// so their types must be registered before attempting to deserialize
+    /// them.
+    ///
+    /// `Fixed` must be a struct with named fields. Field names are taken from
+    /// `Fixed`'s `Deserialize` implementation, so `#[serde(rename = "..")]` is
+    /// respected, but `#[serde(flatten)]` fields and field aliases are not
+    /// recognized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use type_reg::untagged::{StructWithExtras, TypeReg};
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     name: config\n\
+    ///     one: 1\n\
+    ///     ",
+    /// );
+    ///
+    /// let StructWithExtras { fixed, extras } = type_reg
+    ///     .deserialize_struct_with_extras::<Config, _, _>(deserializer)
+    ///     .unwrap();
+    /// let data_u32 = extras.get::<u32, _>("one").copied().unwrap();
+    ///
+    /// println!("{}, {data_u32}", fixed.name); // prints "config, 1"
+    /// ```
+    pub fn deserialize_struct_with_extras<'de, Fixed, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<StructWithExtras<Fixed, K, BoxDT>, E>
+    where
+        Fixed: DeserializeOwned,
+        K: Borrow<str> + serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let fields = struct_with_extras::struct_fields::<Fixed>().ok_or_else(|| {
+            E::custom(format_args!(
+                "`{}` is not a struct with named fields.",
+                std::any::type_name::<Fixed>()
+            ))
+        })?;
+        let visitor = StructWithExtrasVisitor::<Fixed, K, BoxDT>::new(self, fields);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], returning an
+    /// error when values are nested deeper than `depth_limit`.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// Sequences, maps, and enums each count as one level of nesting, including
+    /// the outer map. This guards against stack overflows when registered types
+    /// recursively deserialize untrusted input.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<Vec<Vec<u32>>>(String::from("one"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: [[1]]");
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_map_with_depth_limit(deserializer, 3)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     Some(&vec![vec![1u32]]),
+    ///     type_map.get::<Vec<Vec<u32>>, _>("one")
+    /// );
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: [[1]]");
+    /// let result = type_reg.deserialize_map_with_depth_limit(deserializer, 2);
+    /// assert!(result.is_err());
+    /// ```
+    pub fn deserialize_map_with_depth_limit<'de, D, E>(
+        &self,
+        deserializer: D,
+        depth_limit: usize,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        self.deserialize_map(DepthLimitDeserializer::new(deserializer, depth_limit))
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], limiting the
+    /// number of entries preallocated from the deserializer's size hint.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// The size hint comes from the input, so a malicious document may claim a
+    /// huge number of entries to trigger a large allocation. The map still
+    /// grows as needed beyond `max_prealloc`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2");
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_map_with_capacity_cap(deserializer, 1)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(2, type_map.len());
+    /// ```
+    pub fn deserialize_map_with_capacity_cap<'de, D, E>(
+        &self,
+        deserializer: D,
+        max_prealloc: usize,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapVisitor::<K, BoxDT, UnknownEntriesNone>::new(self)
+            .with_capacity_cap(max_prealloc);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], returning an
+    /// error if `deadline` passes before all entries are deserialized.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// The deadline is checked between entries, so it cannot interrupt the
+    /// deserialization of a single slow value. Combined with
+    /// [`deserialize_map_with_capacity_cap`], this bounds the resources used to
+    /// deserialize untrusted input.
+    ///
+    /// [`deserialize_map_with_capacity_cap`]: Self::deserialize_map_with_capacity_cap
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::{Duration, Instant};
+    ///
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_map_with_deadline(deserializer, deadline)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// ```
+    pub fn deserialize_map_with_deadline<'de, D, E>(
+        &self,
+        deserializer: D,
+        deadline: Instant,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor =
+            TypeMapVisitor::<K, BoxDT, UnknownEntriesNone>::new(self).with_deadline(deadline);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], returning
+    /// the messages of deprecated keys that were present.
+    ///
+    /// Keys are marked as deprecated using [`register_deprecated`], and are
+    /// deserialized the same as other keys. Each deprecated key in the map adds
+    /// its message to the warnings, which are sorted so that their order is
+    /// stable.
+    ///
+    /// [`register_deprecated`]: Self::register_deprecated
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u16>(String::from("port"));
+    /// type_reg.register_deprecated::<u16>(
+    ///     String::from("listen_port"),
+    ///     "`listen_port` is deprecated, use `port` instead.",
+    /// );
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("listen_port: 8080");
+    /// let (type_map, warnings): (TypeMap<String>, _) = type_reg
+    ///     .deserialize_map_collecting_warnings(deserializer)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Some(8080), type_map.get::<u16, _>("listen_port").copied());
+    /// assert_eq!(
+    ///     vec![String::from(
+    ///         "`listen_port` is deprecated, use `port` instead."
+    ///     )],
+    ///     warnings
+    /// );
+    /// ```
+    pub fn deserialize_map_collecting_warnings<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<(TypeMap<K, BoxDT>, Vec<String>), E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let type_map = self.deserialize_map(deserializer)?;
+        let mut warnings = type_map
+            .keys()
+            .filter_map(|key| self.owner(key)?.deprecations.get(key))
+            .map(|message| String::from(*message))
+            .collect::<Vec<_>>();
+
+        // The map's iteration order is random without the `"ordered"` feature.
+        warnings.sort_unstable();
+
+        Ok((type_map, warnings))
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], returning an
+    /// error if the type registered for a key in the map does not match the
+    /// type name expected by `schema`.
+    ///
+    /// This detects when the registry and a separately declared schema have
+    /// drifted apart. Type names are compared with the name captured when the
+    /// type was registered, see [`registered_type_name`]. Keys that are not in
+    /// `schema` are not validated.
+    ///
+    /// The error lists every mismatched entry, one per line, sorted so that
+    /// the message is stable.
+    ///
+    /// [`registered_type_name`]: Self::registered_type_name
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("port"));
+    ///
+    /// let mut schema = HashMap::new();
+    /// schema.insert(String::from("port"), "u16");
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("port: 8080");
+    /// let error = type_reg
+    ///     .deserialize_map_validated_against(deserializer, &schema)
+    ///     .unwrap_err();
+    ///
+    /// assert!(error
+    ///     .to_string()
+    ///     .contains("Entry `\"port\"` is registered as `u32`, but the schema expects `u16`."));
+    /// ```
+    pub fn deserialize_map_validated_against<'de, D, E>(
+        &self,
+        deserializer: D,
+        schema: &std::collections::HashMap<K, &'static str>,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let type_map = self.deserialize_map(deserializer)?;
+
+        let mut mismatches = type_map
+            .keys()
+            .filter_map(|key| {
+                let expected = *schema.get(key)?;
+                let registered = self.registered_type_name(key)?;
+                (registered.0 != expected).then(|| {
+                    format!(
+                        "Entry `{key:?}` is registered as `{registered}`, but the schema \
+                        expects `{expected}`."
+                    )
+                })
+            })
+            .collect::<Vec<String>>();
+
+        if mismatches.is_empty() {
+            Ok(type_map)
+        } else {
+            mismatches.sort_unstable();
+            Err(E::custom(mismatches.join("\n")))
+        }
+    }
+
+    /// Deserializes a map of arbitrary values into `pooled`, reusing its
+    /// allocation.
+    ///
+    /// `pooled` is cleared before deserializing, then returned with the
+    /// deserialized entries. This is useful to recycle maps across many
+    /// similar documents, instead of allocating a new map for each.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let pooled = TypeMap::<String>::with_capacity(16);
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    /// let type_map = type_reg
+    ///     .deserialize_map_reuse(deserializer, pooled)
+    ///     .unwrap();
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("two: 2");
+    /// let type_map = type_reg
+    ///     .deserialize_map_reuse(deserializer, type_map)
+    ///     .unwrap();
+    /// assert_eq!(None, type_map.get::<u32, _>("one"));
+    /// assert_eq!(Some(2), type_map.get::<u64, _>("two").copied());
+    /// ```
+    pub fn deserialize_map_reuse<'de, D, E>(
+        &self,
+        deserializer: D,
+        pooled: TypeMap<K, BoxDT>,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor =
+            TypeMapVisitor::<K, BoxDT, UnknownEntriesNone>::new(self).with_type_map(pooled);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`] from a type
+    /// erased deserializer.
+    ///
+    /// This allows the input format to be chosen at runtime. Each type must be
+    /// registered in this type registry before attempting to deserialize the
+    /// type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let mut json_deserializer = serde_json::Deserializer::from_str(r#"{ "one": 1, "two": 2 }"#);
+    /// let mut deserializer: Box<dyn erased_serde::Deserializer> = Box::new(
+    ///     <dyn erased_serde::Deserializer>::erase(&mut json_deserializer),
+    /// );
+    ///
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map_erased(&mut *deserializer).unwrap();
+    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
+    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
+    ///
+    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// ```
+    pub fn deserialize_map_erased<'de>(
+        &self,
+        deserializer: &mut dyn erased_serde::Deserializer<'de>,
+    ) -> Result<TypeMap<K, BoxDT>, erased_serde::Error>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+    {
+        self.deserialize_map(deserializer)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], recording
+    /// the time taken to deserialize each value.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// The duration for each key covers deserializing the value, and not the
+    /// key.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: 2\n\
+    ///     ",
+    /// );
+    ///
+    /// let (type_map, durations) = type_reg.deserialize_map_timed(deserializer).unwrap();
+    /// let duration_one = durations.get("one").unwrap();
+    ///
+    /// println!("`one` took {duration_one:?}");
+    /// ```
+    #[cfg(feature = "instrument")]
+    #[allow(clippy::type_complexity)]
+    pub fn deserialize_map_timed<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<(TypeMap<K, BoxDT>, Map<K, std::time::Duration>), E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapTimedVisitor::<K, BoxDT>::new(self);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values, returning an iterator that
+    /// deserializes each entry when it is reached.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// The whole map is buffered as [`serde_value::Value`]s before this
+    /// returns, and entries are returned in the order they appear in the
+    /// input. Breaking out of iteration early skips deserializing the remaining
+    /// values into their registered types. If the map fails to be buffered,
+    /// the iterator returns that error and then ends.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: 2\n\
+    ///     ",
+    /// );
+    ///
+    /// for entry in type_reg.deserialize_map_iter::<_, serde_yaml::Error>(deserializer) {
+    ///     let (key, value) = entry.unwrap();
+    ///     if key == "one" {
+    ///         println!("{:?}", value.downcast_ref::<u32>()); // prints "Some(1)"
+    ///         break;
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "iter")]
+    pub fn deserialize_map_iter<'de, D, E>(&self, deserializer: D) -> TypeMapIter<'_, K, BoxDT, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = EntriesBufferVisitor::<K, BoxDT>::new(self);
+        match deserializer.deserialize_map(visitor) {
+            Ok(entries) => TypeMapIter::new(self, entries),
+            Err(error) => TypeMapIter::from_error(self, error),
+        }
+    }
+
+    /// Deserializes a map of arbitrary values into a [`LazyTypeMap`], which
+    /// defers deserializing each value until it is first accessed.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the map. Keys are checked while deserializing the map, but
+    /// values are only buffered as [`serde_value::Value`]s.
+    ///
+    /// This is faster than [`deserialize_map`] when few entries are accessed
+    /// and the registered types are expensive to deserialize. Buffering has a
+    /// cost, so when most entries are accessed, [`deserialize_map`] is faster.
+    ///
+    /// [`deserialize_map`]: Self::deserialize_map
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{LazyTypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: 2\n\
+    ///     ",
+    /// );
+    ///
+    /// let lazy_type_map: LazyTypeMap<'_, String> =
+    ///     type_reg.deserialize_map_lazy(deserializer).unwrap();
+    /// let data_u32 = lazy_type_map.get::<u32, _>("one").copied().unwrap();
+    ///
+    /// println!("{data_u32}"); // prints "1"
+    /// ```
+    #[cfg(feature = "lazy")]
+    pub fn deserialize_map_lazy<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<LazyTypeMap<'_, K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = LazyTypeMapVisitor::<K, BoxDT>::new(self);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a sequence of objects into a [`TypeMap`], where each object
+    /// carries its own key and type key.
+    ///
+    /// For each object, the `id_field` value is used as the key in the
+    /// returned [`TypeMap`], and the `type_field` value is the key of the
+    /// registered type to deserialize the remaining fields as. An error is
+    /// returned if an object is missing either field, or if two objects have
+    /// the same id.
+    ///
+    /// Each object is buffered before deserialization, so this is slower than
+    /// [`deserialize_map`].
+    ///
+    /// [`deserialize_map`]: Self::deserialize_map
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// #[derive(Clone, Debug, Deserialize, Serialize)]
+    /// struct User {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<User>(String::from("user"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     - { id: u1, type: user, name: Ada }\n\
+    ///     - { id: u2, type: user, name: Grace }\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_seq_to_map_by_id(deserializer, "id", "type")
+    ///     .unwrap();
+    /// let user = type_map.get::<User, _>("u2").unwrap();
+    ///
+    /// println!("{}", user.name); // prints "Grace"
+    /// ```
+    #[cfg(feature = "id_field")]
+    pub fn deserialize_seq_to_map_by_id<'de, D, E>(
+        &self,
+        deserializer: D,
+        id_field: &str,
+        type_field: &str,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapSeqByIdVisitor::<K, BoxDT>::new(self, id_field, type_field);
+        deserializer.deserialize_seq(visitor)
+    }
+
+    /// Deserializes a map of YAML tagged values into a [`TypeMap`], using each
+    /// value's tag to select its type.
+    ///
+    /// Each tag must be registered in this type registry as the key of the
+    /// type to deserialize. The map keys are used as the keys of the returned
+    /// [`TypeMap`], and do not need to be registered.
+    ///
+    /// Values without a YAML tag cause an error to be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("u32"));
+    /// type_reg.register::<u64>(String::from("u64"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: !u32 1\n\
+    ///     two: !u64 2\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map_by_yaml_tag(deserializer).unwrap();
+    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
+    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
+    ///
+    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// ```
+    #[cfg(feature = "yaml_tag")]
+    pub fn deserialize_map_by_yaml_tag<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapYamlTagVisitor::<K, BoxDT>::new(self);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes JSON lines, where each line is a JSON object, into a single
+    /// [`TypeMap`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// Blank lines are skipped. Entries in later lines replace entries with the
+    /// same key in earlier lines. Errors include the line number, starting
+    /// from 1.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// let jsonl = "{ \"one\": 1 }\n\
+    ///     \n\
+    ///     { \"two\": 2 }\n";
+    ///
+    /// let type_map: TypeMap<String> = type_reg.deserialize_jsonl(jsonl.as_bytes()).unwrap();
+    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
+    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
+    ///
+    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// ```
+    #[cfg(feature = "jsonl")]
+    pub fn deserialize_jsonl<R>(&self, reader: R) -> Result<TypeMap<K, BoxDT>, serde_json::Error>
+    where
+        K: DeserializeOwned,
+        R: std::io::Read,
+    {
+        use std::io::BufRead;
+
+        let mut type_map = TypeMap::new_typed();
+        for (line_index, line) in std::io::BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(serde_json::Error::io)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut deserializer = serde_json::Deserializer::from_str(&line);
+            let type_map_line = self
+                .deserialize_map(&mut deserializer)
+                .and_then(|type_map_line| deserializer.end().map(|()| type_map_line))
+                .map_err(|error| {
+                    let line_number = line_index + 1;
+                    serde::de::Error::custom(format!(
+                        "Failed to deserialize line {line_number}: {error}"
+                    ))
+                })?;
+
+            type_map.extend(type_map_line.into_inner());
+        }
+
+        Ok(type_map)
+    }
+
+    /// Deserializes the entries of a JSON object into a [`TypeMap`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let json_object = serde_json::json!({ "one": 1 })
+    ///     .as_object()
+    ///     .cloned()
+    ///     .unwrap();
+    ///
+    /// let type_map: TypeMap<String> = type_reg.from_json_object(json_object).unwrap();
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json_object(
+        &self,
+        json_object: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<TypeMap<K, BoxDT>, serde_json::Error>
+    where
+        K: DeserializeOwned,
+    {
+        self.deserialize_map(serde_json::Value::Object(json_object))
+    }
+
+    /// Deserializes a JSON object into a [`TypeMap`], storing each unknown
+    /// entry with the byte offset of its key in `json`.
+    ///
+    /// The offset is the position of the key's opening quote, which may be
+    /// used to point at the unknown key in error messages.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let json = r#"{ "one": 1, "two": 2 }"#;
+    /// let type_map = type_reg
+    ///     .deserialize_map_with_unknowns_positioned(json)
+    ///     .unwrap();
+    ///
+    /// let (value, offset) = type_map.get_unknown_entry("two").unwrap();
+    /// assert_eq!(&serde_json::json!(2), value);
+    /// assert_eq!(12, *offset);
+    /// assert!(json[*offset..].starts_with(r#""two""#));
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn deserialize_map_with_unknowns_positioned<'de>(
+        &self,
+        json: &'de str,
+    ) -> Result<TypeMap<K, BoxDT, UnknownEntriesSome<PositionedValue>>, serde_json::Error>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+    {
+        use serde::Deserializer;
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let type_map =
+            (&mut deserializer).deserialize_map(TypeMapPositionedVisitor::new(self, json))?;
+        deserializer.end()?;
+
+        Ok(type_map)
+    }
+
+    /// Deserializes a map of values that may be untagged or externally tagged
+    /// into a [`TypeMap`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// Each value is first deserialized as the untagged type registered for
+    /// its key. If that fails, and the value is a map with a single entry
+    /// whose key is the type name of the registered type, the inner value is
+    /// deserialized as that type. That is, both `one: 1` and `one: { u32: 1 }`
+    /// are accepted when `one` is registered as a `u32`.
+    ///
+    /// When a value is valid in both forms, the untagged interpretation is
+    /// used. If neither interpretation succeeds, the error from the tagged
+    /// interpretation is returned when the value has the tagged shape,
+    /// otherwise the untagged error is returned.
+    ///
+    /// Values are buffered before deserialization, so this is slower than
+    /// [`deserialize_map`].
+    ///
+    /// [`deserialize_map`]: Self::deserialize_map
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: { u64: 2 }\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map_adaptive(deserializer).unwrap();
+    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
+    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
+    ///
+    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    /// ```
+    #[cfg(feature = "adaptive")]
+    pub fn deserialize_map_adaptive<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapAdaptiveVisitor::<K, BoxDT>::new(self);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMapOpt`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMapOpt, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: null\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
+    /// let data_u32 = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
+    /// let data_u64 = type_map_opt.get::<u64, _>("two").map(|two| two.copied());
+    ///
+    /// assert_eq!(Some(Some(1)), data_u32);
+    /// assert_eq!(Some(None), data_u64);
+    /// ```
+    pub fn deserialize_map_opt<'de, D, E>(&self, deserializer: D) -> Result<TypeMapOpt<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapOptVisitor::<K, BoxDT, UnknownEntriesNone>::new(self);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes an arbitrary value into a [`DataType`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+    ///
+    /// let data_u32 = type_reg.deserialize_single(deserializer).unwrap();
+    /// let data_u32 = data_u32.downcast_ref::<u32>().copied();
+    ///
+    /// println!("{data_u32:?}"); // prints "1"
+    /// ```
+    pub fn deserialize_single<'de, D, E>(&self, deserializer: D) -> Result<BoxDT, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        serde_tagged::de::external::deserialize(deserializer, self)
+    }
+
+    /// Returns the key with this registry's key transform applied.
+    pub(crate) fn fold_key(&self, key: K) -> K {
+        match self.key_transform.as_ref() {
+            Some(key_transform) => key_transform(key),
+            None => key,
+        }
+    }
+
+    /// Deserializes the next map key, with this registry's key transform
+    /// applied.
+    pub(crate) fn next_key<'de, A>(&self, map_access: &mut A) -> Result<Option<K>, A::Error>
+    where
+        K: serde::de::Deserialize<'de>,
+        A: serde::de::MapAccess<'de>,
+    {
+        Ok(map_access.next_key::<K>()?.map(|key| self.fold_key(key)))
+    }
+
+    pub(crate) fn deserialize_seed<E>(&self, type_key: &K) -> Result<&BoxFnSeed<BoxDT>, E>
+    where
+        E: serde::de::Error,
+    {
+        self.deserialize_seed_opt(type_key)
+            .ok_or_else(|| self.unknown_type_error(type_key))
+    }
+
+    /// Deserializes a buffered value as the type registered for `type_key`,
+    /// falling back to the externally tagged form of the value.
+    #[cfg(feature = "adaptive")]
+    pub(crate) fn deserialize_adaptive<E>(
+        &self,
+        type_key: &K,
+        value: serde_value::Value,
+    ) -> Result<BoxDT, E>
+    where
+        E: serde::de::Error,
+    {
+        use serde::de::DeserializeSeed;
+        use serde_value::{Value, ValueDeserializer};
+
+        let deserialize_seed = self.deserialize_seed::<E>(type_key)?;
+        let untagged_error =
+            match deserialize_seed.deserialize(ValueDeserializer::<E>::new(value.clone())) {
+                Ok(box_dt) => return Ok(box_dt),
+                Err(error) => error,
+            };
+
+        let type_name = self
+            .registered_type_name(type_key)
+            .map(|type_name| type_name.0);
+        match (value, type_name) {
+            (Value::Map(mut map), Some(type_name)) if map.len() == 1 => {
+                match map.remove(&Value::String(String::from(type_name))) {
+                    Some(inner) => deserialize_seed.deserialize(ValueDeserializer::<E>::new(inner)),
+                    None => Err(untagged_error),
+                }
+            }
+            _ => Err(untagged_error),
+        }
+    }
+
+    pub(crate) fn deserialize_opt_seed<E>(
+        &self,
+        type_key: &K,
+    ) -> Result<&BoxFnSeed<Option<BoxDT>>, E>
+    where
+        E: serde::de::Error,
+    {
+        match self.owner(type_key) {
+            Some(owner) => owner
+                .fn_opt_seeds
+                .get(type_key)
+                .ok_or_else(|| owner.no_opt_error(type_key)),
+            None => self
+                .catch_all()
+                .map(|catch_all| &catch_all.fn_opt_seed)
+                .ok_or_else(|| self.unknown_type_error(type_key)),
+        }
+    }
+
+    /// Returns an error for a key registered with [`register_no_opt`] that is
+    /// deserialized into a [`TypeMapOpt`].
+    ///
+    /// [`register_no_opt`]: Self::register_no_opt
+    fn no_opt_error<E>(&self, type_key: &K) -> E
+    where
+        E: serde::de::Error,
+    {
+        let type_name = self
+            .type_names
+            .get(type_key)
+            .map(|type_name| type_name.0)
+            .unwrap_or("?");
+        E::custom(format!(
+            "Type key `{type_key:?}` was registered as `{type_name}` using `register_no_opt`, \
+            so it cannot be deserialized into a `TypeMapOpt`. \
+            Register it using `register` instead."
+        ))
+    }
+
+    fn unknown_type_error<E>(&self, type_key: &K) -> E
+    where
+        E: serde::de::Error,
+    {
+        use std::fmt::Write;
+        let mut message = String::with_capacity(256);
+        write!(
+            message,
+            "Type key `{type_key:?}` not registered in type registry."
+        )
+        .expect("Failed to write error message");
+
+        message.push_str("\nAvailable types are:\n\n");
+        let mut message = std::iter::successors(Some(self), |type_reg| {
+            type_reg.parent.as_deref().map(Deref::deref)
+        })
+        .flat_map(|type_reg| type_reg.fn_seeds.keys())
+        .try_fold(message, |mut message, key| {
+            writeln!(message, "- {key:?}")?;
+            Result::<_, fmt::Error>::Ok(message)
+        })
+        .expect("Failed to write error message");
+        message.push('\n');
+
+        serde::de::Error::custom(message)
+    }
+
+    /// Returns an error that adds the key and registered type name to an error
+    /// from deserializing a value.
+    ///
+    /// When the value was `null`, the message also suggests deserializing into
+    /// a [`TypeMapOpt`] instead. See [`is_null_error`] for how `null` is
+    /// detected.
+    ///
+    /// The original error has already passed through `erased_serde`, which
+    /// keeps only its message, so rewrapping it loses no further structure.
+    /// The original message is kept at the end, so that formats which read the
+    /// position back from the message, such as `serde_json`, still report the
+    /// line and column of the value.
+    pub(crate) fn value_error<E>(&self, type_key: &K, error: E) -> E
+    where
+        E: serde::de::Error,
+    {
+        let error = error.to_string();
+        let type_name = self
+            .registered_type_name(type_key)
+            .or_else(|| {
+                self.catch_all()
+                    .map(|catch_all| TypeNameLit(catch_all.type_name.0))
+            })
+            .map(|type_name| type_name.0)
+            .unwrap_or("<unknown>");
+
+        let message = if is_null_error(&error) {
+            format!(
+                "Entry `{type_key:?}` was null, but type `{type_name}` is not optional; \
+                use `TypeReg::deserialize_map_opt` if null values are expected.\n\
+                {error}"
+            )
+        } else {
+            format!("Failed to deserialize entry `{type_key:?}` as `{type_name}`: {error}")
+        };
+
+        serde::de::Error::custom(message)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// Entries whose key is not registered are stored as `ValueT`, which is
+    /// usually the format's generic value type, such as `serde_yaml::Value`.
+    /// For lossless passthrough of JSON, `Box<serde_json::value::RawValue>`
+    /// may be used to preserve the source text of each unknown entry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: 2\n\
+    ///     three: 3\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map: TypeMap<String, _, _> = type_reg
+    ///     .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+    ///     .unwrap();
+    /// let data_u32 = type_map.get::<u32, _>("one").copied().unwrap();
+    /// let data_u64 = type_map.get::<u64, _>("two").copied().unwrap();
+    ///
+    /// println!("{data_u32}, {data_u64}"); // prints "1, 2"
+    ///
+    /// assert_eq!(
+    ///     Some(serde_yaml::Value::Number(serde_yaml::Number::from(3u64))),
+    ///     type_map.get_unknown_entry("three").cloned(),
+    /// );
+    /// ```
+    pub fn deserialize_map_with_unknowns<'de, ValueT, D, E>(
+        &'de self,
+        deserializer: D,
+    ) -> Result<TypeMap<K, BoxDT, UnknownEntriesSome<ValueT>>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de + 'static,
+        ValueT: Clone + Debug + DeserializeOwned + 'static,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapVisitor::<K, BoxDT, BoxFnSeed<ValueT>>::new(
+            self,
+            BoxFnSeed::new(Self::deserialize_value::<ValueT>),
+        );
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], replacing
+    /// each key with its copy from `key_interner`.
+    ///
+    /// When many maps share the same keys, this lets equal keys share storage
+    /// across maps. Storage is only shared when cloning `K` shares storage,
+    /// such as for `Arc<str>`, which requires `serde`'s `"rc"` feature to be
+    /// deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::Arc;
+    ///
+    /// use type_reg::untagged::{KeyInterner, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<Arc<str>>::new();
+    /// type_reg.register::<u32>(Arc::from("one"));
+    ///
+    /// let mut key_interner = KeyInterner::new();
+    /// let type_maps = ["one: 1", "one: 2"]
+    ///     .into_iter()
+    ///     .map(|document| {
+    ///         let deserializer = serde_yaml::Deserializer::from_str(document);
+    ///         type_reg
+    ///             .deserialize_map_interned_keys(deserializer, &mut key_interner)
+    ///             .unwrap()
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let key_0 = type_maps[0].keys().next().unwrap();
+    /// let key_1 = type_maps[1].keys().next().unwrap();
+    /// assert!(Arc::ptr_eq(key_0, key_1));
+    /// ```
+    pub fn deserialize_map_interned_keys<'de, D, E>(
+        &self,
+        deserializer: D,
+        key_interner: &mut KeyInterner<K>,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapInternedKeysVisitor::new(self, key_interner);
+        deserializer.deserialize_map(visitor)
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], calling
+    /// `handler` with each unknown entry instead of storing it.
+    ///
+    /// Entries whose key is not registered are deserialized as `ValueT`, and
+    /// passed to `handler`, which may log or record them. If `handler` returns
+    /// an error, deserialization stops, and that error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde::de::Error;
+    /// use type_reg::untagged::{TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// let mut unknown_keys = Vec::new();
+    /// let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+    /// let type_map: TypeMap<String> = type_reg
+    ///     .deserialize_map_handling_unknowns::<'_, serde_yaml::Value, _, _, _>(
+    ///         deserializer,
+    ///         |key, _value| {
+    ///             unknown_keys.push(key.clone());
+    ///             Ok(())
+    ///         },
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+    /// assert_eq!(vec![String::from("two")], unknown_keys);
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+    /// let error = type_reg
+    ///     .deserialize_map_handling_unknowns::<'_, serde_yaml::Value, _, _, _>(
+    ///         deserializer,
+    ///         |key, _value| Err(serde_yaml::Error::custom(format!("`{key}` is not allowed"))),
+    ///     )
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!("`two` is not allowed", error.to_string());
+    /// ```
+    pub fn deserialize_map_handling_unknowns<'de, ValueT, D, E, F>(
+        &'de self,
+        deserializer: D,
+        handler: F,
+    ) -> Result<TypeMap<K, BoxDT>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de + 'static,
+        ValueT: DeserializeOwned,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+        F: FnMut(&K, &ValueT) -> Result<(), E>,
+    {
+        let mut handler_error = None;
+        let visitor = TypeMapUnknownsHandlerVisitor::<K, BoxDT, ValueT, F, E>::new(
+            self,
+            handler,
+            &mut handler_error,
+        );
+        let result = deserializer.deserialize_map(visitor);
+
+        match handler_error {
+            Some(error) => Err(error),
+            None => result,
+        }
+    }
+
+    /// Deserializes a map of arbitrary values into a [`TypeMapOpt`].
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{TypeMapOpt, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u64>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: null\n\
+    ///     three: 3\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map_opt: TypeMapOpt<String, _, _> = type_reg
+    ///     .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+    ///     .unwrap();
+    /// let data_u32 = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
+    /// let data_u64 = type_map_opt.get::<u64, _>("two").map(|two| two.copied());
+    ///
+    /// assert_eq!(Some(Some(1)), data_u32);
+    /// assert_eq!(Some(None), data_u64);
+    ///
+    /// assert_eq!(
+    ///     Some(Some(serde_yaml::Value::Number(serde_yaml::Number::from(
+    ///         3u64
+    ///     )))),
+    ///     type_map_opt
+    ///         .get_unknown_entry("three")
+    ///         .map(|three| three.cloned()),
+    /// );
+    /// ```
+    pub fn deserialize_map_opt_with_unknowns<'de, ValueT, D, E>(
+        &'de self,
+        deserializer: D,
+    ) -> Result<TypeMapOpt<K, BoxDT, UnknownEntriesSome<ValueT>>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de + 'static,
+        ValueT: Clone + Debug + DeserializeOwned + 'static,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let visitor = TypeMapOptVisitor::<K, BoxDT, BoxFnSeed<Option<ValueT>>>::new(
+            self,
+            BoxFnSeed::new(Self::deserialize_opt_value::<ValueT>),
+        );
+        deserializer.deserialize_map(visitor)
+    }
+
+    pub(crate) fn deserialize_seed_opt(&self, type_key: &K) -> Option<&BoxFnSeed<BoxDT>> {
+        match self.owner(type_key) {
+            Some(owner) => owner.fn_seeds.get(type_key),
+            None => self.catch_all().map(|catch_all| &catch_all.fn_seed),
+        }
+    }
+
+    /// Returns the `Option` seed for the key, or `None` if the key is not
+    /// registered.
+    ///
+    /// Returns an error if the key was registered using
+    /// [`register_no_opt`](Self::register_no_opt).
+    pub(crate) fn deserialize_opt_seed_opt<E>(
+        &self,
+        type_key: &K,
+    ) -> Result<Option<&BoxFnSeed<Option<BoxDT>>>, E>
+    where
+        E: serde::de::Error,
+    {
+        match self.owner(type_key) {
+            Some(owner) => match owner.fn_opt_seeds.get(type_key) {
+                Some(deserialize_opt_seed) => Ok(Some(deserialize_opt_seed)),
+                None => Err(owner.no_opt_error(type_key)),
+            },
+            None => Ok(self.catch_all().map(|catch_all| &catch_all.fn_opt_seed)),
+        }
+    }
+}
+
+#[cfg(feature = "interned")]
+impl<K> TypeReg<K, ArcDt>
+where
+    K: Clone + Debug + Eq + Hash + 'static,
+{
+    /// Deserializes a map of arbitrary values into a [`TypeMap`], sharing
+    /// values that are equal.
+    ///
+    /// Each type must be registered in this type registry before attempting to
+    /// deserialize the type.
+    ///
+    /// Values are considered equal when they have the same type and the same
+    /// serialized form. Equal values share a single allocation, which reduces
+    /// memory use when many entries hold the same value.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{ArcDt, TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String, ArcDt>::new_typed();
+    /// type_reg.register::<u32>(String::from("one"));
+    /// type_reg.register::<u32>(String::from("two"));
+    ///
+    /// // This may be any deserializer.
+    /// let deserializer = serde_yaml::Deserializer::from_str(
+    ///     "---\n\
+    ///     one: 1\n\
+    ///     two: 1\n\
+    ///     ",
+    /// );
+    ///
+    /// let type_map: TypeMap<String, ArcDt> = type_reg.deserialize_map_interned(deserializer).unwrap();
+    /// let one = type_map.get_raw("one").unwrap();
+    /// let two = type_map.get_raw("two").unwrap();
+    ///
+    /// assert!(ArcDt::ptr_eq(one, two));
+    /// ```
+    pub fn deserialize_map_interned<'de, D, E>(
+        &self,
+        deserializer: D,
+    ) -> Result<TypeMap<K, ArcDt>, E>
+    where
+        K: serde::de::Deserialize<'de> + 'de,
+        D: serde::de::Deserializer<'de, Error = E>,
+        E: serde::de::Error,
+    {
+        let mut type_map = self.deserialize_map(deserializer)?;
+
+        let mut interned = std::collections::HashMap::<_, ArcDt>::new();
+        type_map.values_mut().for_each(|arc_dt| {
+            // Values that cannot be represented as a `serde_value::Value` are
+            // left as is.
+            if let Ok(value) = serde_value::to_value(arc_dt.inner()) {
+                let type_id = arc_dt.inner().type_id_inner();
+                let arc_dt_interned = interned
+                    .entry((type_id, value))
+                    .or_insert_with(|| Clone::clone(arc_dt));
+                *arc_dt = Clone::clone(arc_dt_interned);
+            }
+        });
+
+        Ok(type_map)
+    }
+}
+
+impl<K, BoxDT> TypeReg<K, BoxDT>
+where
+    K: Borrow<str> + From<String> + Clone + Debug + Eq + Hash + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    /// Returns this registry with a transform applied to keys when registering
+    /// types and deserializing maps.
+    ///
+    /// This centralizes naming convention handling, e.g. registering types
+    /// with `snake_case` keys, and deserializing documents with `kebab-case`
+    /// keys. Keys in deserialized maps are stored in their transformed form.
+    ///
+    /// Keys that are already registered are transformed as well. The transform
+    /// is applied to registered keys and to deserialized keys, so it should be
+    /// idempotent -- applying it to a transformed key must return the same
+    /// key. [`KeyTransform`] provides common transforms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if multiple registered keys transform to the same key, such as
+    /// `max_connections` and `max-connections` with
+    /// [`KeyTransform::kebab_case`].
+    ///
+    /// [`KeyTransform`]: crate::untagged::KeyTransform
+    /// [`KeyTransform::kebab_case`]: crate::untagged::KeyTransform::kebab_case
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::{KeyTransform, TypeMap, TypeReg};
+    ///
+    /// let mut type_reg = TypeReg::<String>::new().with_key_transform(KeyTransform::kebab_case);
+    /// type_reg.register::<u32>(String::from("max_connections"));
+    ///
+    /// let deserializer = serde_yaml::Deserializer::from_str("max-connections: 10");
+    /// let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+    ///
+    /// assert_eq!(Some(10), type_map.get::<u32, _>("max-connections").copied());
+    /// ```
+    pub fn with_key_transform<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        let key_transform: KeyTransformFn<K> = Box::new(move |key: K| K::from(f(key.borrow())));
+
+        // Every other map is keyed by a subset of `fn_seeds`' keys, so they are
+        // re-keyed in a single pass over `fn_seeds`. This keeps each key's
+        // entries together, and detects keys that transform to the same key.
+        let mut fn_opt_seeds = take_hash_map(&mut self.fn_opt_seeds);
+        let mut type_names = take_hash_map(&mut self.type_names);
+        let mut type_layouts = take_hash_map(&mut self.type_layouts);
+        let mut deprecations = take_hash_map(&mut self.deprecations);
+        #[cfg(feature = "schemars")]
+        let mut schema_fns = take_hash_map(&mut self.schema_fns);
+
+        std::mem::take(&mut self.fn_seeds)
+            .into_iter()
+            .for_each(|(key, fn_seed)| {
+                let key_transformed = key_transform(key.clone());
+                if self.fn_seeds.contains_key::<K>(&key_transformed) {
+                    panic!(
+                        "Multiple registered keys transform to `{key_transformed:?}`, \
+                        including `{key:?}`."
+                    );
+                }
+
+                if let Some(fn_opt_seed) = fn_opt_seeds.remove::<K>(&key) {
+                    self.fn_opt_seeds
+                        .insert(key_transformed.clone(), fn_opt_seed);
+                }
+                if let Some(type_name) = type_names.remove::<K>(&key) {
+                    self.type_names.insert(key_transformed.clone(), type_name);
+                }
+                if let Some(type_layout) = type_layouts.remove::<K>(&key) {
+                    self.type_layouts
+                        .insert(key_transformed.clone(), type_layout);
+                }
+                if let Some(message) = deprecations.remove::<K>(&key) {
+                    self.deprecations.insert(key_transformed.clone(), message);
+                }
+                #[cfg(feature = "schemars")]
+                if let Some(schema_fn) = schema_fns.remove::<K>(&key) {
+                    self.schema_fns.insert(key_transformed.clone(), schema_fn);
+                }
+                self.fn_seeds.insert(key_transformed, fn_seed);
+            });
+        self.key_transform = Some(key_transform);
+
+        self
+    }
+}
+
+impl<K, BoxDT> TypeReg<K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    /// Removes the type registered for the key, returning whether a type was
+    /// registered.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but `Hash` and
+    /// `Eq` on the borrowed form must match those for the key type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use type_reg::untagged::TypeReg;
+    ///
+    /// let mut type_reg = TypeReg::<String>::new();
+    /// type_reg.register::<u32>(String::from("one"));
+    ///
+    /// assert!(type_reg.unregister("one"));
+    /// assert!(!type_reg.unregister("one"));
+    /// assert!(type_reg.is_empty());
+    /// ```
+    pub fn unregister<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let is_registered = map_remove(&mut self.fn_seeds, key).is_some();
+        map_remove(&mut self.fn_opt_seeds, key);
+        map_remove(&mut self.type_names, key);
+        map_remove(&mut self.type_layouts, key);
+        map_remove(&mut self.deprecations, key);
+        #[cfg(feature = "schemars")]
+        map_remove(&mut self.schema_fns, key);
+
+        is_registered
+    }
+
+    /// Removes the registration that the handle was returned for, returning
+    /// whether it was removed.
+    ///
+    /// The registration is only removed if the key is still registered to the
+    /// same type. If the key has since been registered to a different type,
+    /// that registration is kept. A registration that was replaced by the
+    /// handle's registration is not restored.
+    pub fn unregister_handle(&mut self, handle: RegistrationHandle<K>) -> bool {
+        let RegistrationHandle { key, type_name } = handle;
+        if self.type_names.get(&key) == Some(&type_name) {
+            self.unregister(&key)
+        } else {
+            false
+        }
+    }
+}
+
+/// Visitor that deserializes an optional value using a function registered
+/// with [`TypeReg::register_boxed`].
+struct BoxedOptVisitor<'f, F>(&'f F);
+
+impl<'de, BoxDT, F> serde::de::Visitor<'de> for BoxedOptVisitor<'_, F>
+where
+    F: Fn(&mut dyn erased_serde::Deserializer<'_>) -> Result<BoxDT, erased_serde::Error>,
+{
+    type Value = Option<BoxDT>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an optional value")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.0)(&mut deserializer)
+            .map(Some)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Takes the entries out of the map, into a `HashMap` for constant time
+/// removal regardless of the `"ordered"` feature.
+fn take_hash_map<K, V>(map: &mut Map<K, V>) -> std::collections::HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    std::mem::take(map).into_iter().collect()
+}
+
+/// Removes the entry for the key, preserving the order of remaining entries.
+fn map_remove<K, V, Q>(map: &mut Map<K, V>, key: &Q) -> Option<V>
+where
+    K: Borrow<Q> + Eq + Hash,
+    Q: Hash + Eq + ?Sized,
+{
+    #[cfg(not(feature = "ordered"))]
+    {
+        map.remove(key)
+    }
+
+    #[cfg(feature = "ordered")]
+    {
+        map.shift_remove(key)
+    }
+}
+
+impl<K, BoxDT> Default for TypeReg<K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn default() -> Self {
+        Self {
+            fn_seeds: Map::default(),
+            fn_opt_seeds: Map::default(),
+            type_names: Map::default(),
+            type_layouts: Map::default(),
+            #[cfg(feature = "schemars")]
+            schema_fns: Map::default(),
+            key_transform: None,
+            parent: None,
+            catch_all: None,
+            deprecations: Map::new(),
+        }
+    }
+}
+
+impl<K, BoxDT> Debug for TypeReg<K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug_map = f.debug_map();
+
+        // BoxFnSeed is `!Debug`, so we just use "..".
+        self.fn_seeds.keys().for_each(|k| {
+            debug_map.key(&k);
+            debug_map.value(&"..");
+        });
+
+        debug_map.finish()
+    }
+}
+
+impl<K, BoxDT> Deref for TypeReg<K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    type Target = Map<K, BoxFnSeed<BoxDT>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.fn_seeds
+    }
+}
+
+impl<K, BoxDT> DerefMut for TypeReg<K, BoxDT>
+where
+    K: Eq + Hash + Debug,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.fn_seeds
+    }
+}
+
+// Used by [`serde_tagged`] to select which [`DeserializeSeed`] function to use.
+impl<'r, 'de, K, BoxDT> SeedFactory<'de, K> for &'r TypeReg<K, BoxDT>
+where
+    K: Clone + Eq + Hash + Debug + 'de + 'static,
+    BoxDT: DataTypeWrapper + 'static,
+{
+    type Seed = &'r BoxFnSeed<BoxDT>;
+    type Value = BoxDT;
+
+    fn seed<E>(self, type_key: K) -> Result<Self::Seed, E>
+    where
+        E: serde::de::Error,
+    {
+        self.deserialize_seed(&self.fold_key(type_key))
+    }
+}
+
+/// Returns whether a value error message says the value was `null`.
+///
+/// This is best-effort: by the time the error reaches the visitor, it has
+/// passed through `erased_serde`, which keeps only its message, and the value
+/// has already been consumed. So this matches the invalid type messages that
+/// `serde`, `serde_json`, and `serde-value` (used to buffer values) produce
+/// for `null`. Formats that describe `null` differently do not get the hint,
+/// but still get the key and type name.
+fn is_null_error(error: &str) -> bool {
+    // serde formats `null` as "unit value", and `serde_json` uses "null".
+    error.contains("invalid type: unit value, expected")
+        || error.contains("invalid type: null, expected")
+        || error.contains("Invalid type unit value. Expected")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt,
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        untagged::{
+            BoxDataTypeDowncast, BoxDt, BoxDtDisplay, KeyInterner, KeyTransform, TraitObjectReg,
+            TypeMap, TypeMapOpt, TypeReg,
+        },
+        TypeNameLit,
+    };
+
+    #[test]
+    fn deserialize_single() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        let data_u32 = type_reg.deserialize_single(deserializer).unwrap();
+        let data_u32 = BoxDataTypeDowncast::<u32>::downcast_ref(&data_u32).copied();
+
+        assert_eq!(Some(1), data_u32);
+    }
+
+    #[test]
+    fn deserialize_map_generic_types() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<Wrapper<u32>>(String::from("one"));
+        type_reg.register::<Wrapper<String>>(String::from("two"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: two }");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+        assert_eq!(
+            Some(Wrapper(1u32)),
+            type_map.get::<Wrapper<u32>, _>("one").cloned()
+        );
+        assert_eq!(
+            Some(Wrapper(String::from("two"))),
+            type_map.get::<Wrapper<String>, _>("two").cloned()
+        );
+        assert_eq!(None, type_map.get::<Wrapper<String>, _>("one"));
+    }
+
+    #[test]
+    fn deserialize_map_reuse_clears_and_keeps_allocation() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let mut pooled = TypeMap::<String>::with_capacity(32);
+        pooled.insert_ephemeral(String::from("two"), A(0));
+        pooled.insert(String::from("three"), 3u64);
+        let capacity = pooled.capacity();
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let type_map = type_reg
+            .deserialize_map_reuse(deserializer, pooled)
+            .unwrap();
+
+        assert_eq!(2, type_map.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+        assert!(!type_map.is_ephemeral("two"));
+        assert!(type_map.capacity() >= capacity);
+    }
+
+    #[test]
+    fn deserialize_map_integer_keys_from_json_object() {
+        let mut type_reg = TypeReg::<u32>::new();
+        type_reg.register::<u32>(1);
+        type_reg.register::<A>(2);
+
+        let mut type_map = TypeMap::<u32>::new();
+        type_map.insert(1, 1u32);
+        type_map.insert(2, A(2));
+        let serialized = serde_json::to_string(&type_map).unwrap();
+        assert!(serialized.contains(r#""1":1"#));
+
+        let mut deserializer = serde_json::Deserializer::from_str(&serialized);
+        let type_map: TypeMap<u32> = type_reg.deserialize_map(&mut deserializer).unwrap();
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>(&1).copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>(&2).copied());
+    }
+
+    #[test]
+    fn deserialize_map() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("two"));
+        type_reg.register::<A>(String::from("three"));
+
+        let serialized = "---\n\
+        one: 1\n\
+        two: 2\n\
+        three: 3\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let data_u64 = type_map.get::<u64, _>("two").copied();
+        let data_a = type_map.get::<A, _>("three").copied();
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(2u64), data_u64);
+        assert_eq!(Some(A(3)), data_a);
+    }
+
+    #[test]
+    fn deserialize_map_erased() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let mut deserializer_yaml = serde_yaml::Deserializer::from_str("one: 1\nthree: 3\n");
+        let mut deserializer_json = serde_json::Deserializer::from_str(r#"{"one":1,"three":3}"#);
+        let mut deserializers: [Box<dyn erased_serde::Deserializer>; 2] = [
+            Box::new(<dyn erased_serde::Deserializer>::erase(
+                deserializer_yaml.next().unwrap(),
+            )),
+            Box::new(<dyn erased_serde::Deserializer>::erase(
+                &mut deserializer_json,
+            )),
+        ];
+
+        deserializers.iter_mut().for_each(|deserializer| {
+            let type_map: TypeMap<String> = type_reg
+                .deserialize_map_erased(&mut **deserializer)
+                .unwrap();
+
+            assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+            assert_eq!(Some(A(3)), type_map.get::<A, _>("three").copied());
+        });
+    }
+
+    #[test]
+    fn deserialize_map_with_deadline_errs_between_entries() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<Slow>(String::from("slow"));
+        type_reg.register::<u32>(String::from("fast"));
+
+        let deadline = Instant::now() + Slow::DURATION / 2;
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{"slow":1,"fast":2}"#);
+        let error = type_reg
+            .deserialize_map_with_deadline(&mut deserializer, deadline)
+            .map(|_| ())
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("Deadline exceeded before deserializing the value for `\"fast\"`."),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_with_deadline_completes_before_deadline() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<Slow>(String::from("slow"));
+        type_reg.register::<u32>(String::from("fast"));
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{"slow":1,"fast":2}"#);
+        let type_map = type_reg
+            .deserialize_map_with_deadline(&mut deserializer, deadline)
+            .unwrap();
+
+        assert_eq!(Some(&Slow(1)), type_map.get::<Slow, _>("slow"));
+        assert_eq!(Some(2), type_map.get::<u32, _>("fast").copied());
+    }
+
+    #[test]
+    fn register_catch_all_deserializes_unregistered_keys() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register_catch_all::<A>();
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\n");
+        let type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+        assert!(type_map.unknown_entries().is_empty());
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: ~\ntwo: ~\n");
+        let type_map_opt = type_reg.deserialize_map_opt(deserializer).unwrap();
+        assert_eq!(Some(None), type_map_opt.get::<A, _>("two"));
+    }
+
+    #[test]
+    fn register_catch_all_has_lower_precedence_than_parent() {
+        let mut type_reg_parent = TypeReg::<String>::new();
+        type_reg_parent.register::<u32>(String::from("one"));
+        type_reg_parent.register_catch_all::<u64>();
+        let mut type_reg =
+            TypeReg::<String>::new().with_parent(Box::leak(Box::new(type_reg_parent)));
+        type_reg.register_catch_all::<A>();
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: x\n");
+        let error = type_reg.deserialize_map(deserializer).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains(&format!("as `{}`", std::any::type_name::<A>())),
+            "{error}"
+        );
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\n");
+        let type_map = type_reg.deserialize_map(deserializer).unwrap();
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn deserialize_map_collecting_warnings_includes_parent_deprecations() {
+        let mut type_reg_parent = TypeReg::<String>::new();
+        type_reg_parent.register_deprecated::<u32>(String::from("old"), "`old` is deprecated.");
+        let mut type_reg =
+            TypeReg::<String>::new().with_parent(Box::leak(Box::new(type_reg_parent)));
+        type_reg.register::<u32>(String::from("new"));
+        type_reg.register_deprecated::<A>(String::from("older"), "`older` is deprecated.");
+
+        let deserializer = serde_yaml::Deserializer::from_str("new: 1\nold: 2\n");
+        let (type_map, warnings) = type_reg
+            .deserialize_map_collecting_warnings(deserializer)
+            .unwrap();
+
+        assert_eq!(Some(2), type_map.get::<u32, _>("old").copied());
+        assert_eq!(vec![String::from("`old` is deprecated.")], warnings);
+    }
+
+    #[test]
+    fn deserialize_map_collecting_warnings_returns_warnings_in_stable_order() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_deprecated::<u32>(String::from("b"), "`b` is deprecated.");
+        type_reg.register_deprecated::<u32>(String::from("a"), "`a` is deprecated.");
+        type_reg.register_deprecated::<u32>(String::from("c"), "`c` is deprecated.");
+
+        let deserializer = serde_yaml::Deserializer::from_str("a: 1\nb: 2\nc: 3\n");
+        let (_type_map, warnings) = type_reg
+            .deserialize_map_collecting_warnings(deserializer)
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                String::from("`a` is deprecated."),
+                String::from("`b` is deprecated."),
+                String::from("`c` is deprecated."),
+            ],
+            warnings
+        );
+    }
+
+    #[test]
+    fn register_removes_deprecation() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_deprecated::<u32>(String::from("old"), "`old` is deprecated.");
+        type_reg.register::<u32>(String::from("old"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("old: 1\n");
+        let (_type_map, warnings) = type_reg
+            .deserialize_map_collecting_warnings(deserializer)
+            .unwrap();
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn merge_removes_deprecation_of_key_registered_in_other() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_deprecated::<u32>(String::from("old"), "`old` is deprecated.");
+        let mut type_reg_other = TypeReg::<String>::new();
+        type_reg_other.register::<u32>(String::from("old"));
+        type_reg.merge(type_reg_other);
+
+        let deserializer = serde_yaml::Deserializer::from_str("old: 1\n");
+        let (_type_map, warnings) = type_reg
+            .deserialize_map_collecting_warnings(deserializer)
+            .unwrap();
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn unregister_removes_deprecation() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_deprecated::<u32>(String::from("old"), "`old` is deprecated.");
+        type_reg.unregister("old");
+        type_reg.register::<u32>(String::from("old"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("old: 1\n");
+        let (_type_map, warnings) = type_reg
+            .deserialize_map_collecting_warnings(deserializer)
+            .unwrap();
+
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn deserialize_map_validated_against_accepts_matching_and_unlisted_keys() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let mut schema = std::collections::HashMap::new();
+        schema.insert(String::from("one"), "u32");
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\n");
+        let type_map = type_reg
+            .deserialize_map_validated_against(deserializer, &schema)
+            .unwrap();
+
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn deserialize_map_validated_against_ignores_keys_absent_from_document() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+
+        let mut schema = std::collections::HashMap::new();
+        schema.insert(String::from("two"), "u64");
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\n");
+        assert!(type_reg
+            .deserialize_map_validated_against(deserializer, &schema)
+            .is_ok());
+
+        let deserializer = serde_yaml::Deserializer::from_str("two: 2\n");
+        assert!(type_reg
+            .deserialize_map_validated_against(deserializer, &schema)
+            .is_err());
+    }
+
+    #[test]
+    fn deserialize_map_validated_against_lists_all_mismatches_sorted() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+        type_reg.register::<u32>(String::from("three"));
+
+        let mut schema = std::collections::HashMap::new();
+        schema.insert(String::from("one"), "u64");
+        schema.insert(String::from("two"), "u32");
+        schema.insert(String::from("three"), "u16");
+
+        let deserializer = serde_yaml::Deserializer::from_str("three: 3\ntwo: 2\none: 1\n");
+        let error = type_reg
+            .deserialize_map_validated_against(deserializer, &schema)
+            .unwrap_err();
+
+        assert_eq!(
+            "Entry `\"one\"` is registered as `u32`, but the schema expects `u64`.\n\
+            Entry `\"three\"` is registered as `u32`, but the schema expects `u16`.",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn deserialize_map_interned_keys_shares_keys_across_maps() {
+        let mut type_reg = TypeReg::<Arc<str>>::new();
+        type_reg.register::<u32>(Arc::from("one"));
+        type_reg.register::<A>(Arc::from("two"));
+
+        let mut key_interner = KeyInterner::new();
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\ntwo: 2\n");
+        let type_map_0 = type_reg
+            .deserialize_map_interned_keys(deserializer, &mut key_interner)
+            .unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{ "two": 3 }"#);
+        let type_map_1 = type_reg
+            .deserialize_map_interned_keys(&mut deserializer, &mut key_interner)
+            .unwrap();
+
+        let (key_0, _) = type_map_0.get_key_value("two").unwrap();
+        let (key_1, _) = type_map_1.get_key_value("two").unwrap();
+        assert!(Arc::ptr_eq(key_0, key_1));
+        assert_eq!(Some(A(3)), type_map_1.get::<A, _>("two").copied());
+        assert_eq!(2, key_interner.len());
+    }
+
+    #[test]
+    fn deserialize_map_interned_keys_does_not_intern_failed_keys() {
+        let mut type_reg = TypeReg::<Arc<str>>::new();
+        type_reg.register::<u32>(Arc::from("one"));
+
+        let mut key_interner = KeyInterner::new();
+        let deserializer = serde_yaml::Deserializer::from_str("unregistered: 1\n");
+        assert!(type_reg
+            .deserialize_map_interned_keys(deserializer, &mut key_interner)
+            .is_err());
+        let deserializer = serde_yaml::Deserializer::from_str("one: abc\n");
+        assert!(type_reg
+            .deserialize_map_interned_keys(deserializer, &mut key_interner)
+            .is_err());
+
+        assert!(key_interner.is_empty());
+    }
+
+    #[test]
+    fn deserialize_map_with_depth_limit() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<Tree>(String::from("tree"));
+
+        let serialized = r#"{"one":1,"tree":{"Node":{"Node":"Leaf"}}}"#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let type_map: TypeMap<String> = type_reg
+            .deserialize_map_with_depth_limit(&mut deserializer, 4)
+            .unwrap();
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(
+            Some(&Tree::Node(Box::new(Tree::Node(Box::new(Tree::Leaf))))),
+            type_map.get::<Tree, _>("tree")
+        );
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let error = type_reg
+            .deserialize_map_with_depth_limit(&mut deserializer, 3)
+            .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("Value nesting exceeds the depth limit of 3."),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_with_capacity_cap() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\nthree: 3\n");
+        let type_map: TypeMap<String> = type_reg
+            .deserialize_map_with_capacity_cap(deserializer, 0)
+            .unwrap();
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(3)), type_map.get::<A, _>("three").copied());
+    }
+
+    #[test]
+    fn deserialize_map_new_typed() {
+        let mut type_reg = TypeReg::<String, BoxDtDisplay>::new_typed();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("two"));
+        type_reg.register::<A>(String::from("three"));
+
+        let serialized = "---\n\
+        one: 1\n\
+        two: 2\n\
+        three: 3\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map: TypeMap<String, BoxDtDisplay> =
+            type_reg.deserialize_map(deserializer).unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let data_u64 = type_map.get::<u64, _>("two").copied();
+        let data_a = type_map.get::<A, _>("three").copied();
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(2u64), data_u64);
+        assert_eq!(Some(A(3)), data_a);
+    }
+
+    #[test]
+    fn deserialize_map_with_capacity_typed() {
+        let mut type_reg = TypeReg::<String, BoxDtDisplay>::with_capacity_typed(3);
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("two"));
+        type_reg.register::<A>(String::from("three"));
+
+        let serialized = "---\n\
+        one: 1\n\
+        two: 2\n\
+        three: 3\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map: TypeMap<String, BoxDtDisplay> =
+            type_reg.deserialize_map(deserializer).unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let data_u64 = type_map.get::<u64, _>("two").copied();
+        let data_a = type_map.get::<A, _>("three").copied();
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(2u64), data_u64);
+        assert_eq!(Some(A(3)), data_a);
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn deserialize_single_has_good_error_message_when_type_not_registered() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("two: 2");
+        let error = type_reg.deserialize_single(deserializer).unwrap_err();
+        assert_eq!(
+            r#"Type key `"two"` not registered in type registry.
+Available types are:
+
+- "one"
+- "three"
+
+"#,
+            format!("{error}")
+        );
+    }
+
+    #[cfg(feature = "ordered")]
+    #[test]
+    fn deserialize_map_opt_has_good_error_message_when_type_not_registered() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("two: 2");
+        let error = type_reg.deserialize_map_opt(deserializer).unwrap_err();
+        assert_eq!(
+            r#"Type key `"two"` not registered in type registry.
+Available types are:
+
+- "one"
+- "three"
+
+"#,
+            format!("{error}")
+        );
+    }
+
+    #[test]
+    fn deserialize_map_with_unknown_entries_yaml() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let serialized = "---\n\
+            one: 1\n\
+            two: 2\n\
+            three: 3\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let data_u64 = type_map.get_unknown_entry("two").cloned();
+        let data_a = type_map.get::<A, _>("three").copied();
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(A(3)), data_a);
+
+        assert_eq!(
+            data_u64,
+            Some(serde_yaml::Value::Number(serde_yaml::Number::from(2u64)))
+        );
+        assert_eq!(1, type_map.unknown_entries().len());
+    }
+
+    #[test]
+    fn deserialize_map_handling_unknowns_calls_handler() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let mut unknowns = Vec::new();
+        let mut deserializer =
+            serde_json::Deserializer::from_str(r#"{ "one": 1, "two": 2, "three": [3] }"#);
+        let type_map = type_reg
+            .deserialize_map_handling_unknowns::<'_, serde_json::Value, _, _, _>(
+                &mut deserializer,
+                |key, value| {
+                    unknowns.push((key.clone(), value.clone()));
+                    Ok(())
+                },
+            )
+            .unwrap();
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(1, type_map.len());
+        assert_eq!(
+            vec![
+                (String::from("two"), serde_json::json!(2)),
+                (String::from("three"), serde_json::json!([3])),
+            ],
+            unknowns
+        );
+    }
+
+    #[test]
+    fn deserialize_map_handling_unknowns_returns_handler_error() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let mut calls = 0;
+        let mut deserializer =
+            serde_json::Deserializer::from_str(r#"{ "two": 2, "three": 3, "one": 1 }"#);
+        let error = type_reg
+            .deserialize_map_handling_unknowns::<'_, serde_json::Value, _, _, _>(
+                &mut deserializer,
+                |key, _value| {
+                    calls += 1;
+                    Err(serde::de::Error::custom(format!("`{key}` is not allowed")))
+                },
+            )
+            .map(|_| ())
+            .unwrap_err();
+
+        assert_eq!(1, calls);
+        assert_eq!("`two` is not allowed", error.to_string());
+    }
+
+    #[test]
+    fn deserialize_map_with_unknown_entries_json() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+
+        let serialized = r#"
+            {
+                "one": 1,
+                "two": 2,
+                "three": 3
+            }
+        "#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, serde_json::Value, _, _>(&mut deserializer)
+            .unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let data_u64 = type_map.get_unknown_entry("two").cloned();
+        let data_a = type_map.get::<A, _>("three").copied();
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(A(3)), data_a);
+
+        assert_eq!(
+            data_u64,
+            Some(serde_json::Value::Number(serde_json::Number::from(2u64)))
+        );
+        assert_eq!(1, type_map.unknown_entries().len());
+    }
+
+    #[test]
+    fn deserialize_map_with_unknown_entries_json_raw_value() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let serialized = r#"{ "one": 1, "two": { "a": 1.50,  "b": [ 1 ] } }"#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let type_map = type_reg
+            .deserialize_map_with_unknowns::<'_, Box<serde_json::value::RawValue>, _, _>(
+                &mut deserializer,
+            )
+            .unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let two = type_map
+            .get_unknown_entry("two")
+            .map(|raw_value| raw_value.get());
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(r#"{ "a": 1.50,  "b": [ 1 ] }"#), two);
+    }
+
+    #[cfg(feature = "adaptive")]
+    #[test]
+    fn deserialize_map_adaptive() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("two"));
+        type_reg.register::<A>(String::from("three"));
+        type_reg.register::<std::collections::BTreeMap<String, u32>>(String::from("four"));
+
+        let serialized = "---\n\
+        one: 1\n\
+        two: { u64: 2 }\n\
+        three: { 'type_reg::untagged::type_reg::tests::A': 3 }\n\
+        four: { u32: 4 }\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map: TypeMap<String> = type_reg.deserialize_map_adaptive(deserializer).unwrap();
+
+        let data_u32 = type_map.get::<u32, _>("one").copied();
+        let data_u64 = type_map.get::<u64, _>("two").copied();
+        let data_a = type_map.get::<A, _>("three").copied();
+        let data_map = type_map
+            .get::<std::collections::BTreeMap<String, u32>, _>("four")
+            .and_then(|map| map.get("u32"))
+            .copied();
+
+        assert_eq!(Some(1u32), data_u32);
+        assert_eq!(Some(2u64), data_u64);
+        assert_eq!(Some(A(3)), data_a);
+        // Untagged interpretation takes precedence.
+        assert_eq!(Some(4u32), data_map);
+    }
+
+    #[cfg(feature = "adaptive")]
+    #[test]
+    fn deserialize_map_adaptive_returns_error_when_neither_form_matches() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: { u64: 1 }");
+        let result: Result<TypeMap<String>, _> = type_reg.deserialize_map_adaptive(deserializer);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserialize_map_opt() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("two"));
+        type_reg.register::<A>(String::from("three"));
+
+        let serialized = "---\n\
+        one: 1\n\
+        two: 2\n\
+        three: null\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
+
+        let data_u32 = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
+        let data_u64 = type_map_opt.get::<u64, _>("two").map(|two| two.copied());
+        let data_a = type_map_opt
+            .get::<A, _>("three")
+            .map(|three| three.copied());
+
+        assert_eq!(Some(Some(1u32)), data_u32);
+        assert_eq!(Some(Some(2u64)), data_u64);
+        assert_eq!(Some(None), data_a);
+    }
+
+    #[test]
+    fn deserialize_map_opt_with_unknown_entries_yaml() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+        type_reg.register::<u8>(String::from("four"));
+
+        let serialized = "---\n\
+            one: 1\n\
+            two: 2\n\
+            three: 3\n\
+            four: null\n\
+            five: null\n\
+        ";
+
+        let deserializer = serde_yaml::Deserializer::from_str(serialized);
+        let type_map_opt = type_reg
+            .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
+            .unwrap();
+
+        let one = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
+        let two = type_map_opt
+            .get_unknown_entry("two")
+            .map(|two| two.cloned());
+        let three = type_map_opt
+            .get::<A, _>("three")
+            .map(|three| three.copied());
+        let four = type_map_opt.get::<u8, _>("four").map(|four| four.copied());
+        let five = type_map_opt
+            .get_unknown_entry("five")
+            .map(|five| five.cloned());
+
+        assert_eq!(Some(Some(1u32)), one);
+        assert_eq!(Some(Some(A(3))), three);
+        assert_eq!(Some(None::<u8>), four);
+
+        assert_eq!(
+            two,
+            Some(Some(serde_yaml::Value::Number(serde_yaml::Number::from(
+                2u64
+            ))))
+        );
+        assert_eq!(five, Some(None));
+        assert_eq!(2, type_map_opt.unknown_entries().len());
+    }
+
+    #[test]
+    fn deserialize_map_opt_with_unknown_entries_json() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("three"));
+        type_reg.register::<u8>(String::from("four"));
+
+        let serialized = r#"
+            {
+                "one": 1,
+                "two": 2,
+                "three": 3,
+                "four": null,
+                "five": null
+            }
+        "#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(serialized);
+        let type_map_opt = type_reg
+            .deserialize_map_opt_with_unknowns::<'_, serde_json::Value, _, _>(&mut deserializer)
+            .unwrap();
+
+        let one = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
+        let two = type_map_opt
+            .get_unknown_entry("two")
+            .map(|two| two.cloned());
+        let three = type_map_opt
+            .get::<A, _>("three")
+            .map(|three| three.copied());
+        let four = type_map_opt.get::<u8, _>("four").map(|four| four.copied());
+        let five = type_map_opt
+            .get_unknown_entry("five")
+            .map(|five| five.cloned());
+
+        assert_eq!(Some(Some(1u32)), one);
+        assert_eq!(Some(Some(A(3))), three);
+        assert_eq!(Some(None::<u8>), four);
+
+        assert_eq!(
+            two,
+            Some(Some(serde_json::Value::Number(serde_json::Number::from(
+                2u64
+            ))))
+        );
+        assert_eq!(five, Some(None));
+        assert_eq!(2, type_map_opt.unknown_entries().len());
+    }
+
+    #[test]
+    fn with_capacity() {
+        let type_reg = TypeReg::<String>::default();
+        assert_eq!(0, type_reg.capacity());
+
+        let type_reg = TypeReg::<String>::with_capacity(5);
+        assert!(type_reg.capacity() >= 5);
+    }
+
+    #[test]
+    fn deref_mut() {
+        let mut type_reg = TypeReg::<String>::new();
+        assert!(type_reg.get_mut("one").is_none())
+    }
+
+    #[test]
+    fn len() {
+        let mut type_reg = TypeReg::<String>::new();
+        assert_eq!(0, type_reg.len());
+        assert!(type_reg.is_empty());
+
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("two"));
+        type_reg.register::<u64>(String::from("two"));
+
+        assert_eq!(2, type_reg.len());
+        assert!(!type_reg.is_empty());
+    }
+
+    #[cfg(feature = "interned")]
+    #[test]
+    fn deserialize_map_interned() {
+        use crate::untagged::ArcDt;
+
+        let mut type_reg = TypeReg::<String, ArcDt>::new_typed();
+        type_reg.register::<A>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+        type_reg.register::<A>(String::from("three"));
+        type_reg.register::<u32>(String::from("four"));
+
+        let deserializer =
+            serde_yaml::Deserializer::from_str("{ one: 1, two: 1, three: 3, four: 1 }");
+        let type_map: TypeMap<String, ArcDt> =
+            type_reg.deserialize_map_interned(deserializer).unwrap();
+
+        let one = type_map.get_raw("one").unwrap();
+        let two = type_map.get_raw("two").unwrap();
+        let three = type_map.get_raw("three").unwrap();
+        let four = type_map.get_raw("four").unwrap();
+
+        assert!(ArcDt::ptr_eq(one, two));
+        assert!(!ArcDt::ptr_eq(one, three));
+        assert!(!ArcDt::ptr_eq(one, four));
+        assert_eq!(Some(A(1)), type_map.get::<A, _>("one").copied());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("four").copied());
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema_fragment() {
+        #[derive(Clone, Debug, Deserialize, Serialize, schemars::JsonSchema)]
+        struct Config {
+            name: String,
+        }
+
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_with_schema::<Config>(String::from("one"));
+        type_reg.register_with_schema::<Config>(String::from("two"));
+        type_reg.register::<u32>(String::from("three"));
+
+        let schema = type_reg.json_schema_fragment();
+
+        assert_eq!(
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "one": { "$ref": "#/$defs/Config" },
+                    "two": { "$ref": "#/$defs/Config" },
+                    "three": true,
+                },
+                "$defs": {
+                    "Config": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                        },
+                        "required": ["name"],
+                    },
+                },
+            }),
+            schema
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn register_replaces_schema() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_with_schema::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("one"));
+
+        let schema = type_reg.json_schema_fragment();
+
+        assert_eq!(
+            Some(&serde_json::json!(true)),
+            schema.pointer("/properties/one")
+        );
+    }
+
+    #[test]
+    fn deserialize_map_error_includes_key_and_null_hint() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: ~");
+        let error = type_reg
+            .deserialize_map::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert_eq!(
+            "Entry `\"one\"` was null, but type `u32` is not optional; \
+            use `TypeReg::deserialize_map_opt` if null values are expected.\n\
+            one: invalid type: unit value, expected u32 at line 1 column 6",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn deserialize_map_error_json_includes_key_and_null_hint() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{ "one": null }"#);
+        let error = type_reg
+            .deserialize_map::<_, serde_json::Error>(&mut deserializer)
+            .unwrap_err();
+
+        assert_eq!(
+            "Entry `\"one\"` was null, but type `u32` is not optional; \
+            use `TypeReg::deserialize_map_opt` if null values are expected.\n\
+            invalid type: null, expected u32 at line 1 column 13",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn deserialize_map_error_json_value_includes_null_hint() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let value = serde_json::json!({ "one": null });
+        let error = type_reg
+            .deserialize_map::<_, serde_json::Error>(value)
+            .unwrap_err();
+
+        assert!(
+            error.to_string().starts_with(
+                "Entry `\"one\"` was null, but type `u32` is not optional; \
+                use `TypeReg::deserialize_map_opt` if null values are expected.\n"
+            ),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_error_yaml_value_includes_null_hint() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let value = serde_yaml::from_str::<serde_yaml::Value>("one: ~").unwrap();
+        let error = type_reg
+            .deserialize_map::<_, serde_yaml::Error>(value)
+            .unwrap_err();
+
+        assert!(
+            error.to_string().starts_with(
+                "Entry `\"one\"` was null, but type `u32` is not optional; \
+                use `TypeReg::deserialize_map_opt` if null values are expected.\n"
+            ),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_error_excludes_null_hint_for_non_null_value() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: \"~\"");
+        let error = type_reg
+            .deserialize_map::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("Failed to deserialize entry `\"one\"` as `u32`: "),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_error_json_keeps_line_and_column() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+
+        let mut deserializer =
+            serde_json::Deserializer::from_str("{\n  \"one\": 1,\n  \"two\": \"x\"\n}");
+        let error = type_reg
+            .deserialize_map::<_, serde_json::Error>(&mut deserializer)
+            .unwrap_err();
+
+        assert_eq!(
+            "Failed to deserialize entry `\"two\"` as `u32`: \
+            invalid type: string \"x\", expected u32 at line 3 column 12",
+            error.to_string()
+        );
+        assert_eq!((3, 12), (error.line(), error.column()));
+    }
+
+    #[test]
+    fn deserialize_map_error_json_null_keeps_line_and_column() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let mut deserializer = serde_json::Deserializer::from_str("{\n  \"one\": null\n}");
+        let error = type_reg
+            .deserialize_map::<_, serde_json::Error>(&mut deserializer)
+            .unwrap_err();
+
+        assert_eq!((2, 13), (error.line(), error.column()));
+        assert_eq!(1, error.to_string().matches(" at line ").count());
+    }
+
+    #[test]
+    fn deserialize_maps() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let documents = serde_yaml::Deserializer::from_str(
+            "---\n\
+            one: 1\n\
+            ---\n\
+            one: abc\n\
+            ---\n\
+            one: 3\n\
+            two: 4\n",
+        );
+        let mut type_maps = type_reg.deserialize_maps::<_, _, serde_yaml::Error>(documents);
+
+        let type_map = type_maps.next().unwrap().unwrap();
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert!(type_maps.next().unwrap().is_err());
+        let type_map = type_maps.next().unwrap().unwrap();
+        assert_eq!(Some(3u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(4)), type_map.get::<A, _>("two").copied());
+        assert!(type_maps.next().is_none());
+    }
+
+    #[test]
+    fn deserialize_map_with_reviver() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let mut keys_revived = Vec::new();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let type_map: TypeMap<String> = type_reg
+            .deserialize_map_with_reviver(deserializer, |key, mut value| {
+                keys_revived.push(key.clone());
+                if let Some(n) = BoxDataTypeDowncast::<u32>::downcast_mut(&mut value) {
+                    *n *= 10;
+                }
+                value
+            })
+            .unwrap();
+        keys_revived.sort();
+
+        assert_eq!(vec!["one", "two"], keys_revived);
+        assert_eq!(Some(10u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn deserialize_seq_of_entries() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("- one: 1\n- two: 2\n");
+        let type_map: TypeMap<String> = type_reg.deserialize_seq_of_entries(deserializer).unwrap();
+
+        assert_eq!(2, type_map.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+    }
+
+    #[test]
+    fn deserialize_seq_of_entries_error_on_empty_element() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("- one: 1\n- {}\n");
+        let error = type_reg
+            .deserialize_seq_of_entries(deserializer)
+            .unwrap_err();
+
+        assert!(error.to_string().contains(
+            "Expected sequence element 1 to be a map with a single entry, but it has no entries."
+        ));
+    }
+
+    #[test]
+    fn deserialize_seq_of_entries_error_on_multiple_entries() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("- one: 1\n  two: 2\n");
+        let error = type_reg
+            .deserialize_seq_of_entries(deserializer)
+            .unwrap_err();
+
+        assert!(error.to_string().contains(
+            "Expected sequence element 0 to be a map with a single entry, but it has multiple \
+            entries."
+        ));
+    }
+
+    #[test]
+    fn deserialize_map_flattened() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("db.pool.size"));
+        type_reg.register::<A>(String::from("db.port"));
+
+        let deserializer = serde_yaml::Deserializer::from_str(
+            "one: 1\n\
+            db:\n\
+            \x20 pool: { size: 4 }\n\
+            \x20 port: 5432\n",
+        );
+        let type_map: TypeMap<String> = type_reg
+            .deserialize_map_flattened(deserializer, ".")
+            .unwrap();
+
+        assert_eq!(3, type_map.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(4u32), type_map.get::<u32, _>("db.pool.size").copied());
+        assert_eq!(Some(A(5432)), type_map.get::<A, _>("db.port").copied());
+    }
+
+    #[test]
+    fn deserialize_map_flattened_registered_key_takes_map_value() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<std::collections::BTreeMap<String, u32>>(String::from("db"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("db: { port: 5432 }");
+        let type_map: TypeMap<String> = type_reg
+            .deserialize_map_flattened(deserializer, "/")
+            .unwrap();
+
+        let db = type_map
+            .get::<std::collections::BTreeMap<String, u32>, _>("db")
+            .unwrap();
+        assert_eq!(Some(&5432), db.get("port"));
+    }
+
+    #[test]
+    fn deserialize_map_flattened_error_on_collision() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("db.port"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("db.port: 1\ndb: { port: 2 }\n");
+        let error = type_reg
+            .deserialize_map_flattened(deserializer, ".")
+            .map(|_: TypeMap<String>| ())
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Key `\"db.port\"` appears more than once after flattening."));
+    }
+
+    #[test]
+    fn deserialize_map_flattened_error_on_unregistered_leaf() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("db.port"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("db: { host: localhost }");
+        let error = type_reg
+            .deserialize_map_flattened(deserializer, ".")
+            .map(|_: TypeMap<String>| ())
+            .unwrap_err();
+
+        assert!(error.to_string().contains(
+            "expected `db.host` to be a registered type key, or a map of nested entries"
+        ));
+    }
+
+    #[test]
+    fn deserialize_map_error_includes_key() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: abc");
+        let error = type_reg
+            .deserialize_map::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert_eq!(
+            "Failed to deserialize entry `\"one\"` as `u32`: \
+            one: invalid type: string \"abc\", expected u32 at line 1 column 6",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn deserialize_map_opt_error_includes_key_and_type_name() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: not a number");
+        let error = type_reg
+            .deserialize_map_opt::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert_eq!(
+            "Failed to deserialize entry `\"one\"` as `u32`: \
+            one: invalid type: string \"not a number\", expected u32 at line 1 column 6",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn deserialize_map_opt_with_unknowns_error_includes_key_and_type_name() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: not a number\ntwo: 2");
+        let error = type_reg
+            .deserialize_map_opt_with_unknowns::<serde_yaml::Value, _, serde_yaml::Error>(
+                deserializer,
+            )
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("Failed to deserialize entry `\"one\"` as `u32`: "),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn deserialize_map_value_from_scalar_or_map() {
+        #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+        #[serde(untagged)]
+        enum LogConfig {
+            Level(String),
+            Detailed { level: String, json: bool },
+        }
+
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<LogConfig>(String::from("log"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("log: info");
+        let type_map_scalar: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str("log: { level: info, json: true }");
+        let type_map_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+        let mut deserializer = serde_json::Deserializer::from_str(r#"{ "log": "info" }"#);
+        let type_map_json: TypeMap<String> = type_reg.deserialize_map(&mut deserializer).unwrap();
+
+        assert_eq!(
+            Some(&LogConfig::Level(String::from("info"))),
+            type_map_scalar.get::<LogConfig, _>("log")
+        );
+        assert_eq!(
+            Some(&LogConfig::Detailed {
+                level: String::from("info"),
+                json: true
+            }),
+            type_map_map.get::<LogConfig, _>("log")
+        );
+        assert_eq!(
+            Some(&LogConfig::Level(String::from("info"))),
+            type_map_json.get::<LogConfig, _>("log")
+        );
+    }
+
+    #[cfg(feature = "instrument")]
+    #[test]
+    fn deserialize_map_timed() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let (type_map, durations) = type_reg.deserialize_map_timed(deserializer).unwrap();
+
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+        assert_eq!(2, durations.len());
+        assert!(durations.contains_key("one"));
+        assert!(durations.contains_key("two"));
+    }
+
+    #[cfg(feature = "iter")]
+    #[test]
+    fn deserialize_map_iter() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ two: 2, one: 1 }");
+        let entries = type_reg
+            .deserialize_map_iter::<_, serde_yaml::Error>(deserializer)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(2, entries.len());
+        assert_eq!("two", entries[0].0);
+        assert_eq!(
+            Some(&A(2)),
+            BoxDataTypeDowncast::<A>::downcast_ref(&entries[0].1)
+        );
+        assert_eq!("one", entries[1].0);
+        assert_eq!(
+            Some(&1u32),
+            BoxDataTypeDowncast::<u32>::downcast_ref(&entries[1].1)
+        );
+    }
+
+    #[cfg(feature = "iter")]
+    #[test]
+    fn deserialize_map_iter_returns_value_error_when_reached() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: abc }");
+        let mut entries = type_reg.deserialize_map_iter::<_, serde_yaml::Error>(deserializer);
+
+        assert!(matches!(entries.next(), Some(Ok((key, _))) if key == "one"));
+        let error = entries.next().unwrap().unwrap_err();
+        assert!(error.to_string().contains("`\"two\"`"), "{error}");
+        assert!(entries.next().is_none());
+    }
+
+    #[cfg(feature = "iter")]
+    #[test]
+    fn deserialize_map_iter_returns_buffer_error() {
+        let type_reg = TypeReg::<String>::new();
+
+        let deserializer = serde_yaml::Deserializer::from_str("- 1");
+        let mut entries = type_reg.deserialize_map_iter::<_, serde_yaml::Error>(deserializer);
+
+        assert_eq!((1, Some(1)), entries.size_hint());
+        assert!(entries.next().unwrap().is_err());
+        assert!(entries.next().is_none());
+    }
+
+    #[cfg(feature = "id_field")]
+    #[test]
+    fn deserialize_seq_to_map_by_id() {
+        #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+        struct User {
+            name: String,
+        }
+
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<User>(String::from("user"));
+        type_reg.register::<A>(String::from("a"));
+
+        let mut deserializer = serde_json::Deserializer::from_str(
+            r#"[
+                { "id": "u1", "type": "user", "name": "Ada" },
+                { "name": "Grace", "type": "user", "id": "u2" }
+            ]"#,
+        );
+        let type_map: TypeMap<String> = type_reg
+            .deserialize_seq_to_map_by_id(&mut deserializer, "id", "type")
+            .unwrap();
+
+        assert_eq!(2, type_map.len());
+        assert_eq!(
+            Some(&User {
+                name: String::from("Ada")
+            }),
+            type_map.get::<User, _>("u1")
+        );
+        assert_eq!(
+            Some(&User {
+                name: String::from("Grace")
+            }),
+            type_map.get::<User, _>("u2")
+        );
+    }
+
+    #[cfg(feature = "id_field")]
+    #[test]
+    fn deserialize_seq_to_map_by_id_returns_error_for_missing_field() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<A>(String::from("a"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("- { type: a }");
+        let error = type_reg
+            .deserialize_seq_to_map_by_id::<_, serde_yaml::Error>(deserializer, "id", "type")
+            .unwrap_err();
+
+        assert!(error.to_string().contains("missing field `id`"), "{error}");
+    }
+
+    #[cfg(feature = "id_field")]
+    #[test]
+    fn deserialize_seq_to_map_by_id_returns_error_for_duplicate_id() {
+        #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+        struct Unit {}
+
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<Unit>(String::from("unit"));
 
-    pub(crate) fn deserialize_opt_seed_opt(
-        &self,
-        type_key: &K,
-    ) -> Option<&BoxFnSeed<Option<BoxDT>>> {
-        self.fn_opt_seeds.get(type_key)
+        let deserializer = serde_yaml::Deserializer::from_str(
+            "- { id: u1, type: unit }\n- { id: u1, type: unit }",
+        );
+        let error = type_reg
+            .deserialize_seq_to_map_by_id::<_, serde_yaml::Error>(deserializer, "id", "type")
+            .unwrap_err();
+
+        assert!(
+            error.to_string().contains("duplicate id `\"u1\"`"),
+            "{error}"
+        );
     }
-}
 
-impl<K, BoxDT> Default for TypeReg<K, BoxDT>
-where
-    K: Eq + Hash + Debug,
-{
-    fn default() -> Self {
-        Self {
-            fn_seeds: Map::default(),
-            fn_opt_seeds: Map::default(),
+    #[cfg(feature = "id_field")]
+    #[test]
+    fn deserialize_seq_to_map_by_id_returns_error_for_unregistered_type() {
+        let type_reg = TypeReg::<String>::new();
+
+        let deserializer = serde_yaml::Deserializer::from_str("- { id: u1, type: user }");
+        let error = type_reg
+            .deserialize_seq_to_map_by_id::<_, serde_yaml::Error>(deserializer, "id", "type")
+            .unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .contains("Type key `\"user\"` not registered"),
+            "{error}"
+        );
+    }
+
+    #[cfg(feature = "yaml_tag")]
+    #[test]
+    fn deserialize_map_by_yaml_tag() {
+        #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+        struct Task {
+            name: String,
         }
+
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("u32"));
+        type_reg.register::<A>(String::from("A"));
+        type_reg.register::<Task>(String::from("Task"));
+
+        let deserializer = serde_yaml::Deserializer::from_str(
+            "one: !u32 1\n\
+            two: !A 2\n\
+            three: !Task { name: build }\n",
+        );
+        let type_map: TypeMap<String> = type_reg.deserialize_map_by_yaml_tag(deserializer).unwrap();
+
+        assert_eq!(3, type_map.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+        assert_eq!(
+            Some(&Task {
+                name: String::from("build")
+            }),
+            type_map.get::<Task, _>("three")
+        );
     }
-}
 
-impl<K, BoxDT> Debug for TypeReg<K, BoxDT>
-where
-    K: Eq + Hash + Debug,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut debug_map = f.debug_map();
+    #[cfg(feature = "yaml_tag")]
+    #[test]
+    fn deserialize_map_by_yaml_tag_returns_error_for_unregistered_tag() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("u32"));
 
-        // BoxFnSeed is `!Debug`, so we just use "..".
-        self.fn_seeds.keys().for_each(|k| {
-            debug_map.key(&k);
-            debug_map.value(&"..");
-        });
+        let deserializer = serde_yaml::Deserializer::from_str("one: !u64 1\n");
+        let error = type_reg
+            .deserialize_map_by_yaml_tag::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
 
-        debug_map.finish()
+        assert!(error.to_string().contains("u64"), "{error}");
     }
-}
 
-impl<K, BoxDT> Deref for TypeReg<K, BoxDT>
-where
-    K: Eq + Hash + Debug,
-{
-    type Target = Map<K, BoxFnSeed<BoxDT>>;
+    #[cfg(feature = "yaml_tag")]
+    #[test]
+    fn deserialize_map_by_yaml_tag_returns_error_for_untagged_value() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
 
-    fn deref(&self) -> &Self::Target {
-        &self.fn_seeds
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1\n");
+        let error = type_reg
+            .deserialize_map_by_yaml_tag::<_, serde_yaml::Error>(deserializer)
+            .unwrap_err();
+
+        assert!(
+            error.to_string().contains("expected a YAML tagged value"),
+            "{error}"
+        );
     }
-}
 
-impl<K, BoxDT> DerefMut for TypeReg<K, BoxDT>
-where
-    K: Eq + Hash + Debug,
-{
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.fn_seeds
+    #[cfg(feature = "jsonl")]
+    #[test]
+    fn deserialize_jsonl() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<A>(String::from("two"));
+
+        let jsonl = "{ \"one\": 1 }\n\
+            \n\
+            { \"two\": 2 }\n\
+            { \"one\": 3 }";
+        let type_map = type_reg.deserialize_jsonl(jsonl.as_bytes()).unwrap();
+
+        assert_eq!(2, type_map.len());
+        assert_eq!(Some(3u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
     }
-}
 
-// Used by [`serde_tagged`] to select which [`DeserializeSeed`] function to use.
-impl<'r, 'de, K, BoxDT> SeedFactory<'de, K> for &'r TypeReg<K, BoxDT>
-where
-    K: Clone + Eq + Hash + Debug + 'de + 'static,
-    BoxDT: DataTypeWrapper + 'static,
-{
-    type Seed = &'r BoxFnSeed<BoxDT>;
-    type Value = BoxDT;
+    #[cfg(feature = "jsonl")]
+    #[test]
+    fn deserialize_jsonl_error_includes_line_number() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
 
-    fn seed<E>(self, type_key: K) -> Result<Self::Seed, E>
-    where
-        E: serde::de::Error,
-    {
-        self.deserialize_seed(&type_key)
+        let jsonl = "{ \"one\": 1 }\n\
+            \n\
+            { \"one\": \"a\" }\n";
+        let error = type_reg.deserialize_jsonl(jsonl.as_bytes()).unwrap_err();
+
+        assert_eq!(
+            "Failed to deserialize line 3: \
+            Failed to deserialize entry `\"one\"` as `u32`: \
+            invalid type: string \"a\", expected u32 at line 1 column 12",
+            error.to_string()
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::fmt;
+    #[cfg(feature = "jsonl")]
+    #[test]
+    fn deserialize_jsonl_error_on_trailing_characters() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
 
-    use serde::{Deserialize, Serialize};
+        let jsonl = "{ \"one\": 1 } x";
+        let error = type_reg.deserialize_jsonl(jsonl.as_bytes()).unwrap_err();
 
-    use crate::untagged::{BoxDataTypeDowncast, BoxDtDisplay, TypeMap, TypeMapOpt, TypeReg};
+        assert!(error
+            .to_string()
+            .starts_with("Failed to deserialize line 1: "));
+    }
 
+    #[cfg(feature = "json")]
     #[test]
-    fn deserialize_single() {
+    fn deserialize_map_with_unknowns_positioned() {
         let mut type_reg = TypeReg::<String>::new();
         type_reg.register::<u32>(String::from("one"));
 
-        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
-        let data_u32 = type_reg.deserialize_single(deserializer).unwrap();
-        let data_u32 = BoxDataTypeDowncast::<u32>::downcast_ref(&data_u32).copied();
+        let json = "{\n  \"one\": 1,\n  \"two\": { \"a\": [1] },\n  \"th\\u0072ee\": null\n}";
+        let type_map = type_reg
+            .deserialize_map_with_unknowns_positioned(json)
+            .unwrap();
 
-        assert_eq!(Some(1), data_u32);
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+        assert_eq!(
+            Some(&(serde_json::json!({ "a": [1] }), 16)),
+            type_map.get_unknown_entry("two")
+        );
+        assert_eq!(
+            Some(&(serde_json::Value::Null, 39)),
+            type_map.get_unknown_entry("three")
+        );
+        assert!(json[16..].starts_with("\"two\""));
+        assert!(json[39..].starts_with("\"th"));
     }
 
+    #[cfg(feature = "json")]
     #[test]
-    fn deserialize_map() {
+    fn deserialize_map_with_unknowns_positioned_integer_keys() {
+        let mut type_reg = TypeReg::<u32>::new();
+        type_reg.register::<u32>(1);
+
+        let json = r#"{ "1": 1, "2": 2 }"#;
+        let type_map = type_reg
+            .deserialize_map_with_unknowns_positioned(json)
+            .unwrap();
+
+        assert_eq!(Some(1), type_map.get::<u32, _>(&1).copied());
+        assert_eq!(
+            Some(&(serde_json::json!(2), 10)),
+            type_map.get_unknown_entry(&2)
+        );
+
+        let error = type_reg
+            .deserialize_map_with_unknowns_positioned(r#"{ "one": 1 }"#)
+            .map(|_| ())
+            .unwrap_err();
+        assert!(
+            error.to_string().contains("invalid value: string \"one\""),
+            "{error}"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn deserialize_map_with_unknowns_positioned_rejects_trailing_characters() {
+        let type_reg = TypeReg::<String>::new();
+
+        let error = type_reg
+            .deserialize_map_with_unknowns_positioned(r#"{ "one": 1 } x"#)
+            .map(|_| ())
+            .unwrap_err();
+
+        assert!(error.to_string().contains("trailing characters"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json_object_round_trips_to_json_object() {
         let mut type_reg = TypeReg::<String>::new();
         type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<u64>(String::from("two"));
-        type_reg.register::<A>(String::from("three"));
+        type_reg.register::<A>(String::from("a"));
 
-        let serialized = "---\n\
-        one: 1\n\
-        two: 2\n\
-        three: 3\n\
-        ";
+        let mut type_map = TypeMap::<String>::new();
+        type_map.insert(String::from("one"), 1u32);
+        type_map.insert(String::from("a"), A(2));
+
+        let json_object = type_map.to_json_object().unwrap();
+        let type_map: TypeMap<String> = type_reg.from_json_object(json_object).unwrap();
+
+        assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("a").cloned());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json_object_error_on_unregistered_key() {
+        let type_reg = TypeReg::<String>::new();
+
+        let json_object = serde_json::json!({ "one": 1 })
+            .as_object()
+            .cloned()
+            .unwrap();
+        let error = type_reg.from_json_object(json_object).unwrap_err();
+
+        assert!(error.to_string().contains("one"));
+    }
+
+    #[test]
+    fn with_key_transform() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("max_connections"));
+        let mut type_reg = type_reg.with_key_transform(KeyTransform::kebab_case);
+        type_reg.register::<A>(String::from("retryCount"));
 
+        let serialized = "max-connections: 10\nretry_count: 3\n";
         let deserializer = serde_yaml::Deserializer::from_str(serialized);
         let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
 
-        let data_u32 = type_map.get::<u32, _>("one").copied();
-        let data_u64 = type_map.get::<u64, _>("two").copied();
-        let data_a = type_map.get::<A, _>("three").copied();
+        assert_eq!(
+            Some(10u32),
+            type_map.get::<u32, _>("max-connections").copied()
+        );
+        assert_eq!(Some(A(3)), type_map.get::<A, _>("retry-count").copied());
+        assert_eq!(
+            Some(TypeNameLit(std::any::type_name::<u32>())),
+            type_reg.registered_type_name("max-connections")
+        );
+    }
 
-        assert_eq!(Some(1u32), data_u32);
-        assert_eq!(Some(2u64), data_u64);
-        assert_eq!(Some(A(3)), data_a);
+    #[test]
+    #[should_panic(expected = "Multiple registered keys transform to `\"max-connections\"`")]
+    fn with_key_transform_panics_on_colliding_keys() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("max_connections"));
+        type_reg.register::<A>(String::from("max-connections"));
+
+        let _type_reg = type_reg.with_key_transform(KeyTransform::kebab_case);
     }
 
     #[test]
-    fn deserialize_map_new_typed() {
-        let mut type_reg = TypeReg::<String, BoxDtDisplay>::new_typed();
+    fn with_key_transform_rekeys_type_layouts() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("max_connections"));
+        let type_reg = type_reg.with_key_transform(KeyTransform::kebab_case);
+
+        assert_eq!(
+            Some((std::mem::size_of::<u32>(), std::mem::align_of::<u32>())),
+            type_reg.type_layout("max-connections")
+        );
+        assert_eq!(None, type_reg.type_layout("max_connections"));
+    }
+
+    #[test]
+    fn with_key_transform_is_applied_once_when_registering() {
+        let mut trait_object_reg = TraitObjectReg::<dyn Area>::new();
+        trait_object_reg.register::<u32>(|n| Box::new(n));
+
+        let mut type_reg =
+            TypeReg::<String>::new().with_key_transform(|key: &str| format!("app.{key}"));
         type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<u64>(String::from("two"));
-        type_reg.register::<A>(String::from("three"));
+        type_reg.register_deprecated::<u32>(String::from("two"), "`two` is deprecated.");
+        let handle = type_reg.register_handle::<u32>(String::from("three"));
+        type_reg.register_trait_object(String::from("four"), trait_object_reg);
+        #[cfg(feature = "schemars")]
+        type_reg.register_with_schema::<u32>(String::from("five"));
 
-        let serialized = "---\n\
-        one: 1\n\
-        two: 2\n\
-        three: 3\n\
-        ";
+        let mut keys = type_reg.keys().map(String::as_str).collect::<Vec<_>>();
+        keys.sort_unstable();
 
-        let deserializer = serde_yaml::Deserializer::from_str(serialized);
-        let type_map: TypeMap<String, BoxDtDisplay> =
-            type_reg.deserialize_map(deserializer).unwrap();
+        #[cfg(not(feature = "schemars"))]
+        assert_eq!(vec!["app.four", "app.one", "app.three", "app.two"], keys);
+        #[cfg(feature = "schemars")]
+        assert_eq!(
+            vec!["app.five", "app.four", "app.one", "app.three", "app.two"],
+            keys
+        );
+        assert!(type_reg.unregister_handle(handle));
+    }
 
-        let data_u32 = type_map.get::<u32, _>("one").copied();
-        let data_u64 = type_map.get::<u64, _>("two").copied();
-        let data_a = type_map.get::<A, _>("three").copied();
+    #[test]
+    fn with_key_transform_rekeys_deprecations() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_deprecated::<u32>(
+            String::from("max_connections"),
+            "`max_connections` is deprecated.",
+        );
+        let type_reg = type_reg.with_key_transform(KeyTransform::kebab_case);
 
-        assert_eq!(Some(1u32), data_u32);
-        assert_eq!(Some(2u64), data_u64);
-        assert_eq!(Some(A(3)), data_a);
+        let deserializer = serde_yaml::Deserializer::from_str("max-connections: 10");
+        let (type_map, warnings) = type_reg
+            .deserialize_map_collecting_warnings::<_, serde_yaml::Error>(deserializer)
+            .unwrap();
+
+        assert_eq!(
+            Some(10u32),
+            type_map.get::<u32, _>("max-connections").copied()
+        );
+        assert_eq!(
+            vec![String::from("`max_connections` is deprecated.")],
+            warnings
+        );
     }
 
     #[test]
-    fn deserialize_map_with_capacity_typed() {
-        let mut type_reg = TypeReg::<String, BoxDtDisplay>::with_capacity_typed(3);
+    fn with_key_transform_deserialize_single() {
+        let mut type_reg = TypeReg::<String>::new().with_key_transform(KeyTransform::camel_case);
+        type_reg.register::<u32>(String::from("max_connections"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("max-connections: 10");
+        let data_u32 = type_reg.deserialize_single(deserializer).unwrap();
+        let data_u32 = BoxDataTypeDowncast::<u32>::downcast_ref(&data_u32).copied();
+
+        assert_eq!(Some(10), data_u32);
+    }
+
+    #[test]
+    fn unregister() {
+        let mut type_reg = TypeReg::<String>::new();
         type_reg.register::<u32>(String::from("one"));
         type_reg.register::<u64>(String::from("two"));
-        type_reg.register::<A>(String::from("three"));
 
-        let serialized = "---\n\
-        one: 1\n\
-        two: 2\n\
-        three: 3\n\
-        ";
+        assert!(type_reg.unregister("one"));
+        assert!(!type_reg.unregister("one"));
+
+        assert_eq!(1, type_reg.len());
+        assert_eq!(None, type_reg.registered_type_name("one"));
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        assert!(type_reg.deserialize_map(deserializer).is_err());
+    }
+
+    #[test]
+    fn unregister_handle_keeps_registration_of_different_type() {
+        let mut type_reg = TypeReg::<String>::new();
+        let handle = type_reg.register_handle::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("one"));
+
+        assert!(!type_reg.unregister_handle(handle));
+        assert_eq!(
+            Some(TypeNameLit(std::any::type_name::<u64>())),
+            type_reg.registered_type_name("one")
+        );
+    }
+
+    #[test]
+    fn register_scoped_unregisters_on_drop() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+
+        {
+            let mut type_reg = type_reg.register_scoped::<u64>(String::from("two"));
+            let type_reg = type_reg.register_scoped::<A>(String::from("three"));
+            assert_eq!(3, type_reg.len());
+        }
+
+        assert_eq!(1, type_reg.len());
+        assert_eq!(None, type_reg.registered_type_name("two"));
+        assert_eq!(None, type_reg.registered_type_name("three"));
+    }
+
+    #[test]
+    fn register_scoped_into_handle_keeps_registration() {
+        let mut type_reg = TypeReg::<String>::new();
+
+        let handle = type_reg
+            .register_scoped::<u32>(String::from("one"))
+            .into_handle();
+        assert_eq!("one", handle.key());
+        assert_eq!(1, type_reg.len());
+
+        assert!(type_reg.unregister_handle(handle));
+        assert!(type_reg.is_empty());
+    }
+
+    #[test]
+    fn merge() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<u32>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+        let mut type_reg_other = TypeReg::<String>::new();
+        type_reg_other.register::<u64>(String::from("two"));
+        type_reg_other.register::<A>(String::from("three"));
+
+        type_reg.merge(type_reg_other);
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2, three: 3 }");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: ~, two: 2, three: ~ }");
+        let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
+
+        assert_eq!(3, type_reg.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(2u64), type_map.get::<u64, _>("two").copied());
+        assert_eq!(Some(A(3)), type_map.get::<A, _>("three").copied());
+        assert_eq!(Some(Some(&2u64)), type_map_opt.get::<u64, _>("two"));
+    }
+
+    #[test]
+    fn type_reg_is_sync() {
+        fn assert_sync<T: Sync>() {}
+
+        assert_sync::<TypeReg<String>>();
+        assert_sync::<TypeReg<String, BoxDtDisplay>>();
+    }
+
+    #[test]
+    fn with_parent_type_reg_is_shareable_across_threads() {
+        let mut type_reg_parent = TypeReg::<String>::new();
+        type_reg_parent.register::<u32>(String::from("one"));
+        let type_reg = TypeReg::<String>::new().with_parent(Box::leak(Box::new(type_reg_parent)));
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+                let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+                assert_eq!(Some(1), type_map.get::<u32, _>("one").copied());
+            });
+        });
+    }
+
+    #[test]
+    fn with_parent_looks_up_parent_keys() {
+        let mut type_reg_parent = TypeReg::<String>::new();
+        type_reg_parent.register::<u32>(String::from("one"));
+        type_reg_parent.register::<u32>(String::from("two"));
+        let type_reg_parent = Box::leak(Box::new(type_reg_parent));
 
-        let deserializer = serde_yaml::Deserializer::from_str(serialized);
-        let type_map: TypeMap<String, BoxDtDisplay> =
-            type_reg.deserialize_map(deserializer).unwrap();
+        let mut type_reg = TypeReg::<String>::new().with_parent(type_reg_parent);
+        type_reg.register::<A>(String::from("two"));
 
-        let data_u32 = type_map.get::<u32, _>("one").copied();
-        let data_u64 = type_map.get::<u64, _>("two").copied();
-        let data_a = type_map.get::<A, _>("three").copied();
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: 2 }");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
 
-        assert_eq!(Some(1u32), data_u32);
-        assert_eq!(Some(2u64), data_u64);
-        assert_eq!(Some(A(3)), data_a);
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
+        assert_eq!(Some(A(2)), type_map.get::<A, _>("two").copied());
+        assert_eq!(1, type_reg.len());
+        assert_eq!(
+            Some(TypeNameLit("u32")),
+            type_reg.registered_type_name("one")
+        );
+
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: null, two: 2 }");
+        let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
+
+        assert_eq!(Some(None), type_map_opt.get::<u32, _>("one"));
+        assert_eq!(Some(Some(&A(2))), type_map_opt.get::<A, _>("two"));
     }
 
-    #[cfg(feature = "ordered")]
     #[test]
-    fn deserialize_single_has_good_error_message_when_type_not_registered() {
-        let mut type_reg = TypeReg::<String>::new();
-        type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<A>(String::from("three"));
+    fn with_parent_error_lists_parent_keys() {
+        let mut type_reg_parent = TypeReg::<String>::new();
+        type_reg_parent.register::<u32>(String::from("one"));
 
-        let deserializer = serde_yaml::Deserializer::from_str("two: 2");
-        let error = type_reg.deserialize_single(deserializer).unwrap_err();
-        assert_eq!(
-            r#"Type key `"two"` not registered in type registry.
-Available types are:
+        let mut type_reg =
+            TypeReg::<String>::new().with_parent(Box::leak(Box::new(type_reg_parent)));
+        type_reg.register::<u32>(String::from("two"));
 
-- "one"
-- "three"
+        let deserializer = serde_yaml::Deserializer::from_str("three: 3");
+        let error = type_reg
+            .deserialize_map(deserializer)
+            .map(|_: TypeMap<String>| ())
+            .unwrap_err()
+            .to_string();
 
-"#,
-            format!("{error}")
-        );
+        assert!(error.contains("- \"two\"\n- \"one\"\n"), "{error}");
     }
 
-    #[cfg(feature = "ordered")]
     #[test]
-    fn deserialize_map_opt_has_good_error_message_when_type_not_registered() {
+    fn try_merge() {
         let mut type_reg = TypeReg::<String>::new();
         type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<A>(String::from("three"));
-
-        let deserializer = serde_yaml::Deserializer::from_str("two: 2");
-        let error = type_reg.deserialize_map_opt(deserializer).unwrap_err();
-        assert_eq!(
-            r#"Type key `"two"` not registered in type registry.
-Available types are:
+        let mut type_reg_other = TypeReg::<String>::new();
+        type_reg_other.register::<u64>(String::from("two"));
 
-- "one"
-- "three"
+        let result = type_reg.try_merge(type_reg_other);
 
-"#,
-            format!("{error}")
-        );
+        assert!(result.is_ok());
+        assert_eq!(2, type_reg.len());
     }
 
     #[test]
-    fn deserialize_map_with_unknown_entries_yaml() {
+    fn try_merge_returns_collisions() {
         let mut type_reg = TypeReg::<String>::new();
         type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<A>(String::from("three"));
+        type_reg.register::<u32>(String::from("two"));
+        let mut type_reg_other = TypeReg::<String>::new();
+        type_reg_other.register::<u64>(String::from("two"));
+        type_reg_other.register::<A>(String::from("three"));
 
-        let serialized = "---\n\
-            one: 1\n\
-            two: 2\n\
-            three: 3\n\
-        ";
+        let collisions = type_reg.try_merge(type_reg_other).unwrap_err();
 
-        let deserializer = serde_yaml::Deserializer::from_str(serialized);
-        let type_map = type_reg
-            .deserialize_map_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
-            .unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str("two: 2");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
 
-        let data_u32 = type_map.get::<u32, _>("one").copied();
-        let data_u64 = type_map.get_unknown_entry("two").cloned();
-        let data_a = type_map.get::<A, _>("three").copied();
+        assert_eq!(vec![String::from("two")], collisions);
+        assert_eq!(2, type_reg.len());
+        assert_eq!(Some(2u32), type_map.get::<u32, _>("two").copied());
+    }
 
-        assert_eq!(Some(1u32), data_u32);
-        assert_eq!(Some(A(3)), data_a);
+    #[test]
+    fn register_boxed_chooses_type_at_runtime() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_boxed(String::from("value"), |deserializer| {
+            match serde_yaml::Value::deserialize(deserializer)? {
+                serde_yaml::Value::Number(n) => n
+                    .as_u64()
+                    .map(|n| BoxDt::new(A(n as u32)))
+                    .ok_or_else(|| serde::de::Error::custom("expected an unsigned integer")),
+                value => Ok(BoxDt::new(format!("{value:?}"))),
+            }
+        });
+
+        let deserializer = serde_yaml::Deserializer::from_str("value: 1");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+        assert_eq!(Some(A(1)), type_map.get::<A, _>("value").copied());
 
+        let deserializer = serde_yaml::Deserializer::from_str("value: abc");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+        assert!(type_map.get::<String, _>("value").is_some());
         assert_eq!(
-            data_u64,
-            Some(serde_yaml::Value::Number(serde_yaml::Number::from(2u64)))
+            Some(TypeNameLit(std::any::type_name::<BoxDt>())),
+            type_reg.registered_type_name("value")
         );
-        assert_eq!(1, type_map.unknown_entries().len());
     }
 
     #[test]
-    fn deserialize_map_with_unknown_entries_json() {
+    fn register_boxed_deserializes_opt() {
         let mut type_reg = TypeReg::<String>::new();
-        type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<A>(String::from("three"));
-
-        let serialized = r#"
-            {
-                "one": 1,
-                "two": 2,
-                "three": 3
-            }
-        "#;
-
-        let mut deserializer = serde_json::Deserializer::from_str(serialized);
-        let type_map = type_reg
-            .deserialize_map_with_unknowns::<'_, serde_json::Value, _, _>(&mut deserializer)
-            .unwrap();
-
-        let data_u32 = type_map.get::<u32, _>("one").copied();
-        let data_u64 = type_map.get_unknown_entry("two").cloned();
-        let data_a = type_map.get::<A, _>("three").copied();
+        type_reg.register_boxed(String::from("one"), |deserializer| {
+            u32::deserialize(deserializer).map(BoxDt::new)
+        });
+        type_reg.register_boxed(String::from("two"), |deserializer| {
+            u32::deserialize(deserializer).map(BoxDt::new)
+        });
 
-        assert_eq!(Some(1u32), data_u32);
-        assert_eq!(Some(A(3)), data_a);
+        let deserializer = serde_yaml::Deserializer::from_str("{ one: 1, two: ~ }");
+        let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
 
         assert_eq!(
-            data_u64,
-            Some(serde_json::Value::Number(serde_json::Number::from(2u64)))
+            Some(Some(1u32)),
+            type_map_opt.get::<u32, _>("one").map(|one| one.copied())
+        );
+        assert_eq!(
+            Some(None),
+            type_map_opt.get::<u32, _>("two").map(|two| two.copied())
         );
-        assert_eq!(1, type_map.unknown_entries().len());
     }
 
     #[test]
-    fn deserialize_map_opt() {
+    fn register_no_opt_deserializes_map() {
         let mut type_reg = TypeReg::<String>::new();
-        type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<u64>(String::from("two"));
-        type_reg.register::<A>(String::from("three"));
-
-        let serialized = "---\n\
-        one: 1\n\
-        two: 2\n\
-        three: null\n\
-        ";
-
-        let deserializer = serde_yaml::Deserializer::from_str(serialized);
-        let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
+        type_reg.register_no_opt::<u32>(String::from("one"));
 
-        let data_u32 = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
-        let data_u64 = type_map_opt.get::<u64, _>("two").map(|two| two.copied());
-        let data_a = type_map_opt
-            .get::<A, _>("three")
-            .map(|three| three.copied());
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
 
-        assert_eq!(Some(Some(1u32)), data_u32);
-        assert_eq!(Some(Some(2u64)), data_u64);
-        assert_eq!(Some(None), data_a);
+        assert_eq!(1, type_reg.len());
+        assert_eq!(Some(1u32), type_map.get::<u32, _>("one").copied());
     }
 
     #[test]
-    fn deserialize_map_opt_with_unknown_entries_yaml() {
+    fn register_no_opt_errors_when_deserializing_map_opt() {
         let mut type_reg = TypeReg::<String>::new();
-        type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<A>(String::from("three"));
-        type_reg.register::<u8>(String::from("four"));
+        type_reg.register_no_opt::<u32>(String::from("one"));
 
-        let serialized = "---\n\
-            one: 1\n\
-            two: 2\n\
-            three: 3\n\
-            four: null\n\
-            five: null\n\
-        ";
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        let error = type_reg
+            .deserialize_map_opt(deserializer)
+            .map(|_: TypeMapOpt<String>| ())
+            .unwrap_err();
 
-        let deserializer = serde_yaml::Deserializer::from_str(serialized);
-        let type_map_opt = type_reg
+        assert!(error.to_string().contains(
+            "Type key `\"one\"` was registered as `u32` using `register_no_opt`, \
+            so it cannot be deserialized into a `TypeMapOpt`."
+        ));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        let error = type_reg
             .deserialize_map_opt_with_unknowns::<'_, serde_yaml::Value, _, _>(deserializer)
-            .unwrap();
+            .map(|_| ())
+            .unwrap_err();
 
-        let one = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
-        let two = type_map_opt
-            .get_unknown_entry("two")
-            .map(|two| two.cloned());
-        let three = type_map_opt
-            .get::<A, _>("three")
-            .map(|three| three.copied());
-        let four = type_map_opt.get::<u8, _>("four").map(|four| four.copied());
-        let five = type_map_opt
-            .get_unknown_entry("five")
-            .map(|five| five.cloned());
+        assert!(error.to_string().contains("using `register_no_opt`"));
+    }
 
-        assert_eq!(Some(Some(1u32)), one);
-        assert_eq!(Some(Some(A(3))), three);
-        assert_eq!(Some(None::<u8>), four);
+    #[test]
+    fn register_replaces_register_no_opt() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_no_opt::<u32>(String::from("one"));
+        type_reg.register::<u64>(String::from("one"));
+
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        let type_map_opt: TypeMapOpt<String> = type_reg.deserialize_map_opt(deserializer).unwrap();
 
         assert_eq!(
-            two,
-            Some(Some(serde_yaml::Value::Number(serde_yaml::Number::from(
-                2u64
-            ))))
+            Some(Some(1u64)),
+            type_map_opt.get::<u64, _>("one").map(|one| one.copied())
         );
-        assert_eq!(five, Some(None));
-        assert_eq!(2, type_map_opt.unknown_entries().len());
     }
 
     #[test]
-    fn deserialize_map_opt_with_unknown_entries_json() {
+    fn merge_register_no_opt_removes_opt_seed() {
         let mut type_reg = TypeReg::<String>::new();
         type_reg.register::<u32>(String::from("one"));
-        type_reg.register::<A>(String::from("three"));
-        type_reg.register::<u8>(String::from("four"));
 
-        let serialized = r#"
-            {
-                "one": 1,
-                "two": 2,
-                "three": 3,
-                "four": null,
-                "five": null
-            }
-        "#;
+        let mut type_reg_other = TypeReg::<String>::new();
+        type_reg_other.register_no_opt::<u64>(String::from("one"));
+        type_reg.merge(type_reg_other);
 
-        let mut deserializer = serde_json::Deserializer::from_str(serialized);
-        let type_map_opt = type_reg
-            .deserialize_map_opt_with_unknowns::<'_, serde_json::Value, _, _>(&mut deserializer)
-            .unwrap();
+        let deserializer = serde_yaml::Deserializer::from_str("one: 1");
+        let error = type_reg
+            .deserialize_map_opt(deserializer)
+            .map(|_: TypeMapOpt<String>| ())
+            .unwrap_err();
 
-        let one = type_map_opt.get::<u32, _>("one").map(|one| one.copied());
-        let two = type_map_opt
-            .get_unknown_entry("two")
-            .map(|two| two.cloned());
-        let three = type_map_opt
-            .get::<A, _>("three")
-            .map(|three| three.copied());
-        let four = type_map_opt.get::<u8, _>("four").map(|four| four.copied());
-        let five = type_map_opt
-            .get_unknown_entry("five")
-            .map(|five| five.cloned());
+        assert_eq!(1, type_reg.len());
+        assert!(error.to_string().contains("using `register_no_opt`"));
+    }
 
-        assert_eq!(Some(Some(1u32)), one);
-        assert_eq!(Some(Some(A(3))), three);
-        assert_eq!(Some(None::<u8>), four);
+    #[test]
+    fn register_trait_object() {
+        let mut trait_object_reg = TraitObjectReg::<dyn Area>::new();
+        trait_object_reg.register::<A>(|a| Box::new(a));
+        trait_object_reg.register::<u32>(|n| Box::new(n));
+
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_trait_object(String::from("shape"), trait_object_reg);
 
+        let serialized = format!("shape: {{ '{}': 3 }}", std::any::type_name::<A>());
+        let deserializer = serde_yaml::Deserializer::from_str(&serialized);
+        let type_map: TypeMap<String> = type_reg.deserialize_map(deserializer).unwrap();
+
+        let shape = type_map.get::<Box<dyn Area>, _>("shape").unwrap();
+        assert_eq!(9, shape.area());
         assert_eq!(
-            two,
-            Some(Some(serde_json::Value::Number(serde_json::Number::from(
-                2u64
-            ))))
+            Some(TypeNameLit(std::any::type_name::<Box<dyn Area>>())),
+            type_reg.registered_type_name("shape")
         );
-        assert_eq!(five, Some(None));
-        assert_eq!(2, type_map_opt.unknown_entries().len());
     }
 
     #[test]
-    fn with_capacity() {
-        let type_reg = TypeReg::<String>::default();
-        assert_eq!(0, type_reg.capacity());
+    fn register_trait_object_error_on_unregistered_tag() {
+        let mut trait_object_reg = TraitObjectReg::<dyn Area>::new();
+        trait_object_reg.register::<u32>(|n| Box::new(n));
 
-        let type_reg = TypeReg::<String>::with_capacity(5);
-        assert!(type_reg.capacity() >= 5);
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register_trait_object(String::from("shape"), trait_object_reg);
+
+        let deserializer = serde_yaml::Deserializer::from_str("shape: { u64: 3 }");
+        let error = type_reg
+            .deserialize_map(deserializer)
+            .map(|_: TypeMap<String>| ())
+            .unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Type `\"u64\"` not registered in trait object registry."));
     }
 
     #[test]
-    fn deref_mut() {
+    fn type_layout() {
+        #[derive(Clone, Debug, Deserialize, Serialize)]
+        struct Large([u64; 8]);
+
         let mut type_reg = TypeReg::<String>::new();
-        assert!(type_reg.get_mut("one").is_none())
+        type_reg.register::<Large>(String::from("large"));
+        type_reg.register::<u8>(String::from("boxed"));
+        type_reg.register_boxed(String::from("boxed"), |deserializer| {
+            serde::Deserialize::deserialize(deserializer).map(BoxDt::new::<u8>)
+        });
+
+        assert_eq!(Some((64, 8)), type_reg.type_layout("large"));
+        assert_eq!(None, type_reg.type_layout("boxed"));
+
+        let mut type_reg_other = TypeReg::<String>::new();
+        type_reg_other.register_boxed(String::from("large"), |deserializer| {
+            serde::Deserialize::deserialize(deserializer).map(BoxDt::new::<u8>)
+        });
+        type_reg.merge(type_reg_other);
+        assert_eq!(None, type_reg.type_layout("large"));
+
+        type_reg.register::<u16>(String::from("large"));
+        assert!(type_reg.unregister("large"));
+        assert_eq!(None, type_reg.type_layout("large"));
+    }
+
+    #[test]
+    fn registered_type_name() {
+        let mut type_reg = TypeReg::<String>::new();
+        type_reg.register::<A>(String::from("one"));
+        type_reg.register::<u32>(String::from("two"));
+        type_reg.register::<u64>(String::from("two"));
+
+        assert_eq!(
+            Some(TypeNameLit(std::any::type_name::<A>())),
+            type_reg.registered_type_name("one")
+        );
+        assert_eq!(
+            Some(TypeNameLit("u64")),
+            type_reg.registered_type_name("two")
+        );
+        assert_eq!(None, type_reg.registered_type_name("three"));
     }
 
     #[test]
@@ -877,9 +5221,49 @@ Available types are:
         assert_eq!(r#"{"one": ".."}"#, format!("{type_reg:?}"));
     }
 
+    trait Area: dyn_clone::DynClone + erased_serde::Serialize + fmt::Debug + Send + Sync {
+        fn area(&self) -> u32;
+    }
+
+    dyn_clone::clone_trait_object!(Area);
+    erased_serde::serialize_trait_object!(Area);
+
+    impl Area for A {
+        fn area(&self) -> u32 {
+            self.0 * self.0
+        }
+    }
+
+    impl Area for u32 {
+        fn area(&self) -> u32 {
+            *self
+        }
+    }
+
     #[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
     struct A(u32);
 
+    /// Type that takes [`Slow::DURATION`] to deserialize.
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+    struct Slow(u32);
+
+    impl Slow {
+        const DURATION: Duration = Duration::from_millis(50);
+    }
+
+    impl<'de> Deserialize<'de> for Slow {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            std::thread::sleep(Self::DURATION);
+            u32::deserialize(deserializer).map(Slow)
+        }
+    }
+
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    struct Wrapper<T>(T);
+
     impl fmt::Display for A {
         #[cfg_attr(coverage_nightly, coverage(off))]
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -887,6 +5271,12 @@ Available types are:
         }
     }
 
+    #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+    enum Tree {
+        Leaf,
+        Node(Box<Tree>),
+    }
+
     #[test]
     fn a_coverage() {
         let a = Clone::clone(&A(0));